@@ -6,7 +6,7 @@
 
 // Get access to functions / datatypes of wasm module.
 // ---------------------------------------------------
-const { hello, Game } = wasm_bindgen;
+const { hello, Game, themes } = wasm_bindgen;
 
 // Gamepad array (they are appended on plug and play).
 // ---------------------------------------------------
@@ -24,9 +24,11 @@ function run() {
     // --------------------------------------------------
     let canvas = document.getElementById("main_canvas");
 
-    // Instantiate a game object from the rust module Game type.
-    // ---------------------------------------------------------
-    game = new Game(canvas);
+    // Instantiate a game object from the rust module Game type. With the #daily url hash, the
+    // daily challenge is played instead of the classic game. Switching the mode reloads the page.
+    // -------------------------------------------------------------------------------------------
+    game = location.hash === "#daily" ? Game.daily(canvas) : new Game(canvas);
+    window.onhashchange = () => location.reload();
 
     // Register onkeydown & onkeyup with callback that formwards the key to
     // the rust module keyboard_event() function.
@@ -60,11 +62,88 @@ function run() {
         }
     }, false);
 
+    // Fill the theme selection with the built-in themes. Switching it recolors the game right away (the
+    // game remembers the choice for the next visit).
+    // ---------------------------------------------------------------------------------------------------
+    let theme = document.getElementById("theme");
+    for (const name of themes()) {
+        let option = document.createElement("option");
+        option.value = name;
+        option.textContent = name;
+        theme.appendChild(option);
+    }
+    theme.value = game.theme();
+    theme.onchange = () => game.set_theme(theme.value);
+
+    // The reduced motion switch turns off screen shake, hit-stop and flashes (also remembered).
+    // ------------------------------------------------------------------------------------------
+    let reduced_motion = document.getElementById("reduced_motion");
+    reduced_motion.checked = game.reduced_motion();
+    reduced_motion.onchange = () => game.set_reduced_motion(reduced_motion.checked);
+
+    // The starfield in the background can be switched off (also remembered).
+    // -----------------------------------------------------------------------
+    let starfield = document.getElementById("starfield");
+    starfield.checked = game.starfield();
+    starfield.onchange = () => game.set_starfield(starfield.checked);
+
+    // Moving entities are drawn between the game ticks, unless they snap to the grid (also remembered).
+    // --------------------------------------------------------------------------------------------------
+    let snap_to_grid = document.getElementById("snap_to_grid");
+    snap_to_grid.checked = game.snap_to_grid();
+    snap_to_grid.onchange = () => game.set_snap_to_grid(snap_to_grid.checked);
+
+    // The render mode only changes how the canvas is drawn (fill_rect per virtual pixel, path per batch,
+    // or one image).
+    // ---------------------------------------------------------------------------------------------------
+    let render_mode = document.getElementById("render_mode");
+    render_mode.value = game.render_mode();
+    render_mode.onchange = () => game.set_render_mode(render_mode.value);
+
+    // Dirty rects only redraw the parts of the canvas, which changed since the last frame.
+    // -------------------------------------------------------------------------------------
+    let dirty_rects = document.getElementById("dirty_rects");
+    dirty_rects.checked = game.dirty_rects();
+    dirty_rects.onchange = () => game.set_dirty_rects(dirty_rects.checked);
+
+    // Save the last 10 seconds as animated GIF. The game plays them back from its replay, so it keeps
+    // running. The button gives up the focus again, so space still shoots.
+    // -----------------------------------------------------------------------------------------------
+    let save_gif = document.getElementById("save_gif");
+    save_gif.onclick = () => {
+        let gif = game.capture_gif(2, 1);
+        let link = document.createElement("a");
+        link.href = URL.createObjectURL(new Blob([gif], { type: "image/gif" }));
+        link.download = "rinvaders.gif";
+        link.click();
+        URL.revokeObjectURL(link.href);
+        save_gif.blur();
+    };
+
+    // Show the achievements, and refresh the list every second to pick up new unlocks.
+    // ---------------------------------------------------------------------------------
+    update_trophies();
+    window.setInterval(update_trophies, 1000);
+
     // Request the first game loop (animation frame).
     // ----------------------------------------------
     window.requestAnimationFrame(update);
 }
 
+// Fill the trophy list with all achievements from the game.
+// ----------------------------------------------------------
+function update_trophies() {
+    let list = document.getElementById("trophies");
+    list.innerHTML = "";
+
+    for (const a of game.achievements()) {
+        let item = document.createElement("li");
+        item.className = a.unlocked ? "unlocked" : "locked";
+        item.textContent = (a.unlocked ? "\u{1F3C6} " : "\u{1F512} ") + a.name + " - " + a.description;
+        list.appendChild(item);
+    }
+}
+
 function standard_gamepad(state, gamepad) {
     state.left |= gamepad.buttons[14].pressed;
     state.right |= gamepad.buttons[15].pressed;
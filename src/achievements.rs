@@ -0,0 +1,288 @@
+//! Achievements module.
+//!
+//! Achievements are unlocked by gameplay (kills, misses, hits and cleared waves). The **Tracker** is
+//! the state machine: it is fed with the gameplay events, keeps the progress of every achievement, and
+//! queues the fresh unlocks, so the game can show a toast for each of them. The **Achievements**
+//! subscriber wraps it, and persists the unlocks in the browser local storage, so they survive reloads.
+
+use crate::events::{GameEvent, Subscriber};
+use crate::storage;
+use crate::waves::WaveResult;
+use js_sys::{Array, Object, Reflect};
+use wasm_bindgen::JsValue;
+
+/// Local storage key, under which the ids of unlocked achievements are stored (comma separated).
+const STORAGE_KEY: &str = "rinvaders.achievements";
+
+/// Static definition of an achievement.
+pub struct Achievement {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// All achievements, which can be unlocked.
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_blood",
+        name: "First blood",
+        description: "Destroy your first enemy.",
+    },
+    Achievement {
+        id: "sharpshooter",
+        name: "Sharpshooter",
+        description: "Destroy 100 enemies in a row without a miss.",
+    },
+    Achievement {
+        id: "veteran",
+        name: "Veteran",
+        description: "Clear wave 10.",
+    },
+    Achievement {
+        id: "flawless",
+        name: "Flawless",
+        description: "Clear a wave without letting an enemy through.",
+    },
+];
+
+/// Index of the achievements in **ACHIEVEMENTS**.
+const FIRST_BLOOD: usize = 0;
+const SHARPSHOOTER: usize = 1;
+const VETERAN: usize = 2;
+const FLAWLESS: usize = 3;
+
+/// Kills in a row required for the sharpshooter achievement.
+const SHARPSHOOTER_STREAK: u32 = 100;
+
+/// Wave, which has to be cleared for the veteran achievement.
+const VETERAN_WAVE: u32 = 10;
+
+/// Achievement tracker. Contains the unlock state of every achievement, the progress for the ones
+/// which require it, and the queue of fresh unlocks.
+pub struct Tracker {
+    unlocked: [bool; ACHIEVEMENTS.len()],
+    kill_streak: u32,
+    flawless: bool,
+    pending: Vec<&'static Achievement>,
+}
+
+impl Tracker {
+    /// Create new tracker with the achievements of the ids in *unlocked* already unlocked (unknown ids
+    /// are ignored).
+    pub fn new<'a>(unlocked: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut tracker = Self {
+            unlocked: [false; ACHIEVEMENTS.len()],
+            kill_streak: 0,
+            flawless: true,
+            pending: Vec::new(),
+        };
+
+        for id in unlocked {
+            if let Some(i) = ACHIEVEMENTS.iter().position(|a| a.id == id) {
+                tracker.unlocked[i] = true;
+            }
+        }
+        tracker
+    }
+
+    /// Iterate over all achievements together with their unlock state.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static Achievement, bool)> + '_ {
+        ACHIEVEMENTS.iter().zip(self.unlocked.iter().copied())
+    }
+
+    /// Take the next freshly unlocked achievement from the queue.
+    pub fn next_unlock(&mut self) -> Option<&'static Achievement> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(self.pending.remove(0))
+        }
+    }
+
+    /// Track the progress of the gameplay *event*, and unlock achievements. Returns if an achievement
+    /// was unlocked.
+    pub fn track(&mut self, event: &GameEvent) -> bool {
+        match *event {
+            GameEvent::EnemyKilled { .. } => {
+                self.kill_streak += 1;
+
+                let first = self.unlock(FIRST_BLOOD);
+                let streak = self.kill_streak >= SHARPSHOOTER_STREAK && self.unlock(SHARPSHOOTER);
+                first || streak
+            }
+            GameEvent::ProjectileMissed => {
+                self.kill_streak = 0;
+                false
+            }
+            GameEvent::EnemyEscaped | GameEvent::PlayerHit { .. } => {
+                self.flawless = false; //<- Both let an enemy through the wave.
+                false
+            }
+            GameEvent::WaveCleared(wave) => self.on_wave_cleared(wave),
+            GameEvent::ShotFired => false,
+        }
+    }
+
+    /// Unlock the wave based achievements, and start tracking the next wave.
+    fn on_wave_cleared(&mut self, wave: WaveResult) -> bool {
+        let flawless = self.flawless && wave.escaped == 0 && self.unlock(FLAWLESS);
+        let veteran = wave.number >= VETERAN_WAVE && self.unlock(VETERAN);
+
+        self.flawless = true;
+        flawless || veteran
+    }
+
+    /// Unlock achievement with index *i*. Does nothing, when it was already unlocked. Otherwise
+    /// the unlock is queued. Returns if it was unlocked now.
+    fn unlock(&mut self, i: usize) -> bool {
+        if self.unlocked[i] {
+            return false;
+        }
+
+        self.unlocked[i] = true;
+        self.pending.push(&ACHIEVEMENTS[i]);
+        true
+    }
+}
+
+/// Achievements of the player. A **Tracker**, whose unlocks are loaded from and persisted to local
+/// storage.
+pub struct Achievements {
+    tracker: Tracker,
+}
+
+impl Achievements {
+    /// Create new achievements, which persist the unlocks of *tracker*.
+    pub fn new(tracker: Tracker) -> Self {
+        Self { tracker }
+    }
+
+    /// Create new achievements, and load the already unlocked ones from local storage.
+    pub fn load() -> Self {
+        let stored = storage::load(STORAGE_KEY).unwrap_or_default();
+        Self::new(Tracker::new(stored.split(',')))
+    }
+
+    /// Take the next freshly unlocked achievement from the queue.
+    pub fn next_unlock(&mut self) -> Option<&'static Achievement> {
+        self.tracker.next_unlock()
+    }
+
+    /// Create a JS array with an object (`id`, `name`, `description`, `unlocked`) per achievement.
+    pub fn to_js(&self) -> Array {
+        self.tracker
+            .iter()
+            .map(|(a, unlocked)| {
+                let o = Object::new();
+                let _ = Reflect::set(&o, &"id".into(), &a.id.into());
+                let _ = Reflect::set(&o, &"name".into(), &a.name.into());
+                let _ = Reflect::set(&o, &"description".into(), &a.description.into());
+                let _ = Reflect::set(&o, &"unlocked".into(), &JsValue::from(unlocked));
+                JsValue::from(o)
+            })
+            .collect()
+    }
+
+    /// Write the ids of all unlocked achievements to local storage.
+    fn save(&self) {
+        let ids: Vec<&str> = self
+            .tracker
+            .iter()
+            .filter(|(_, unlocked)| *unlocked)
+            .map(|(a, _)| a.id)
+            .collect();
+        storage::store(STORAGE_KEY, &ids.join(","));
+    }
+}
+
+impl Subscriber for Achievements {
+    /// Track progress, and persist fresh unlocks.
+    fn on_event(&mut self, event: &GameEvent) {
+        if self.tracker.track(event) {
+            self.save();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entites::EnemyType;
+
+    const KILL: GameEvent = GameEvent::EnemyKilled {
+        x: 40,
+        y: 30,
+        kind: EnemyType::Star,
+    };
+
+    /// Ids of the achievements unlocked by feeding *events* into a fresh tracker, in unlock order.
+    fn unlocks(events: &[GameEvent]) -> Vec<&'static str> {
+        let mut tracker = Tracker::new(None);
+        for e in events {
+            tracker.track(e);
+        }
+        std::iter::from_fn(|| tracker.next_unlock())
+            .map(|a| a.id)
+            .collect()
+    }
+
+    fn cleared(number: u32, escaped: u32) -> GameEvent {
+        GameEvent::WaveCleared(WaveResult { number, escaped })
+    }
+
+    #[test]
+    fn streak_reset_by_miss() {
+        let mut events = vec![KILL; 99];
+        events.push(GameEvent::ProjectileMissed);
+        events.extend([KILL; 99]);
+        assert_eq!(unlocks(&events), ["first_blood"]);
+
+        events.push(KILL);
+        assert_eq!(unlocks(&events), ["first_blood", "sharpshooter"]); //<- 100 since the miss.
+    }
+
+    #[test]
+    fn veteran_at_wave_10() {
+        let events: Vec<_> = (1..10).map(|n| cleared(n, 1)).collect();
+        assert_eq!(unlocks(&events), [] as [&str; 0]);
+        assert_eq!(unlocks(&[cleared(10, 1)]), ["veteran"]);
+    }
+
+    #[test]
+    fn flawless_broken() {
+        assert_eq!(unlocks(&[KILL, cleared(1, 0)]), ["first_blood", "flawless"]);
+        assert_eq!(
+            unlocks(&[GameEvent::EnemyEscaped, cleared(1, 1)]),
+            [] as [&str; 0]
+        );
+        assert_eq!(
+            unlocks(&[GameEvent::PlayerHit { x: 40, y: 55 }, cleared(1, 0)]),
+            [] as [&str; 0]
+        );
+
+        // A broken wave does not break the next one.
+        // ------------------------------------------
+        assert_eq!(
+            unlocks(&[GameEvent::EnemyEscaped, cleared(1, 1), cleared(2, 0)]),
+            ["flawless"]
+        );
+    }
+
+    #[test]
+    fn unlocked_once() {
+        let mut tracker = Tracker::new(None);
+        assert!(tracker.track(&KILL));
+        assert!(!tracker.track(&KILL));
+        assert_eq!(tracker.next_unlock().map(|a| a.id), Some("first_blood"));
+        assert!(tracker.next_unlock().is_none());
+
+        // Stored unlocks are not unlocked again.
+        // --------------------------------------
+        let mut tracker = Tracker::new("flawless,unknown".split(','));
+        assert!(!tracker.track(&cleared(1, 0)));
+        assert!(tracker.next_unlock().is_none());
+        assert!(tracker
+            .iter()
+            .any(|(a, unlocked)| a.id == "flawless" && unlocked));
+    }
+}
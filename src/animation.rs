@@ -0,0 +1,232 @@
+//! Animation module.
+//!
+//! An animation **Clip** is a sequence of sprite frames, each shown for its own duration, and a **Mode**
+//! which tells what happens at the end of the sequence. Clips are defined together with the sprites in the
+//! sprite sheet. Any entity can play a clip by keeping an **Animation** (the playback state), advancing it
+//! with the elapsed time, and drawing the sprite frame it selects.
+
+/// What happens, when the last frame of a clip is over.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Mode {
+    /// Start over with the first frame.
+    Loop,
+    /// Stop, and keep showing the last frame.
+    Once,
+    /// Play the frames backwards to the first one, and then forwards again.
+    PingPong,
+}
+
+/// An animation clip. The sprite frame indices with the duration (in ms) to show them, and the mode.
+pub struct Clip {
+    name: String,
+    mode: Mode,
+    frames: Vec<(usize, u32)>,
+}
+
+impl Clip {
+    /// Create new clip called *name* with *mode* and *frames* (sprite frame index and duration in ms).
+    /// Every duration has to be at least 1 ms.
+    pub fn new(name: &str, mode: Mode, frames: Vec<(usize, u32)>) -> Self {
+        assert!(!frames.is_empty() && frames.iter().all(|f| f.1 > 0));
+        Self {
+            name: name.to_owned(),
+            mode,
+            frames,
+        }
+    }
+
+    /// Name of the clip.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The frames (sprite frame index and duration).
+    pub fn frames(&self) -> &[(usize, u32)] {
+        &self.frames
+    }
+}
+
+/// Playback state of a clip. Contains the clip, the current position in it, the time the current frame
+/// is shown already, the direction (for ping-pong), and if a one-shot clip is finished.
+pub struct Animation {
+    clip: &'static Clip,
+    index: usize,
+    elapsed: u32,
+    forward: bool,
+    finished: bool,
+}
+
+impl Animation {
+    /// Start playing *clip* from the first frame.
+    pub fn new(clip: &'static Clip) -> Self {
+        Self {
+            clip,
+            index: 0,
+            elapsed: 0,
+            forward: true,
+            finished: false,
+        }
+    }
+
+    /// The sprite frame, which has to be drawn right now.
+    pub fn frame(&self) -> usize {
+        self.clip.frames[self.index].0
+    }
+
+    /// Advance the animation by *dt* ms. Works like **Timer::check()**: the *on_complete* callback is called
+    /// every time the clip completes (the one-shot clip is over, or a loop / ping-pong cycle is done), and
+    /// the return value is `Some(result_of_callback)` (the last one), or `None`, when nothing completed.
+    pub fn advance<F, T>(&mut self, dt: u32, mut on_complete: F) -> Option<T>
+    where
+        F: FnMut() -> T,
+    {
+        if self.finished {
+            return None;
+        }
+
+        let frames = &self.clip.frames;
+        let last = frames.len() - 1;
+        let mut result = None;
+
+        self.elapsed += dt;
+        while self.elapsed >= frames[self.index].1 {
+            self.elapsed -= frames[self.index].1;
+
+            // Step to the next frame depending on the mode, and check if the clip completed.
+            // ---------------------------------------------------------------------------------
+            let completed = match self.clip.mode {
+                Mode::Once if self.index == last => {
+                    self.finished = true;
+                    self.elapsed = 0;
+                    true
+                }
+                Mode::Once => {
+                    self.index += 1;
+                    false
+                }
+                Mode::Loop => {
+                    self.index = if self.index == last {
+                        0
+                    } else {
+                        self.index + 1
+                    };
+                    self.index == 0
+                }
+                Mode::PingPong if last == 0 => true,
+                Mode::PingPong => {
+                    if self.index == last {
+                        self.forward = false;
+                    } else if self.index == 0 {
+                        self.forward = true;
+                    }
+
+                    if self.forward {
+                        self.index += 1;
+                        false
+                    } else {
+                        self.index -= 1;
+                        self.index == 0
+                    }
+                }
+            };
+
+            if completed {
+                result = Some(on_complete());
+            }
+            if self.finished {
+                break;
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clip with *mode* and *frames*, which lives as long as the test (animations need static clips).
+    fn clip(mode: Mode, frames: &[(usize, u32)]) -> &'static Clip {
+        Box::leak(Box::new(Clip::new("test", mode, frames.to_vec())))
+    }
+
+    /// Advance *animation* in steps of *dt* ms *steps* times. Returns the frame after every step, and
+    /// whether the clip completed in it.
+    fn play(animation: &mut Animation, dt: u32, steps: usize) -> Vec<(usize, bool)> {
+        (0..steps)
+            .map(|_| {
+                let completed = animation.advance(dt, || ()).is_some();
+                (animation.frame(), completed)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn loop_mode() {
+        let mut animation = Animation::new(clip(Mode::Loop, &[(0, 100), (1, 50)]));
+        assert_eq!(animation.frame(), 0);
+        assert_eq!(animation.advance(99, || ()), None);
+        assert_eq!(animation.frame(), 0);
+        assert_eq!(animation.advance(1, || ()), None);
+        assert_eq!(animation.frame(), 1);
+        assert_eq!(animation.advance(50, || ()), Some(()));
+        assert_eq!(animation.frame(), 0);
+    }
+
+    #[test]
+    fn once_mode_completes_once() {
+        let mut animation = Animation::new(clip(Mode::Once, &[(0, 10), (1, 10), (2, 10)]));
+        assert_eq!(
+            play(&mut animation, 10, 3),
+            [(1, false), (2, false), (2, true)]
+        );
+
+        // The last frame stays, and the callback is not called again.
+        // -----------------------------------------------------------
+        let mut calls = 0;
+        assert_eq!(animation.advance(1000, || calls += 1), None);
+        assert_eq!(calls, 0);
+        assert_eq!(animation.frame(), 2);
+    }
+
+    #[test]
+    fn ping_pong_mode() {
+        let mut animation = Animation::new(clip(Mode::PingPong, &[(0, 10), (1, 10), (2, 10)]));
+        assert_eq!(
+            play(&mut animation, 10, 8),
+            [
+                (1, false),
+                (2, false),
+                (1, false),
+                (0, true),
+                (1, false),
+                (2, false),
+                (1, false),
+                (0, true)
+            ]
+        );
+
+        let mut single = Animation::new(clip(Mode::PingPong, &[(3, 10)]));
+        assert_eq!(play(&mut single, 10, 2), [(3, true), (3, true)]);
+    }
+
+    #[test]
+    fn dt_spanning_several_frames() {
+        let mut animation = Animation::new(clip(Mode::Loop, &[(0, 100), (1, 50)]));
+        let mut cycles = 0;
+        let result = animation.advance(400, || {
+            cycles += 1;
+            cycles
+        });
+        assert_eq!(result, Some(2)); //<- The result of the last call.
+        assert_eq!(cycles, 2);
+        assert_eq!(animation.frame(), 1); //<- 2 cycles of 150 ms, and the first frame of the third one.
+
+        let mut animation = Animation::new(clip(Mode::Once, &[(0, 10), (1, 10)]));
+        let mut calls = 0;
+        animation.advance(1000, || calls += 1);
+        assert_eq!(calls, 1);
+        assert_eq!(animation.frame(), 1);
+    }
+}
@@ -0,0 +1,110 @@
+//! Benchmark module. Compares the render modes of the **PixelScreen** in the browser.
+//!
+//! A scene with lots of enemies (far more than the game ever spawns) is drawn into a canvas with every
+//! **RenderMode** for a number of frames. The time is measured on the JS side of the canvas calls, so it
+//! is the cost of the calls crossing the wasm boundary, and the rasterization in rust, not the time the
+//! browser needs to composite the canvas.
+//!
+//! Every frame, some of the enemies move (like in the game, where they move on their own ticks). The
+//! modes, which can draw dirty rects, are measured with full redraws (before) and with dirty rects
+//! (after).
+
+use crate::entites::Enemy;
+use crate::rendering::{
+    Backend, Damage, Layer, Palette, PixelScreen, RenderMode, Role, Scene, SceneNode,
+};
+use crate::utils::Rng;
+use bumpalo::Bump;
+use web_sys::HtmlCanvasElement;
+
+/// Every frame, one in this many enemies moves.
+const MOVING: usize = 8;
+
+/// The enemies of the benchmark scene.
+struct Crowd {
+    enemies: Vec<Enemy>,
+}
+
+impl Crowd {
+    /// Create new crowd of *count* enemies at random positions all over the screen.
+    fn new(count: u32) -> Self {
+        let mut rng = Rng::new(0xbe4c_4a11);
+        let enemies = (0..count)
+            .map(|_| {
+                let mut e = Enemy::new_random(&mut rng);
+                for _ in 0..rng.rand(57) {
+                    e.tick();
+                }
+                e
+            })
+            .collect();
+
+        Self { enemies }
+    }
+
+    /// Move the enemies of *frame* one pixel down (unless they reached the bottom).
+    fn step(&mut self, frame: u32) {
+        let moving = self.enemies.iter_mut().skip(frame as usize % MOVING);
+        for e in moving.step_by(MOVING).filter(|e| !e.needs_removal()) {
+            e.tick();
+        }
+    }
+}
+
+impl SceneNode for Crowd {
+    /// Submit all enemies with the enemy PSO.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        for e in self.enemies.iter() {
+            scene.submit(Layer::World, Enemy::pso(), e);
+        }
+    }
+}
+
+/// Draw a scene of *entities* enemies into *canvas* for *frames* frames with every render mode, with
+/// full redraws and with dirty rects (in the modes, which support them). Returns the name of every run
+/// (the mode, with a `+dirty_rects` suffix for dirty rects) with the average time per frame in ms.
+pub fn run(canvas: HtmlCanvasElement, entities: u32, frames: u32) -> Vec<(String, f64)> {
+    let palette = Palette::default_theme();
+    let background = palette.color(Role::Background);
+    let mut screen = PixelScreen::new(canvas);
+
+    let mut results = vec![];
+    for mode in RenderMode::VARIANTS.iter() {
+        for dirty in [false, true].iter() {
+            if *dirty && *mode == RenderMode::ImageData {
+                continue; //<- Always draws whole frames.
+            }
+
+            let mut crowd = Crowd::new(entities);
+            let mut damage = Damage::new();
+            let mut arena = Bump::new();
+            let mut draw = |screen: &mut PixelScreen, crowd: &Crowd| {
+                arena.reset();
+                let mut scene = Scene::new(&arena);
+                crowd.submit(&mut scene);
+                if *dirty {
+                    scene.draw_damaged(screen, palette, background, &mut damage);
+                } else {
+                    screen.clear(background);
+                    scene.draw(screen, palette);
+                }
+                screen.present();
+            };
+
+            screen.set_mode(*mode);
+            draw(&mut screen, &crowd); //<- Warm up (allocations, color cache, first full frame).
+
+            let start = js_sys::Date::now();
+            for frame in 0..frames {
+                crowd.step(frame);
+                draw(&mut screen, &crowd);
+            }
+            let elapsed = js_sys::Date::now() - start;
+
+            let suffix = if *dirty { "+dirty_rects" } else { "" };
+            let name = format!("{}{}", mode.name(), suffix);
+            results.push((name, elapsed / frames.max(1) as f64));
+        }
+    }
+    results
+}
@@ -0,0 +1,100 @@
+//! GIF capture frontend for rinvaders. Plays back a replay headless, and captures a range of its frames
+//! into an animated GIF (e.g. to attach it to a bug report, or to share a highlight).
+//!
+//! Usage: `rinvaders-gif [options] <replay> <out.gif>`, where the replay is the encoded string (like the
+//! terminal frontend prints it), or `-` to read it from stdin. Options:
+//!
+//! * `--from <frame>` and `--to <frame>`: the range of frames to capture (default: all of them). Frames
+//!   are the recorded frames of the replay (one per rendered frame of the run), not game ticks, so the
+//!   time they cover depends on the frame rate of the run.
+//! * `--scale <n>`: pixels per virtual pixel (default: 2).
+//! * `--skip <n>`: frames skipped after every captured one (default: 1).
+//! * `--theme <name>`: theme to draw with (default: `default`).
+//! * `--daily <yyyymmdd>`: the replay is a daily challenge run of that date.
+
+use rinvaders::headless::{GifSettings, Headless};
+use rinvaders::{Palette, Replay};
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+/// Usage line printed on errors.
+const USAGE: &str =
+    "usage: rinvaders-gif [--from <frame>] [--to <frame>] [--scale <n>] [--skip <n>] \
+    [--theme <name>] [--daily <yyyymmdd>] <replay | -> <out.gif>";
+
+/// Print *message* and the usage line to stderr, and exit with an error.
+fn fail(message: &str) -> ! {
+    eprintln!("rinvaders-gif: {}", message);
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+/// Parse the *value* of option *name* as number.
+fn number(name: &str, value: Option<String>) -> u32 {
+    value
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| fail(&format!("{} needs a number", name)))
+}
+
+fn main() {
+    let mut settings = GifSettings::default();
+    let (mut from, mut to) = (0, u32::MAX);
+    let mut palette = Palette::default_theme();
+    let mut daily = None;
+    let mut files = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => from = number(&arg, args.next()),
+            "--to" => to = number(&arg, args.next()),
+            "--scale" => settings.scale = number(&arg, args.next()),
+            "--skip" => settings.frame_skip = number(&arg, args.next()),
+            "--daily" => daily = Some(number(&arg, args.next())),
+            "--theme" => {
+                palette = args
+                    .next()
+                    .and_then(|name| Palette::find(&name))
+                    .unwrap_or_else(|| fail("unknown theme"));
+            }
+            _ if arg.starts_with("--") => fail(&format!("unknown option {}", arg)),
+            _ => files.push(arg),
+        }
+    }
+
+    let (replay, out) = match &files[..] {
+        [replay, out] => (replay, out),
+        _ => fail("expected a replay and an output file"),
+    };
+
+    let replay = if replay == "-" {
+        let mut s = String::new();
+        if io::stdin().read_to_string(&mut s).is_err() {
+            fail("can not read the replay from stdin");
+        }
+        s
+    } else {
+        replay.to_owned()
+    };
+    let replay = Replay::decode(replay.trim()).unwrap_or_else(|| fail("invalid replay"));
+
+    let mut game = match daily {
+        Some(date) => Headless::daily(replay, date),
+        None => Headless::new(replay),
+    };
+    game.set_palette(palette);
+
+    let gif = game.capture_gif(from..to, &settings);
+    if let Err(e) = fs::write(out, &gif) {
+        eprintln!("rinvaders-gif: can not write {}: {}", out, e);
+        process::exit(1);
+    }
+    println!(
+        "Captured frames {} to {} into {} ({} bytes).",
+        from,
+        game.frame(),
+        out,
+        gif.len()
+    );
+}
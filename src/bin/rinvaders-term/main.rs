@@ -0,0 +1,220 @@
+//! Terminal frontend for rinvaders. Plays the game in a terminal (e.g. over SSH) with the same
+//! simulation as the wasm build.
+//!
+//! The scene is printed with ANSI escape codes by the **TerminalScreen** backend (needs a terminal with
+//! true color support, and at least 80x30 characters). Input is read from stdin in raw mode.
+//!
+//! Controls: A / arrow left and D / arrow right to move, space / W / arrow up to shoot, T to switch to the
+//! next theme, M to toggle reduced motion, B to toggle the starfield background, Q to quit. With the
+//! `debug-overlay` feature, the backquote key toggles the debug overlay.
+//! When the game ends, the replay of the run is printed, so it can be played back headless.
+
+mod screen;
+
+use rinvaders::headless::Headless;
+use rinvaders::THEMES;
+use screen::TerminalScreen;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time between two frames (~60 FPS).
+const FRAME: Duration = Duration::from_millis(16);
+
+/// Terminals do not report key releases. A key counts as held for this many ms after it was pressed
+/// (or auto repeated).
+const HOLD_MS: u32 = 150;
+
+/// Run `stty` on the terminal connected to stdin with *args*. Returns its output.
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
+/// Read stdin byte by byte on a background thread. The bytes are sent through the returned channel.
+fn spawn_stdin_reader() -> Receiver<u8> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut buf = [0; 64];
+        let stdin = io::stdin();
+        let mut stdin = stdin.lock();
+        while let Ok(n) = stdin.read(&mut buf) {
+            if n == 0 || buf[..n].iter().any(|b| tx.send(*b).is_err()) {
+                break;
+            }
+        }
+    });
+
+    rx
+}
+
+/// Keyboard state. Timestamps of the last press of every key, the number of theme switches, reduced
+/// motion, starfield and debug overlay toggles requested, and the state of the escape sequence
+/// parser (arrow keys are sent as `ESC [ A` to `ESC [ D`, or with `O` instead of `[`).
+struct Keys {
+    left: Option<u32>,
+    right: Option<u32>,
+    shoot: Option<u32>,
+    theme: usize,
+    motion: usize,
+    starfield: usize,
+    debug: usize,
+    quit: bool,
+    escape: u8,
+}
+
+impl Keys {
+    /// Create new keyboard state with no key pressed.
+    fn new() -> Self {
+        Self {
+            left: None,
+            right: None,
+            shoot: None,
+            theme: 0,
+            motion: 0,
+            starfield: 0,
+            debug: 0,
+            quit: false,
+            escape: 0,
+        }
+    }
+
+    /// Process a *byte* read from stdin at timestamp *ts*.
+    fn feed(&mut self, ts: u32, byte: u8) {
+        self.escape = match (self.escape, byte) {
+            (_, 0x1b) => 1,
+            (1, b'[') | (1, b'O') => 2,
+            (2, b'A') | (0, b' ') | (0, b'w') | (0, b'W') => {
+                self.shoot = Some(ts);
+                0
+            }
+            (2, b'C') | (0, b'd') | (0, b'D') => {
+                self.right = Some(ts);
+                0
+            }
+            (2, b'D') | (0, b'a') | (0, b'A') => {
+                self.left = Some(ts);
+                0
+            }
+            (0, b't') | (0, b'T') => {
+                self.theme += 1;
+                0
+            }
+            (0, b'm') | (0, b'M') => {
+                self.motion += 1;
+                0
+            }
+            (0, b'b') | (0, b'B') => {
+                self.starfield += 1;
+                0
+            }
+            (0, b'`') => {
+                self.debug += 1;
+                0
+            }
+            (0, b'q') | (0, b'Q') | (0, 3) => {
+                self.quit = true; //<- 3 is Ctrl-C in raw mode.
+                0
+            }
+            _ => 0, //<- Unknown key or sequence.
+        };
+    }
+
+    /// Input state (*left*, *right*, *shoot*) at timestamp *ts*.
+    fn state(&self, ts: u32) -> (bool, bool, bool) {
+        let held = |pressed: Option<u32>| pressed.is_some_and(|p| ts - p < HOLD_MS);
+        (held(self.left), held(self.right), held(self.shoot))
+    }
+}
+
+fn main() {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() ^ d.as_secs() as u32)
+        .unwrap_or(0);
+
+    // Switch the terminal into raw mode (no line buffering and echo), and into the alternate screen with
+    // hidden cursor. The previous settings are restored at the end.
+    // ---------------------------------------------------------------------------------------------------
+    let saved = match stty(&["-g"]) {
+        Ok(s) if !s.is_empty() => s,
+        _ => {
+            eprintln!("rinvaders-term needs an interactive terminal.");
+            return;
+        }
+    };
+    let _ = stty(&["raw", "-echo"]);
+
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let _ = write!(out, "\x1b[?1049h\x1b[?25l\x1b[2J");
+
+    let input = spawn_stdin_reader();
+    let mut keys = Keys::new();
+    let mut game = Headless::live(seed);
+    let mut screen = TerminalScreen::new();
+    let start = Instant::now();
+
+    // The game loop. Process input, update, draw, and wait for the next frame. After the game is over,
+    // the last frame stays on screen until the player quits.
+    // -------------------------------------------------------------------------------------------------
+    while !keys.quit {
+        let frame_start = Instant::now();
+        let ts = start.elapsed().as_millis() as u32;
+
+        while let Ok(byte) = input.try_recv() {
+            keys.feed(ts, byte);
+        }
+
+        if keys.theme > 0 {
+            let current = THEMES.iter().position(|p| p.name == game.palette().name);
+            let next = (current.unwrap_or(0) + keys.theme) % THEMES.len();
+            game.set_palette(&THEMES[next]);
+            keys.theme = 0;
+        }
+
+        if keys.motion % 2 == 1 {
+            let mut settings = game.feedback_settings();
+            settings.reduced_motion = !settings.reduced_motion;
+            game.set_feedback_settings(settings);
+        }
+        keys.motion = 0;
+
+        if keys.starfield % 2 == 1 {
+            let mut settings = game.starfield_settings().clone();
+            settings.enabled = !settings.enabled;
+            game.set_starfield_settings(settings);
+        }
+        keys.starfield = 0;
+
+        #[cfg(feature = "debug-overlay")]
+        {
+            if keys.debug % 2 == 1 {
+                game.set_debug_overlay(!game.debug_overlay());
+            }
+        }
+        keys.debug = 0;
+
+        let (left, right, shoot) = keys.state(ts);
+        game.update(ts, left, right, shoot);
+        game.draw(&mut screen);
+
+        let _ = out.write_all(screen.ansi().as_bytes());
+        let _ = out.flush();
+
+        thread::sleep(FRAME.saturating_sub(frame_start.elapsed()));
+    }
+
+    let _ = write!(out, "\x1b[?25h\x1b[?1049l");
+    let _ = out.flush();
+    let _ = stty(&[&saved]);
+
+    println!("Score: {}", game.score());
+    println!("Replay: {}", game.replay().encode());
+}
@@ -0,0 +1,158 @@
+//! Terminal screen module. A rendering backend, which draws the scene into a grid of 80x60 cells (one
+//! cell per virtual pixel), and prints it with ANSI escape codes.
+//!
+//! Every character shows two vertically stacked cells of the grid with the upper half block `▀`: the
+//! foreground color is the upper cell, and the background color the lower one. So the 60 rows of the
+//! grid fit into 30 lines of the terminal. Text (e.g. score and FPS) is drawn with the bitmap font of
+//! the game, like the sprites.
+
+use rinvaders::{draw_line, Backend, CssColor, CssFont, Pos, Viewport};
+use std::fmt::Write;
+
+/// Size of the grid in cells.
+const COLS: usize = 80;
+const ROWS: usize = 60;
+
+/// Number of terminal lines (two grid rows per line).
+const LINES: usize = ROWS / 2;
+
+/// Background color until the first clear.
+const WHITE: CssColor = CssColor::new(255, 255, 255);
+
+/// The terminal backend. Contains the viewport into the cells, the color of every cell, the drawing
+/// state, and the buffer the escape codes are written to.
+pub struct TerminalScreen {
+    viewport: Viewport,
+    cells: Vec<CssColor>,
+    fill_color: CssColor,
+    font_size: u32,
+    alpha: u8,
+    state_stack: Vec<(CssColor, u32, u8)>,
+    out: String,
+}
+
+impl TerminalScreen {
+    /// Create new cleared screen.
+    pub fn new() -> Self {
+        Self {
+            viewport: Viewport::fit(COLS as u32, ROWS as u32),
+            cells: vec![WHITE; COLS * ROWS],
+            fill_color: CssColor::new(0, 0, 0),
+            font_size: 10,
+            alpha: 255,
+            state_stack: vec![],
+            out: String::with_capacity(COLS * LINES * 16),
+        }
+    }
+
+    /// Create the escape codes, which print the screen (starting at the top left of the terminal).
+    /// Colors are only switched, when they change.
+    pub fn ansi(&mut self) -> &str {
+        self.out.clear();
+        self.out.push_str("\x1b[H");
+
+        for line in 0..LINES {
+            let mut current = None;
+
+            for col in 0..COLS {
+                let top = self.cells[line * 2 * COLS + col];
+                let bottom = self.cells[(line * 2 + 1) * COLS + col];
+
+                if current != Some((top, bottom)) {
+                    let (r1, g1, b1) = top.rgb();
+                    let (r2, g2, b2) = bottom.rgb();
+                    write!(
+                        self.out,
+                        "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+                        r1, g1, b1, r2, g2, b2
+                    )
+                    .unwrap();
+                    current = Some((top, bottom));
+                }
+                self.out.push('▀');
+            }
+
+            self.out.push_str("\x1b[0m");
+            if line + 1 < LINES {
+                self.out.push_str("\r\n"); //<- Raw mode does not return the carriage by itself.
+            }
+        }
+
+        &self.out
+    }
+}
+
+/// Range of cells along one axis from *start* to *start* + *len* (in cells), clamped to *max*.
+fn cell_span(start: f64, len: f64, max: usize) -> std::ops::Range<usize> {
+    let clamp = |v: f64| v.max(0.0).min(max as f64) as usize;
+    clamp(start)..clamp(start + len)
+}
+
+/// Blend *src* over *dst* with *alpha*.
+fn blend(dst: CssColor, src: CssColor, alpha: u8) -> CssColor {
+    let a = alpha as u32;
+    let mix = |d: u8, s: u8| ((s as u32 * a + d as u32 * (255 - a) + 127) / 255) as u8;
+    let (dr, dg, db) = dst.rgb();
+    let (sr, sg, sb) = src.rgb();
+    CssColor::new(mix(dr, sr), mix(dg, sg), mix(db, sb))
+}
+
+impl Backend for TerminalScreen {
+    fn clear(&mut self, background: CssColor) {
+        for c in self.cells.iter_mut() {
+            *c = background;
+        }
+    }
+
+    /// Fill the covered cells. Rects smaller than half a cell vanish.
+    fn draw_rect(&mut self, pos: Pos, width: f64, height: f64) {
+        let (x, y, w, h) = self.viewport.rect(pos, width, height);
+        let cols = cell_span(x, w, COLS);
+        for row in cell_span(y, h, ROWS) {
+            for col in cols.clone() {
+                let cell = &mut self.cells[row * COLS + col];
+                *cell = blend(*cell, self.fill_color, self.alpha);
+            }
+        }
+    }
+
+    /// Draw *text* with the bitmap font in the current font size (a font pixel covers whole cells).
+    fn draw_text(&mut self, text: &str, pos: Pos) {
+        let size = self.font_size;
+        draw_line(self, text, pos, size);
+    }
+
+    fn set_camera(&mut self, offset: Pos) {
+        self.viewport.set_camera(offset);
+    }
+
+    fn set_fill_color(&mut self, color: CssColor) {
+        self.fill_color = color;
+    }
+
+    /// Only the size of fonts is used by the bitmap font.
+    fn set_font(&mut self, font: CssFont) {
+        self.font_size = font.size();
+    }
+
+    fn font_size(&self) -> u32 {
+        self.font_size
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        self.alpha = alpha;
+    }
+
+    fn save(&mut self) {
+        self.state_stack
+            .push((self.fill_color, self.font_size, self.alpha));
+    }
+
+    fn restore(&mut self) {
+        if let Some((color, font_size, alpha)) = self.state_stack.pop() {
+            self.fill_color = color;
+            self.font_size = font_size;
+            self.alpha = alpha;
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Daily challenge module.
+//!
+//! In the daily challenge everybody plays the same run on the same (UTC) day. The seed of the world
+//! and a set of rule modifiers are derived from the calendar date.
+//!
+//! At the end of a run, a compact share code is created. It encodes the date, the score, the accuracy
+//! and a hash of the replay. Together with the replay, the code can be verified by simulating the
+//! replay again, and comparing the recomputed results with the ones in the code.
+
+use crate::entites::Score;
+use crate::feedback::FeedbackSettings;
+use crate::replay::{Replay, ReplayPlayer};
+use crate::rules::{Modifier, Rules};
+use crate::session::Session;
+use crate::starfield::StarfieldSettings;
+use crate::stats::SessionStats;
+use crate::utils::{self, Rng};
+use js_sys::Date;
+
+/// Prefix of every share code (also used as format version).
+const CODE_PREFIX: &str = "rinv1";
+
+/// Today's date (UTC) as `yyyymmdd` number.
+pub fn today() -> u32 {
+    let d = Date::new_0();
+    d.get_utc_full_year() * 10000 + (d.get_utc_month() + 1) * 100 + d.get_utc_date()
+}
+
+/// The world seed for *date*.
+pub fn seed(date: u32) -> u32 {
+    utils::fnv1a(format!("daily-{}", date).as_bytes())
+}
+
+/// The rule modifiers for *date*. Every day has one or two different modifiers.
+pub fn modifiers(date: u32) -> Vec<Modifier> {
+    let mut rng = Rng::new(seed(date) ^ 0x5eed_da11);
+    let variants = Modifier::VARIANTS;
+
+    let first = variants[rng.rand(variants.len() as u32 - 1) as usize];
+    let second = variants[rng.rand(variants.len() as u32 - 1) as usize];
+
+    if first == second {
+        vec![first]
+    } else {
+        vec![first, second]
+    }
+}
+
+/// The rules for *date* (classic rules with the modifiers of the day applied).
+pub fn rules(date: u32) -> Rules {
+    let mut rules = Rules::default();
+    for m in modifiers(date) {
+        m.apply(&mut rules);
+    }
+    rules
+}
+
+/// Accuracy of a run as integer per mille (so it survives the round trip through a share code).
+fn accuracy_per_mille(stats: &SessionStats) -> u32 {
+    if stats.shots_fired() == 0 {
+        0
+    } else {
+        stats.hits() * 1000 / stats.shots_fired()
+    }
+}
+
+/// Results of a daily challenge run.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DailyResult {
+    pub date: u32,
+    pub score: i32,
+    pub accuracy: u32,
+    pub replay_hash: u32,
+}
+
+impl DailyResult {
+    /// Create the result of a finished run from the final *score* and *stats*, and its *replay*.
+    pub fn new(date: u32, score: &Score, stats: &SessionStats, replay: &Replay) -> Self {
+        Self {
+            date,
+            score: score.value(),
+            accuracy: accuracy_per_mille(stats),
+            replay_hash: utils::fnv1a(replay.encode().as_bytes()),
+        }
+    }
+
+    /// Encode the result as share code, e.g. `rinv1:20190131:-25:512:9f3a1c2e`.
+    pub fn share_code(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{:08x}",
+            CODE_PREFIX, self.date, self.score, self.accuracy, self.replay_hash
+        )
+    }
+
+    /// Parse a share code created by **share_code()**.
+    pub fn parse(code: &str) -> Option<Self> {
+        let mut parts = code.trim().split(':');
+        if parts.next()? != CODE_PREFIX {
+            return None;
+        }
+
+        let result = Self {
+            date: parts.next()?.parse().ok()?,
+            score: parts.next()?.parse().ok()?,
+            accuracy: parts.next()?.parse().ok()?,
+            replay_hash: u32::from_str_radix(parts.next()?, 16).ok()?,
+        };
+
+        if parts.next().is_some() {
+            None
+        } else {
+            Some(result)
+        }
+    }
+}
+
+/// Reasons, why the verification of a share code failed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum VerifyError {
+    /// The share code could not be parsed.
+    MalformedCode,
+    /// The replay could not be decoded.
+    MalformedReplay,
+    /// The hash in the share code does not belong to the replay.
+    HashMismatch,
+    /// The replay was not recorded with the seed of the daily challenge date.
+    SeedMismatch,
+    /// Simulating the replay results in a different score or accuracy.
+    ResultMismatch,
+}
+
+/// Verify a share *code* with the encoded *replay* of the run. This simulates the replay with the
+/// rules of the challenge date, and checks that it results in the score and accuracy of the code.
+pub fn verify(code: &str, replay: &str) -> Result<DailyResult, VerifyError> {
+    let claimed = DailyResult::parse(code).ok_or(VerifyError::MalformedCode)?;
+    if utils::fnv1a(replay.as_bytes()) != claimed.replay_hash {
+        return Err(VerifyError::HashMismatch);
+    }
+
+    let replay = Replay::decode(replay).ok_or(VerifyError::MalformedReplay)?;
+    if replay.seed() != seed(claimed.date) {
+        return Err(VerifyError::SeedMismatch);
+    }
+
+    // Play back the whole replay frame by frame through the same session logic, which decides the
+    // results of a live run.
+    // ---------------------------------------------------------------------------------------------
+    let mut session = Session::new(
+        replay.seed(),
+        rules(claimed.date),
+        FeedbackSettings::default(),
+        StarfieldSettings::default(),
+    );
+    let mut player = ReplayPlayer::new(replay);
+    while let Some(f) = player.next_frame() {
+        if session.simulate(f.ts, &f.input(), &mut []) {
+            break;
+        }
+    }
+
+    let recomputed = DailyResult {
+        date: claimed.date,
+        score: session.score().value(),
+        accuracy: accuracy_per_mille(session.stats()),
+        replay_hash: claimed.replay_hash,
+    };
+
+    if recomputed == claimed {
+        Ok(recomputed)
+    } else {
+        Err(VerifyError::ResultMismatch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::headless::Headless;
+    use crate::input::Input;
+
+    /// Date of the tested challenge.
+    const DATE: u32 = 20190131;
+
+    /// A scripted run of the challenge of *date*: sweep left and right while shooting most of the time.
+    fn run(date: u32) -> Replay {
+        let mut replay = Replay::new(seed(date));
+        for i in 0..3000u32 {
+            let sweep = i / 120 % 2 == 0;
+            replay.record(i * 16, &Input::from_state(sweep, !sweep, i % 5 != 0));
+        }
+        replay
+    }
+
+    /// The genuine share code and encoded replay of the scripted run (the result is computed by a
+    /// headless playback, independently of **verify()**).
+    fn genuine() -> (DailyResult, String) {
+        let replay = run(DATE);
+        let encoded = replay.encode();
+
+        let mut headless = Headless::daily(replay, DATE);
+        while headless.step() {}
+        let result = DailyResult {
+            date: DATE,
+            score: headless.score(),
+            accuracy: accuracy_per_mille(headless.stats()),
+            replay_hash: utils::fnv1a(encoded.as_bytes()),
+        };
+        (result, encoded)
+    }
+
+    #[test]
+    fn share_code_round_trip() {
+        let result = DailyResult {
+            date: DATE,
+            score: -25,
+            accuracy: 512,
+            replay_hash: 0x9f3a_1c2e,
+        };
+        assert_eq!(result.share_code(), "rinv1:20190131:-25:512:9f3a1c2e");
+        assert_eq!(DailyResult::parse(&result.share_code()), Some(result));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_codes() {
+        for code in [
+            "",
+            "rinv0:20190131:-25:512:9f3a1c2e",
+            "rinv1:20190131:-25:512",
+            "rinv1:20190131:-25:512:9f3a1c2e:1",
+            "rinv1:20190131:x:512:9f3a1c2e",
+            "rinv1:20190131:-25:512:xyz",
+        ]
+        .iter()
+        {
+            assert_eq!(DailyResult::parse(code), None, "{}", code);
+        }
+    }
+
+    #[test]
+    fn genuine_code_verifies() {
+        let (result, replay) = genuine();
+        assert!(result.accuracy > 0); //<- The run hits something, so the accuracy is checked too.
+        assert_eq!(verify(&result.share_code(), &replay), Ok(result));
+    }
+
+    #[test]
+    fn tampered_results_are_rejected() {
+        let (result, replay) = genuine();
+
+        let score = DailyResult {
+            score: result.score + 1,
+            ..result
+        };
+        assert_eq!(
+            verify(&score.share_code(), &replay),
+            Err(VerifyError::ResultMismatch)
+        );
+
+        let accuracy = DailyResult {
+            accuracy: result.accuracy + 1,
+            ..result
+        };
+        assert_eq!(
+            verify(&accuracy.share_code(), &replay),
+            Err(VerifyError::ResultMismatch)
+        );
+
+        let date = DailyResult {
+            date: DATE + 1,
+            ..result
+        };
+        assert_eq!(
+            verify(&date.share_code(), &replay),
+            Err(VerifyError::SeedMismatch)
+        );
+    }
+
+    #[test]
+    fn replay_hash_mismatch_is_rejected() {
+        let (result, _) = genuine();
+        let other = run(DATE + 1).encode();
+        assert_eq!(
+            verify(&result.share_code(), &other),
+            Err(VerifyError::HashMismatch)
+        );
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        let (result, replay) = genuine();
+        assert_eq!(
+            verify("rinv1:garbage", &replay),
+            Err(VerifyError::MalformedCode)
+        );
+
+        let broken = "!!!";
+        let code = DailyResult {
+            replay_hash: utils::fnv1a(broken.as_bytes()),
+            ..result
+        };
+        assert_eq!(
+            verify(&code.share_code(), broken),
+            Err(VerifyError::MalformedReplay)
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Debug overlay module. Shows the internals of the simulation on top of the game.
+//!
+//! The overlay outlines the collision box of every enemy (the bounds of its type), and the cell of every
+//! projectile. A panel in the top left corner lists (in lines, which fit into the virtual grid):
+//!
+//! * `P` projectiles, `E` enemies, `X` explosions, and `T` game ticks simulated in the last frame.
+//! * `G` the game tick of the world, and the fraction of the current tick.
+//! * `E` the enemy tick and interval, and `S` the spawn tick and interval.
+//! * `M` the movement and `F` the shoot timer deadline of the player.
+//! * `B` the issued and all bindings, `C` the draw calls and `R` the rects of the backend (when it has
+//!   render statistics).
+//!
+//! The overlay only exists with the `debug-overlay` cargo feature. Without it, it is compiled out
+//! completely (together with the accessors of the entities it reads), so release builds do not carry it.
+
+use crate::effects::Effects;
+use crate::rendering::{
+    self, Align, Backend, BindCounters, CssFont, DrawCounters, Footprint, Layer, Pos, Pso,
+    Renderable, Role, Scene, SceneNode,
+};
+use crate::world::World;
+use std::fmt::Write;
+
+/// Font of the panel.
+const FONT: CssFont = CssFont::monospace(10);
+
+/// Position of the panel text (below the widgets in the top left corner of the HUD).
+const PANEL: (f64, f64) = (1.0, 18.0);
+
+/// Width of the outlines in virtual pixels.
+const LINE: f64 = 0.2;
+
+lazy_static! {
+    /// PSO of the collision boxes (warning fill color).
+    static ref BOX_PSO: Pso = Pso {
+        fill_color: Some(Role::Warning),
+        ..Default::default()
+    };
+
+    /// PSO of the projectile markers (accent fill color).
+    static ref SHOT_PSO: Pso = Pso {
+        fill_color: Some(Role::Accent),
+        ..Default::default()
+    };
+
+    /// PSO of the panel background (translucent background color).
+    static ref PANEL_PSO: Pso = Pso {
+        fill_color: Some(Role::Background),
+        alpha: Some(200),
+        ..Default::default()
+    };
+
+    /// PSO of the panel text (UI fill color, panel font).
+    static ref TEXT_PSO: Pso = Pso {
+        fill_color: Some(Role::Ui),
+        font: Some(FONT),
+        ..Default::default()
+    };
+}
+
+/// Outline of a rect at *pos* with *width* and *height* in virtual pixels.
+struct Outline {
+    pos: Pos,
+    width: f64,
+    height: f64,
+}
+
+impl Renderable for Outline {
+    /// Draw the four edges inside of the rect.
+    fn draw(&self, backend: &mut dyn Backend) {
+        let (x, y) = (self.pos.x(), self.pos.y());
+        backend.draw_rect(Pos::new(x, y), self.width, LINE);
+        backend.draw_rect(Pos::new(x, y + self.height - LINE), self.width, LINE);
+        backend.draw_rect(Pos::new(x, y), LINE, self.height);
+        backend.draw_rect(Pos::new(x + self.width - LINE, y), LINE, self.height);
+    }
+
+    fn footprint(&self) -> Option<Footprint> {
+        Some(Footprint::covering(self.pos, self.width, self.height, 0))
+    }
+}
+
+/// The background of the panel. Its size follows the text.
+struct Panel {
+    width: f64,
+    height: f64,
+}
+
+impl Renderable for Panel {
+    fn draw(&self, backend: &mut dyn Backend) {
+        let pos = Pos::new(PANEL.0 - 0.5, PANEL.1 - 0.5);
+        backend.draw_rect(pos, self.width + 1.0, self.height + 1.0);
+    }
+
+    fn footprint(&self) -> Option<Footprint> {
+        let pos = Pos::new(PANEL.0 - 0.5, PANEL.1 - 0.5);
+        let key = (self.width * 10.0) as u64 * 1000 + (self.height * 10.0) as u64;
+        Some(Footprint::covering(
+            pos,
+            self.width + 1.0,
+            self.height + 1.0,
+            key,
+        ))
+    }
+}
+
+/// The text of the panel.
+struct Text(String);
+
+impl Renderable for Text {
+    fn draw(&self, backend: &mut dyn Backend) {
+        let pos = Pos::new(PANEL.0, PANEL.1);
+        rendering::draw_text(backend, &self.0, pos, Align::Left);
+    }
+
+    fn footprint(&self) -> Option<Footprint> {
+        let pos = Pos::new(PANEL.0, PANEL.1);
+        Some(Footprint::text(&self.0, pos, FONT.size(), Align::Left))
+    }
+}
+
+/// Debug overlay state. Contains the outlines of the collision boxes and projectiles, the panel with its
+/// text, and the game ticks counted since the last update.
+pub struct DebugOverlay {
+    boxes: Vec<Outline>,
+    shots: Vec<Outline>,
+    panel: Panel,
+    text: Text,
+    ticks: u32,
+}
+
+impl DebugOverlay {
+    /// Create new empty debug overlay.
+    pub fn new() -> Self {
+        Self {
+            boxes: vec![],
+            shots: vec![],
+            panel: Panel {
+                width: 0.0,
+                height: 0.0,
+            },
+            text: Text(String::new()),
+            ticks: 0,
+        }
+    }
+
+    /// Count *ticks* simulated game ticks. They are shown (and reset) by the next **update()**.
+    pub fn count_ticks(&mut self, ticks: u32) {
+        self.ticks += ticks;
+    }
+
+    /// Take a snapshot of *world* and *effects*, with the *render* statistics of the backend (bind and
+    /// draw counters of the last frame, if it has them).
+    pub fn update(
+        &mut self,
+        world: &World,
+        effects: &Effects,
+        render: Option<(BindCounters, DrawCounters)>,
+    ) {
+        self.boxes.clear();
+        for e in world.enemies() {
+            let (x, y, w, h) = e.collision_box();
+            self.boxes.push(Outline {
+                pos: Pos::new(x as f64, y as f64),
+                width: w as f64,
+                height: h as f64,
+            });
+        }
+
+        self.shots.clear();
+        for p in world.projectiles() {
+            self.shots.push(Outline {
+                pos: Pos::new(p.pos_x as f64 - 0.5, p.pos_y as f64 - 0.5),
+                width: 2.0,
+                height: 2.0,
+            });
+        }
+
+        // Fill the panel. Timestamps are in ms of the world time, `-` marks timers, which are not set.
+        // --------------------------------------------------------------------------------------------
+        let deadline = |t: Option<u32>| t.map_or("-".to_owned(), |t| t.to_string());
+        let counters = world.tick_counters();
+        let (movement, shoot) = world.player().timer_deadlines();
+
+        let text = &mut self.text.0;
+        text.clear();
+        let _ = writeln!(
+            text,
+            "P{} E{} X{} T{}",
+            world.projectiles().len(),
+            world.enemies().len(),
+            effects.explosion_count(),
+            self.ticks
+        );
+        let _ = writeln!(
+            text,
+            "G@{} +{:.2}",
+            counters.game_tick,
+            world.tick_fraction()
+        );
+        let _ = writeln!(
+            text,
+            "E{}/{} S{}/{}",
+            counters.enemy_tick,
+            counters.enemy_interval,
+            counters.spawn_tick,
+            counters.spawn_interval
+        );
+        let _ = write!(text, "M@{} F@{}", deadline(movement), deadline(shoot));
+        if let Some((binds, draws)) = render {
+            let _ = write!(
+                text,
+                "\nB{}/{} C{} R{}",
+                binds.issued,
+                binds.issued + binds.skipped,
+                draws.calls,
+                draws.rects
+            );
+        }
+        self.ticks = 0;
+
+        let (width, height) = rendering::measure_text(text, FONT.size());
+        self.panel = Panel { width, height };
+    }
+}
+
+impl SceneNode for DebugOverlay {
+    /// Submit the outlines on top of the effects (so they move with the camera), the panel background with
+    /// the screens, and the text on top of everything. They are on different layers, because the order of
+    /// PSOs within a layer is not fixed.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        for b in self.boxes.iter() {
+            scene.submit(Layer::Effects, &BOX_PSO, b);
+        }
+        for s in self.shots.iter() {
+            scene.submit(Layer::Effects, &SHOT_PSO, s);
+        }
+
+        scene.submit(Layer::Overlay, &PANEL_PSO, &self.panel);
+        scene.submit(Layer::Debug, &TEXT_PSO, &self.text);
+    }
+}
@@ -0,0 +1,77 @@
+//! Effects module.
+//!
+//! Visual effects are not part of the simulation. They subscribe to gameplay events and spawn the
+//! matching effect (currently explosions with a burst of debris particles for destroyed enemies and
+//! player hits).
+
+use crate::entites::Explosion;
+use crate::events::{GameEvent, Subscriber};
+use crate::particles::{Burst, Particles, HIT_BURST, KILL_BURST};
+use crate::rendering::{Layer, Scene, SceneNode};
+use crate::utils;
+use crate::world::TICK;
+
+/// Effect state. Contains all active explosions, and the particle system.
+pub struct Effects {
+    explosions: Vec<Explosion>,
+    particles: Particles,
+}
+
+impl Effects {
+    /// Create new effect state without any active effects.
+    pub fn new() -> Self {
+        Self {
+            explosions: vec![],
+            particles: Particles::new(),
+        }
+    }
+
+    /// Number of active explosions.
+    #[cfg(feature = "debug-overlay")]
+    pub fn explosion_count(&self) -> usize {
+        self.explosions.len()
+    }
+
+    /// Effect tick. Has to be called every game tick.
+    pub fn tick(&mut self) {
+        // Update all those beautiful explosions.
+        // -------------------------------------
+        for x in self.explosions.iter_mut() {
+            x.tick(TICK);
+        }
+        self.explosions.retain(|x| !x.needs_removal()); //<- Eliminate the ones with finished animation.
+        self.particles.tick(TICK);
+    }
+
+    /// Spawn an explosion with a *burst* of particles centered around virtual position (*x*, *y*).
+    fn explode(&mut self, x: u32, y: u32, burst: &Burst) {
+        self.explosions.push(Explosion::new(
+            utils::cap(x as i32 - 1, 0, 79) as u32,
+            utils::cap(y as i32 - 1, 0, 59) as u32,
+        ));
+
+        let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5); //<- Center of the virtual pixel.
+        self.particles.emit(burst, cx, cy);
+    }
+}
+
+impl Subscriber for Effects {
+    /// Spawn explosions for destroyed enemies and player hits.
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::EnemyKilled { x, y, .. } => self.explode(x, y + 2, &KILL_BURST),
+            GameEvent::PlayerHit { x, y } => self.explode(x, y, &HIT_BURST),
+            _ => {}
+        }
+    }
+}
+
+impl SceneNode for Effects {
+    /// Submit all explosions with the explosion PSO, and the particles.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        for x in self.explosions.iter() {
+            scene.submit(Layer::Effects, Explosion::pso(), x);
+        }
+        self.particles.submit(scene);
+    }
+}
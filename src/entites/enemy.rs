@@ -6,24 +6,35 @@
 //!   * When the void at the bottom is reached, die and remove some points from the player score.
 //!   * When shot by projectile, die and give player score some points.
 //!   * Spawn on random location on top of the screen every enemy spawn tick.
-//!   * There are several visual types of enemies. They each have a different bounding box for collision.
+//!   * There are several visual types of enemies. They each have a different sprite, and the bounding box
+//!     for collision is derived from it.
+//!   * While moving, they play the walk cycle of their sprite.
 
 use self::EnemyType::*;
-use crate::rendering::{CssColor, PixelScreen, Pos, Pso, Renderable};
-use crate::utils;
+use crate::animation::Animation;
+use crate::rendering::{Backend, Footprint, Interpolation, Pos, Pso, Renderable, Role};
+use crate::sprites::{self, Sprite};
+use crate::utils::{self, Rng};
 
 lazy_static! {
-    /// Pso for enemies. Fill colored black pixels.
+    /// Pso for enemies. Pixels filled with the enemy color.
     pub static ref ENEMY_PSO: Pso = Pso {
-        fill_color: Some(CssColor::new(0, 0, 0)),
+        fill_color: Some(Role::Enemy),
         ..Default::default()
     };
+
+    /// The sprites of the enemy types, indexed by the variant. They are looked up in the sprite sheet only
+    /// once, and not on every draw or collision check.
+    static ref SPRITES: Vec<&'static Sprite> = EnemyType::VARIANTS
+        .iter()
+        .map(|t| sprites::get(t.sprite_name()))
+        .collect();
 }
 
 /// Types of enemies (different visuals and bounding boxes)
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug)]
 #[repr(u8)]
-enum EnemyType {
+pub enum EnemyType {
     Star,
     Thin,
     Arrow,
@@ -34,71 +45,102 @@ impl EnemyType {
     ///
     /// TODO: Consider using not unsafe way to transmute random number into variant. This uses a
     /// byte const data per variant.
-    const VARIANTS: &'static [Self] = &[Star, Thin, Arrow];
+    pub const VARIANTS: &'static [Self] = &[Star, Thin, Arrow];
+
+    /// Name of the variant.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Star => "star",
+            Thin => "thin",
+            Arrow => "arrow",
+        }
+    }
 
     /// Create a random variant.
-    fn random() -> Self {
+    fn random(rng: &mut Rng) -> Self {
         let variants = Self::VARIANTS;
-        let idx = utils::rand(variants.len() as u32 - 1);
+        let idx = rng.rand(variants.len() as u32 - 1);
         *variants.iter().skip(idx as usize).next().unwrap()
     }
 
-    /// Returns a list of virtual to be drawn pixels for each variant of enemy.
-    fn px_list(&self) -> &'static [(u32, u32)] {
+    /// Name of the sprite for each variant of enemy in the sprite sheet.
+    fn sprite_name(&self) -> &'static str {
         match self {
-            Star => &[(0, 0), (2, 0), (1, 1), (0, 2), (2, 2)],
-            Thin => &[(0, 0), (0, 1)],
-            Arrow => &[(0, 0), (2, 0), (1, 1)],
+            Star => "enemy.star",
+            Thin => "enemy.thin",
+            Arrow => "enemy.arrow",
         }
     }
 
+    /// Returns the sprite for each variant of enemy.
+    fn sprite(&self) -> &'static Sprite {
+        SPRITES[*self as usize]
+    }
+
     /// Returns the bounding box for each variant of enemy.
     fn bounds(&self) -> (u32, u32) {
-        match self {
-            Star => (3, 3),
-            Thin => (1, 2),
-            Arrow => (3, 2),
-        }
+        self.sprite().bounds()
     }
 }
 
-/// The actual enemy entity. Contains position and enemy type.
+/// The actual enemy entity. Contains position, enemy type and the walk cycle animation. Also keeps the
+/// vertical position before the last tick, so it can be drawn between both (see **draw_pos()**).
 pub struct Enemy {
     pos_y: u32,
     pos_x: u32,
     kind: EnemyType,
+    walk: Animation,
+    prev_y: u32,
 }
 
 impl Enemy {
-    /// Create new enemy on x-coordinate *pos_x*.
-    pub fn new(pos_x: u32) -> Self {
+    /// Create new enemy of random type on x-coordinate *pos_x*.
+    pub fn new(pos_x: u32, rng: &mut Rng) -> Self {
+        let kind = EnemyType::random(rng);
         Self {
             pos_y: 0,
             pos_x,
-            kind: EnemyType::random(),
+            kind,
+            walk: Animation::new(kind.sprite().clip("walk")),
+            prev_y: 0,
         }
     }
 
     /// Create new enemy on random x-coordinate. This will check bounds to create enemies only there, where
     /// they can be hit by projectiles.
-    pub fn new_random() -> Self {
-        let kind = EnemyType::random();
+    pub fn new_random(rng: &mut Rng) -> Self {
+        let kind = EnemyType::random(rng);
         Self {
             pos_y: 0,
             pos_x: utils::cap(
-                utils::rand(80),
+                rng.rand(80),
                 if kind.bounds().0 > 1 { 0 } else { 1 },
                 80 - kind.bounds().0 - if kind.bounds().0 > 1 { 0 } else { 1 },
             ),
             kind,
+            walk: Animation::new(kind.sprite().clip("walk")),
+            prev_y: 0,
         }
     }
 
+    /// Advance the walk cycle animation by *dt* ms.
+    pub fn animate(&mut self, dt: u32) {
+        self.walk.advance(dt, || ());
+    }
+
     /// Enemy tick (updates the enemy, which is simple down movement).
     pub fn tick(&mut self) {
+        self.prev_y = self.pos_y;
         self.pos_y += 1;
     }
 
+    /// The position the enemy is drawn at with *lerp*. The fraction of the enemy interval of the way from
+    /// its last position to the current one (it only moves every enemy tick).
+    fn draw_pos(&self, lerp: &Interpolation) -> Pos {
+        let y = utils::lerp(self.prev_y, self.pos_y, lerp.enemy, lerp.snap);
+        Pos::new(self.pos_x as f64, y)
+    }
+
     /// Checks, if this enemy has to be removed, because it touches the bottom of the screen.
     pub fn needs_removal(&self) -> bool {
         if self.pos_y + self.kind.bounds().1 > 60 {
@@ -111,7 +153,7 @@ impl Enemy {
     /// Check the enemy type bounding box against the given position. Returns true
     /// on intersection.
     ///
-    /// TODO: Convert to pixel perfect collision detection by reusing the **px_list**.
+    /// TODO: Convert to pixel perfect collision detection by reusing the pixels of the sprite.
     pub fn intersects_with(&self, x: u32, y: u32) -> bool {
         x >= self.pos_x
             && x < self.pos_x + self.kind.bounds().0
@@ -119,6 +161,18 @@ impl Enemy {
             && y < self.pos_y + self.kind.bounds().1
     }
 
+    /// The collision box of the enemy (x, y, width and height in virtual pixels).
+    #[cfg(feature = "debug-overlay")]
+    pub fn collision_box(&self) -> (u32, u32, u32, u32) {
+        let (w, h) = self.kind.bounds();
+        (self.pos_x, self.pos_y, w, h)
+    }
+
+    /// The type of the enemy.
+    pub fn kind(&self) -> EnemyType {
+        self.kind
+    }
+
     /// The PSO for enemy drawing.
     pub fn pso() -> &'static Pso {
         &ENEMY_PSO
@@ -126,19 +180,26 @@ impl Enemy {
 }
 
 impl Renderable for Enemy {
-    /// Render the enemy pixel list to the **PixelScreen**.
-    fn draw(&self, pxs: &PixelScreen) {
-        let px_list = self.kind.px_list();
-
-        for px in px_list {
-            pxs.draw_rect(
-                Pos::new(
-                    (self.pos_x + px.0) as f64 * 10.0,
-                    (self.pos_y + px.1) as f64 * 10.0,
-                ),
-                10.0,
-                10.0,
-            );
-        }
+    fn draw(&self, backend: &mut dyn Backend) {
+        self.draw_at(backend, &Interpolation::default());
+    }
+
+    fn footprint(&self) -> Option<Footprint> {
+        self.footprint_at(&Interpolation::default())
+    }
+
+    /// Render the current frame of the walk cycle.
+    fn draw_at(&self, backend: &mut dyn Backend, lerp: &Interpolation) {
+        let pos = self.draw_pos(lerp);
+        self.kind.sprite().draw(backend, self.walk.frame(), pos);
+    }
+
+    /// The bounds of the sprite, keyed by the frame of the walk cycle.
+    fn footprint_at(&self, lerp: &Interpolation) -> Option<Footprint> {
+        let sprite = self.kind.sprite();
+        let pos = self.draw_pos(lerp);
+        let mut footprint = sprite.footprint(self.walk.frame(), pos);
+        footprint.key = footprint.key.wrapping_mul(3).wrapping_add(self.kind as u64); //<- Different kinds look different.
+        Some(footprint)
     }
 }
@@ -1,25 +1,31 @@
 //! Explosion entity module.
 //!
 //! The explosion spawns every time a enemy is destroyed on the position of the destroyed enemy / the involved projectile.
-//! In comparison to some other entities, the explosing has an animation cycle with 2 animation steps.
-//! The explosion lasts only for a specific time, and will be destroyed after it passed.
+//! The explosion plays the one-shot `explode` clip of its sprite, and will be destroyed after the clip is
+//! finished.
 
-use crate::rendering::{CssColor, PixelScreen, Pos, Pso, Renderable};
+use crate::animation::Animation;
+use crate::rendering::{Backend, Footprint, Pos, Pso, Renderable, Role};
+use crate::sprites::{self, Sprite};
 
 lazy_static! {
     pub static ref EXPLOSION_PSO: Pso = Pso {
-        fill_color: Some(CssColor::new(128, 128, 128)),
+        fill_color: Some(Role::Explosion),
         ..Default::default()
     };
+
+    /// The explosion sprite (looked up once).
+    static ref SPRITE: &'static Sprite = sprites::get("explosion");
 }
 
 /// Explosion entity.
 ///
-/// Contains relevant state like position and animation progress.
+/// Contains relevant state like position and animation.
 pub struct Explosion {
     pos_x: u32,
     pos_y: u32,
-    progress: u8,
+    animation: Animation,
+    done: bool,
 }
 
 impl Explosion {
@@ -28,22 +34,20 @@ impl Explosion {
         Self {
             pos_x,
             pos_y,
-            progress: 0,
+            animation: Animation::new(SPRITE.clip("explode")),
+            done: false,
         }
     }
 
-    /// Explosion tick (will make progress on animation).
-    pub fn tick(&mut self) {
-        self.progress += 1;
+    /// Explosion tick (advances the animation by *dt* ms).
+    pub fn tick(&mut self, dt: u32) {
+        let done = &mut self.done;
+        self.animation.advance(dt, || *done = true);
     }
 
-    /// Check if the progress of the explosion has reached its end.
+    /// Check if the animation of the explosion has finished.
     pub fn needs_removal(&self) -> bool {
-        if self.progress > 5 {
-            true
-        } else {
-            false
-        }
+        self.done
     }
 
     /// Explosion PSO.
@@ -55,24 +59,15 @@ impl Explosion {
 impl Renderable for Explosion {
     /// Render the animation of the explosion to the **PixelScreen**.
     ///
-    /// This has two repeating animation steps, which depend on the **progress** field in the entity.
-    fn draw(&self, pxs: &PixelScreen) {
-        let frames = &[
-            &[(0, 0), (2, 0), (1, 1), (0, 2), (2, 2)][..],
-            &[(1, 0), (0, 1), (2, 1), (1, 2)][..],
-        ];
-
-        let selected = frames[self.progress as usize % 2];
+    /// The frame of the explosion sprite is selected by the animation.
+    fn draw(&self, backend: &mut dyn Backend) {
+        let pos = Pos::new(self.pos_x as f64, self.pos_y as f64);
+        SPRITE.draw(backend, self.animation.frame(), pos);
+    }
 
-        for px in selected {
-            pxs.draw_rect(
-                Pos::new(
-                    (self.pos_x + px.0) as f64 * 10.0,
-                    (self.pos_y + px.1) as f64 * 10.0,
-                ),
-                10.0,
-                10.0,
-            );
-        }
+    /// The bounds of the explosion sprite, keyed by the frame of the animation.
+    fn footprint(&self) -> Option<Footprint> {
+        let pos = Pos::new(self.pos_x as f64, self.pos_y as f64);
+        Some(SPRITE.footprint(self.animation.frame(), pos))
     }
 }
@@ -3,37 +3,59 @@
 //! The fps counter measures current frames per second by being updated with the current ts every
 //! game loop.
 //!
-//! It is also able to draw itself to the screen.
+//! Its label (the current FPS) is drawn by the HUD.
 
-use crate::rendering::{CssColor, CssFont, PixelScreen, Pos, Pso, Renderable};
 use crate::utils::Timer;
 use std::fmt::Write;
 
 /// The fps counter.
 ///
-/// Contains last frame timestamp, its PSO, a timer which provides the interval in which fps should be
-/// drawn to the screen, and also a string buffer, where the current FPS are written to.
+/// Contains last frame timestamp, a timer which provides the interval in which the fps label should be
+/// refreshed, and also a string buffer, where the current FPS are written to.
+///
+/// It also keeps the number of measured frames, their total duration, and the lowest FPS seen so far,
+/// for the session statistics.
 pub struct FpsCounter {
     last_frame_ts: u32,
     timer: Timer,
     print_buf: String,
-    pso: Pso,
+    frames: u32,
+    frames_ms: u32,
+    min_fps: Option<f64>,
 }
 
 impl FpsCounter {
     /// Create new FPS counter.
     pub fn new() -> Self {
         Self {
-            pso: Pso {
-                fill_color: Some(CssColor::new(0, 0, 0)),
-                font: Some(CssFont::monospace(20)),
-            },
             last_frame_ts: 0,
             timer: Timer::interval(0, 500),
             print_buf: String::with_capacity("FPS: XX.XX".len()),
+            frames: 0,
+            frames_ms: 0,
+            min_fps: None,
         }
     }
 
+    /// The label with the current FPS (refreshed twice a second).
+    pub fn label(&self) -> &str {
+        &self.print_buf
+    }
+
+    /// Average FPS over all measured frames.
+    pub fn average(&self) -> f64 {
+        if self.frames_ms == 0 {
+            0.0
+        } else {
+            self.frames as f64 * 1000.0 / self.frames_ms as f64
+        }
+    }
+
+    /// Lowest FPS of all measured frames.
+    pub fn min(&self) -> f64 {
+        self.min_fps.unwrap_or(0.0)
+    }
+
     /// Update function for the FPS counter. It needs per game loop update to measure FPS,
     /// and to check its redraw timer.
     pub fn update(&mut self, ts: u32) {
@@ -46,22 +68,19 @@ impl FpsCounter {
         // --------------------------------------------------------------------------------
         timer.check(ts, |_off| {
             print_buf.clear();
-            write!(
-                print_buf,
-                "FPS: {:.2}",
-                1000.0 / (ts - last_frame_ts) as f64
-            )
-            .unwrap();
+            write!(print_buf, "{:.0} FPS", 1000.0 / (ts - last_frame_ts) as f64).unwrap();
         });
 
-        self.last_frame_ts = ts;
-    }
-}
+        // Measure the frame for the statistics. The very first frame has no predecessor, and frames
+        // without any elapsed time can not be measured.
+        // -------------------------------------------------------------------------------------------
+        if last_frame_ts != 0 && ts > last_frame_ts {
+            let fps = 1000.0 / (ts - last_frame_ts) as f64;
+            self.frames += 1;
+            self.frames_ms += ts - last_frame_ts;
+            self.min_fps = Some(self.min_fps.map_or(fps, |m| m.min(fps)));
+        }
 
-impl Renderable for FpsCounter {
-    /// Render the current print buffer to the screen.
-    fn draw(&self, pxs: &PixelScreen) {
-        self.pso.bind(pxs);
-        pxs.draw_text(&self.print_buf, Pos::new(0.0, 20.0));
+        self.last_frame_ts = ts;
     }
 }
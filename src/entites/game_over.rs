@@ -0,0 +1,96 @@
+//! Game over screen module.
+//!
+//! The game over screen is shown on top of the frozen scene, when the player has no lives left. It
+//! shows a banner and the statistics of the session.
+
+use crate::rendering::{
+    self, Align, Backend, CssFont, Footprint, Layer, Pos, Pso, Renderable, Role, Scene, SceneNode,
+};
+use crate::stats::SessionStats;
+
+/// Font of the banner.
+const BANNER_FONT: CssFont = CssFont::monospace(20);
+
+/// Font of the statistics.
+const FONT: CssFont = CssFont::monospace(10);
+
+/// A (multi-line) text centered at a fixed position.
+struct Label {
+    text: String,
+    pos: Pos,
+    font: CssFont,
+}
+
+impl Renderable for Label {
+    fn draw(&self, backend: &mut dyn Backend) {
+        rendering::draw_text(backend, &self.text, self.pos, Align::Center);
+    }
+
+    /// The area of the text.
+    fn footprint(&self) -> Option<Footprint> {
+        Some(Footprint::text(
+            &self.text,
+            self.pos,
+            self.font.size(),
+            Align::Center,
+        ))
+    }
+}
+
+/// The game over screen. Contains PSOs for the banner and the statistics, the banner, and the statistics
+/// text (captured when the game was over, one line per value).
+pub struct GameOver {
+    banner_pso: Pso,
+    pso: Pso,
+    banner: Label,
+    lines: Label,
+}
+
+impl GameOver {
+    /// Create the game over screen for the session *stats*.
+    pub fn new(stats: &SessionStats) -> Self {
+        let mut game_over = Self {
+            banner_pso: Pso {
+                fill_color: Some(Role::Warning),
+                font: Some(BANNER_FONT),
+                ..Default::default()
+            },
+            pso: Pso {
+                fill_color: Some(Role::Ui),
+                font: Some(FONT),
+                ..Default::default()
+            },
+            banner: Label {
+                text: "GAME OVER".to_owned(),
+                pos: Pos::new(40.0, 4.0),
+                font: BANNER_FONT,
+            },
+            lines: Label {
+                text: String::new(),
+                pos: Pos::new(40.0, 17.0),
+                font: FONT,
+            },
+        };
+
+        for line in stats.summary_lines() {
+            game_over.add_line(line);
+        }
+        game_over
+    }
+
+    /// Append an additional *line* below the statistics.
+    pub fn add_line(&mut self, line: String) {
+        if !self.lines.text.is_empty() {
+            self.lines.text.push('\n');
+        }
+        self.lines.text.push_str(&line);
+    }
+}
+
+impl SceneNode for GameOver {
+    /// Submit the banner, and below it the statistics.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        scene.submit(Layer::Overlay, &self.banner_pso, &self.banner);
+        scene.submit(Layer::Overlay, &self.pso, &self.lines);
+    }
+}
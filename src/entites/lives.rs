@@ -0,0 +1,32 @@
+//! Lives module.
+//!
+//! Keeps the remaining lives of the player. Every time an enemy crashes into the player ship, a life is
+//! lost. When there are no lives left, the game is over. The lives are drawn by the HUD.
+
+use crate::events::{GameEvent, Subscriber};
+
+/// State for the lives. The remaining count.
+pub struct Lives {
+    count: u32,
+}
+
+impl Lives {
+    /// Create new lives with the start *count*.
+    pub fn new(count: u32) -> Self {
+        Self { count }
+    }
+
+    /// Remaining lives.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+impl Subscriber for Lives {
+    /// Lose a life, when the player was hit.
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::PlayerHit { .. } = event {
+            self.count = self.count.saturating_sub(1);
+        }
+    }
+}
@@ -3,13 +3,19 @@
 mod enemy;
 mod explosion;
 mod fps_counter;
+mod game_over;
+mod lives;
 mod player;
 mod projectile;
 mod score;
+mod toast;
 
-pub use self::enemy::Enemy;
+pub use self::enemy::{Enemy, EnemyType};
 pub use self::explosion::Explosion;
 pub use self::fps_counter::FpsCounter;
+pub use self::game_over::GameOver;
+pub use self::lives::Lives;
 pub use self::player::Player;
 pub use self::projectile::Projectile;
 pub use self::score::Score;
+pub use self::toast::Toast;
@@ -5,16 +5,22 @@
 //!
 //! The player moves horizontally at the bottom of the screen and shoots projectiles towards enemies.
 
-use super::Projectile;
+use super::{Enemy, Projectile};
+use crate::events::{Events, GameEvent};
 use crate::input::Input;
-use crate::rendering::{CssColor, PixelScreen, Pos, Pso, Renderable};
+use crate::rendering::{Backend, Footprint, Pos, Pso, Renderable, Role};
+use crate::sprites::{self, Sprite};
 use crate::utils::Timer;
 
+/// Vertical position of the player ship.
+const POS_Y: u32 = 57;
+
 /// The player entity type.
 ///
-/// Contains its PSO, x-coord position, and timers.
+/// Contains its PSO, sprite, x-coord position, and timers.
 pub struct Player {
     pso: Pso,
+    sprite: &'static Sprite,
     pos: u32,
     movement_timer: Option<Timer>,
     shoot_timer: Option<Timer>,
@@ -25,28 +31,51 @@ impl Player {
     pub fn new() -> Self {
         Self {
             pso: Pso {
-                fill_color: Some(CssColor::new(0, 0, 0)),
+                fill_color: Some(Role::Player),
                 ..Default::default()
             },
+            sprite: sprites::get("player"),
             pos: 0,
             movement_timer: None,
             shoot_timer: None,
         }
     }
 
+    /// Checks if *enemy* crashed into any pixel of the player ship. Returns the hit virtual position.
+    pub fn hit_by(&self, enemy: &Enemy) -> Option<(u32, u32)> {
+        self.sprite
+            .frame(0)
+            .pixels()
+            .map(|(x, y, _)| (self.pos + x, POS_Y + y))
+            .find(|&(x, y)| enemy.intersects_with(x, y))
+    }
+
+    /// The deadlines of the movement and the shoot timer (when they are running).
+    #[cfg(feature = "debug-overlay")]
+    pub fn timer_deadlines(&self) -> (Option<u32>, Option<u32>) {
+        let deadline = |t: &Option<Timer>| t.as_ref().map(|t| t.deadline());
+        (deadline(&self.movement_timer), deadline(&self.shoot_timer))
+    }
+
     /// Update the payer every game loop. It has its own ticks provided by timers.utils
-    /// Needs access to projectile vector to spawn new projectiles.
-    pub fn update(&mut self, ts: u32, input: &Input, projectiles: &mut Vec<Projectile>) {
+    /// Needs access to projectile vector to spawn new projectiles, and to the events to emit shots.
+    pub fn update(
+        &mut self,
+        ts: u32,
+        input: &Input,
+        projectiles: &mut Vec<Projectile>,
+        events: &mut Events,
+    ) {
         /// Do movement function. Modifies pos according to input module.
         ///
         /// Also returns if anything acually happened.
-        fn do_movement(pos: &mut u32, input: &Input) -> bool {
+        fn do_movement(pos: &mut u32, max: u32, input: &Input) -> bool {
             if input.left() && input.right() {
                 false
             } else if *pos > 0 && input.left() {
                 *pos -= 1;
                 true
-            } else if *pos < 77 && input.right() {
+            } else if *pos < max && input.right() {
                 *pos += 1;
                 true
             } else {
@@ -54,6 +83,7 @@ impl Player {
             }
         }
 
+        let max = 80 - self.sprite.bounds().0; //<- Rightmost position, where the ship is fully visible.
         let timer = &mut self.movement_timer;
         let pos = &mut self.pos;
         let shoot_timer = &mut self.shoot_timer;
@@ -68,7 +98,7 @@ impl Player {
                 // No timer = move now. This is for instant input reaction.
                 // Ony if returns true set up a timer.
                 // --------------------------------------------------------
-                do_movement(pos, input)
+                do_movement(pos, max, input)
             }
             Some(t) => {
                 // When there is a timer, only execute movement on timer event. This limits movement speed.
@@ -77,7 +107,7 @@ impl Player {
 
                     // Also when the deviation is big enough player may need multiple movements at once to correct deviation.
                     for _ in 0..(off + SPEED) / SPEED {
-                        did_something = do_movement(pos, &input);
+                        did_something = do_movement(pos, max, &input);
                     }
                     // Check if this did not actually do anything. When true, remove timer.
                     !did_something
@@ -101,6 +131,7 @@ impl Player {
             None => {
                 if input.shoot() {
                     projectiles.push(Projectile::new(*pos + 1));
+                    events.emit(GameEvent::ShotFired);
                     true
                 } else {
                     false
@@ -110,6 +141,7 @@ impl Player {
                 t.check(ts, |_off| {
                     if input.shoot() {
                         projectiles.push(Projectile::new(*pos + 1));
+                        events.emit(GameEvent::ShotFired);
                         false
                     } else {
                         true
@@ -130,22 +162,26 @@ impl Player {
             self.shoot_timer = Some(Timer::interval(ts, SPEED));
         }
     }
+
+    /// The position of the ship on screen. It is not interpolated, so it follows the input right away.
+    fn draw_pos(&self) -> Pos {
+        Pos::new(self.pos as f64, POS_Y as f64)
+    }
+
+    /// The PSO for player drawing.
+    pub fn pso(&self) -> &Pso {
+        &self.pso
+    }
 }
 
 impl Renderable for Player {
-    /// Draw the player to the screen using its pixel list.
-    /// This time bind the PSO in the draw call, because we do not batch multiple players.
-    fn draw(&self, pxs: &PixelScreen) {
-        let px_list = &[(1, 0), (0, 1), (1, 1), (2, 1), (0, 2), (2, 2)];
-
-        self.pso.bind(pxs);
-
-        for e in px_list {
-            pxs.draw_rect(
-                Pos::new((self.pos + e.0) as f64 * 10.0, (57 + e.1) as f64 * 10.0),
-                10.0,
-                10.0,
-            );
-        }
+    /// Draw the player ship sprite to the screen.
+    fn draw(&self, backend: &mut dyn Backend) {
+        self.sprite.draw(backend, 0, self.draw_pos());
+    }
+
+    /// The bounds of the ship sprite.
+    fn footprint(&self) -> Option<Footprint> {
+        Some(self.sprite.footprint(0, self.draw_pos()))
     }
 }
@@ -4,22 +4,24 @@
 //! the player gets score. If the projectile reaches the top of the screen and goes into the void, it dies and the player
 //! looses some of its score.
 
-use crate::rendering::{CssColor, PixelScreen, Pos, Pso, Renderable};
+use crate::rendering::{Backend, Footprint, Interpolation, Pos, Pso, Renderable, Role};
 use crate::utils;
 
 lazy_static! {
-    /// Projectils PSO (red fill color).
+    /// Projectils PSO (shot fill color).
     pub static ref PROJECTILE_PSO: Pso = Pso {
-        fill_color: Some(CssColor::new(255, 0, 0)),
+        fill_color: Some(Role::Shot),
         ..Default::default()
     };
 }
 
-/// The projectile with pos on screen + direction in which to fly (unused).
+/// The projectile with pos on screen + direction in which to fly (unused). Also keeps the position before
+/// the last tick, so it can be drawn between both (see **draw_pos()**).
 pub struct Projectile {
     pub pos_x: u32,
     pub pos_y: u32,
     dir: (i32, i32),
+    prev: (u32, u32),
 }
 
 impl Projectile {
@@ -29,11 +31,13 @@ impl Projectile {
             pos_x: x,
             pos_y: 56,
             dir: (0, -1),
+            prev: (x, 56),
         }
     }
 
     /// Projectile tick will move it into *dir* direction, only untils it reaches the screen cap.
     pub fn tick(&mut self) {
+        self.prev = (self.pos_x, self.pos_y);
         self.pos_x = utils::cap(self.pos_x as i32 + self.dir.0, 0, 79) as u32;
         self.pos_y = utils::cap(self.pos_y as i32 + self.dir.1, 0, 59) as u32;
     }
@@ -43,6 +47,15 @@ impl Projectile {
         self.pos_y == 0
     }
 
+    /// The position the projectile is drawn at with *lerp*. The tick fraction of the way from its last
+    /// position to the current one (it moves every game tick).
+    fn draw_pos(&self, lerp: &Interpolation) -> Pos {
+        Pos::new(
+            utils::lerp(self.prev.0, self.pos_x, lerp.tick, lerp.snap),
+            utils::lerp(self.prev.1, self.pos_y, lerp.tick, lerp.snap),
+        )
+    }
+
     /// The PSO for **Projectile**.
     pub fn pso() -> &'static Pso {
         &PROJECTILE_PSO
@@ -50,11 +63,65 @@ impl Projectile {
 }
 
 impl Renderable for Projectile {
-    fn draw(&self, pxs: &PixelScreen) {
-        pxs.draw_rect(
-            Pos::new((self.pos_x) as f64 * 10.0, (self.pos_y) as f64 * 10.0),
-            10.0,
-            10.0,
+    fn draw(&self, backend: &mut dyn Backend) {
+        self.draw_at(backend, &Interpolation::default());
+    }
+
+    fn footprint(&self) -> Option<Footprint> {
+        self.footprint_at(&Interpolation::default())
+    }
+
+    fn draw_at(&self, backend: &mut dyn Backend, lerp: &Interpolation) {
+        backend.draw_rect(self.draw_pos(lerp), 1.0, 1.0);
+    }
+
+    /// A single cell (or the cells it touches between two of them).
+    fn footprint_at(&self, lerp: &Interpolation) -> Option<Footprint> {
+        Some(Footprint::covering(self.draw_pos(lerp), 1.0, 1.0, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Call, Layer, Palette, RecordingBackend, Scene};
+    use bumpalo::Bump;
+
+    #[test]
+    fn draw_calls() {
+        let palette = Palette::default_theme();
+        let mut projectile = Projectile::new(12);
+        projectile.tick();
+
+        let mut backend = RecordingBackend::new();
+        let arena = Bump::new();
+        let mut scene = Scene::new(&arena);
+        scene.submit(Layer::World, Projectile::pso(), &projectile);
+        scene.draw(&mut backend, palette);
+
+        assert_eq!(
+            backend.take(),
+            vec![
+                Call::Camera((0.0, 0.0)),
+                Call::FillColor(palette.color(Role::Shot).rgb()),
+                Call::Alpha(255),
+                Call::Rect((12.0, 55.0), 1.0, 1.0),
+                Call::Camera((0.0, 0.0)),
+            ]
         );
     }
+
+    #[test]
+    fn draw_interpolated() {
+        let mut projectile = Projectile::new(12);
+        projectile.tick();
+        let lerp = Interpolation {
+            tick: 0.25,
+            ..Default::default()
+        };
+
+        let mut backend = RecordingBackend::new();
+        projectile.draw_at(&mut backend, &lerp);
+        assert_eq!(backend.take(), vec![Call::Rect((12.0, 55.75), 1.0, 1.0)]);
+    }
 }
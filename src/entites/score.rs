@@ -1,70 +1,41 @@
 //! Score module.
 //!
-//! Keeps the score, and draws score to the screen.
+//! Keeps the score. The score subscribes to gameplay events, and awards or removes points depending on
+//! them. It is drawn by the HUD.
 
-use crate::rendering::{CssColor, CssFont, PixelScreen, Pos, Pso, Renderable};
-use std::fmt::Write;
+use crate::events::{GameEvent, Subscriber};
 
-/// State for the score. Its pos on screen, PSO for drawing, PSO for drawing negative score, current value, name of the score field,
-/// and buffer for the current label content.
+/// State for the score. The current value.
 pub struct Score {
-    pos: Pos,
-    pso: Pso,
-    bad_pso: Pso,
     value: i32,
-    name: String,
-    label_buf: String,
 }
 
 impl Score {
-    /// Create new score with *color* for normal color, *bad_color* for negative scores with font, name and position.
-    pub fn new(
-        color: CssColor,
-        bad_color: CssColor,
-        font: CssFont,
-        name: String,
-        pos: Pos,
-    ) -> Self {
-        let mut r = Self {
-            pos,
-            pso: Pso {
-                fill_color: Some(color),
-                font: Some(font),
-            },
-            bad_pso: Pso {
-                fill_color: Some(bad_color),
-                font: Some(font),
-            },
-            name,
-            value: 0,
-            label_buf: String::new(),
-        };
-        r.refresh_buffer();
-        r
+    /// Create new score starting at zero.
+    pub fn new() -> Self {
+        Self { value: 0 }
+    }
+
+    /// The current score value.
+    pub fn value(&self) -> i32 {
+        self.value
     }
 
     /// Add points to the score (or remove with negative value).
     pub fn add(&mut self, val: i32) {
         self.value += val;
-        self.refresh_buffer();
-    }
-
-    /// Refresh the label buffer by taking the current score value and the label name, and writing it to a cleared
-    /// **label_buf**
-    fn refresh_buffer(&mut self) {
-        self.label_buf.clear();
-        write!(self.label_buf, "{}: {}", self.name, self.value).unwrap();
     }
 }
 
-impl Renderable for Score {
-    /// Draw score to screen with the correct PSO depending on its score value.
-    fn draw(&self, pxs: &PixelScreen) {
-        if self.value < 0 {
-            self.bad_pso.bind(pxs);
-        } else {
-            self.pso.bind(pxs);
+impl Subscriber for Score {
+    /// Kills give points. Missed projectiles, escaped enemies, and enemies crashing into the player
+    /// cost points.
+    fn on_event(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::EnemyKilled { .. } => self.add(5),
+            GameEvent::ProjectileMissed => self.add(-1),
+            GameEvent::EnemyEscaped | GameEvent::PlayerHit { .. } => self.add(-10),
+            GameEvent::ShotFired | GameEvent::WaveCleared(_) => {}
         }
-        pxs.draw_text(&self.label_buf, self.pos);
     }
 }
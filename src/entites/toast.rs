@@ -0,0 +1,99 @@
+//! Toast module.
+//!
+//! A toast is a small, unobtrusive text notification in the bottom left corner of the screen, which
+//! disappears after a short time. It is used to announce unlocked achievements.
+
+use crate::rendering::{
+    self, Align, Backend, CssFont, Footprint, Layer, Pos, Pso, Renderable, Role, Scene, SceneNode,
+    HEIGHT,
+};
+use crate::utils::Timer;
+
+/// How long a toast is shown in ms.
+const DURATION: u32 = 3000;
+
+/// Font of the message.
+const FONT: CssFont = CssFont::monospace(10);
+
+/// Distance of the message from the edges of the game area in virtual pixels.
+const MARGIN: f64 = 1.0;
+
+/// The toast entity.
+///
+/// Contains its PSO, the current message text, and a timer which hides the message again.
+pub struct Toast {
+    pso: Pso,
+    text: String,
+    timer: Option<Timer>,
+}
+
+impl Toast {
+    /// Create new (hidden) toast.
+    pub fn new() -> Self {
+        Self {
+            pso: Pso {
+                fill_color: Some(Role::UiDim),
+                font: Some(FONT),
+                ..Default::default()
+            },
+            text: String::new(),
+            timer: None,
+        }
+    }
+
+    /// Checks, if there is currently no message shown.
+    pub fn is_idle(&self) -> bool {
+        self.timer.is_none()
+    }
+
+    /// Show *text* (lines separated by `\n`) starting at *ts*.
+    pub fn show(&mut self, ts: u32, text: &str) {
+        self.text.clear();
+        self.text.push_str(text);
+        self.timer = Some(Timer::once(ts + DURATION));
+    }
+
+    /// Position of the message. Its last line is at the bottom margin.
+    fn pos(&self) -> Pos {
+        let (_, height) = rendering::measure_text(&self.text, FONT.size());
+        Pos::new(MARGIN, HEIGHT as f64 - MARGIN - height)
+    }
+
+    /// Update the toast every game loop. Hides the message, when its time is up.
+    pub fn update(&mut self, ts: u32) {
+        let expired = match &mut self.timer {
+            Some(t) => t.check(ts, |_off| ()).is_some(),
+            None => false,
+        };
+
+        if expired {
+            self.timer = None;
+        }
+    }
+}
+
+impl SceneNode for Toast {
+    /// Submit the toast, when there is a message shown.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        if !self.is_idle() {
+            scene.submit(Layer::Hud, &self.pso, self);
+        }
+    }
+}
+
+impl Renderable for Toast {
+    /// Draw the message.
+    fn draw(&self, backend: &mut dyn Backend) {
+        rendering::draw_text(backend, &self.text, self.pos(), Align::Left);
+    }
+
+    /// The area of the message.
+    fn footprint(&self) -> Option<Footprint> {
+        Some(Footprint::text(
+            &self.text,
+            self.pos(),
+            FONT.size(),
+            Align::Left,
+        ))
+    }
+}
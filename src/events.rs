@@ -0,0 +1,234 @@
+//! Gameplay event module.
+//!
+//! The simulation does not handle the outcomes of gameplay (kills, misses, etc...) inline. Instead it
+//! emits typed **GameEvent**s into the **Events** queue. The queue is drained once per frame, and
+//! every event is dispatched to all **Subscriber**s (score, achievements, effects, JS callback, ...).
+
+use crate::entites::EnemyType;
+use crate::waves::WaveResult;
+use js_sys::{Function, Object, Reflect};
+use wasm_bindgen::JsValue;
+
+/// All the gameplay events, which can be emitted by the simulation.
+#[derive(Copy, Clone, Debug)]
+pub enum GameEvent {
+    /// The player fired a projectile.
+    ShotFired,
+    /// An enemy of type *kind* was destroyed by a projectile at virtual position (*x*, *y*).
+    EnemyKilled { x: u32, y: u32, kind: EnemyType },
+    /// A projectile missed all enemies and flew into the void.
+    ProjectileMissed,
+    /// An enemy got past the player and escaped into the void.
+    EnemyEscaped,
+    /// An enemy crashed into the player at virtual position (*x*, *y*).
+    PlayerHit { x: u32, y: u32 },
+    /// All enemies of a wave are gone.
+    WaveCleared(WaveResult),
+}
+
+impl GameEvent {
+    /// Name of the event variant.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::ShotFired => "ShotFired",
+            GameEvent::EnemyKilled { .. } => "EnemyKilled",
+            GameEvent::ProjectileMissed => "ProjectileMissed",
+            GameEvent::EnemyEscaped => "EnemyEscaped",
+            GameEvent::PlayerHit { .. } => "PlayerHit",
+            GameEvent::WaveCleared(_) => "WaveCleared",
+        }
+    }
+
+    /// Convert the event into a JS object. It has a `type` field with the variant name, and
+    /// additional fields with the variant data.
+    fn to_js(self) -> JsValue {
+        let o = Object::new();
+        let set = |k: &str, v: JsValue| {
+            let _ = Reflect::set(&o, &k.into(), &v);
+        };
+
+        set("type", self.name().into());
+        match self {
+            GameEvent::EnemyKilled { x, y, kind } => {
+                set("x", x.into());
+                set("y", y.into());
+                set("kind", kind.name().into());
+            }
+            GameEvent::PlayerHit { x, y } => {
+                set("x", x.into());
+                set("y", y.into());
+            }
+            GameEvent::WaveCleared(w) => {
+                set("wave", w.number.into());
+                set("escaped", w.escaped.into());
+            }
+            GameEvent::ShotFired | GameEvent::ProjectileMissed | GameEvent::EnemyEscaped => {}
+        }
+
+        o.into()
+    }
+}
+
+/// Event queue. The simulation emits into it, and it is drained every frame.
+pub struct Events {
+    queue: Vec<GameEvent>,
+}
+
+impl Events {
+    /// Create new empty queue.
+    pub fn new() -> Self {
+        Self {
+            queue: Vec::with_capacity(16),
+        }
+    }
+
+    /// Emit an event (append it to the queue).
+    pub fn emit(&mut self, event: GameEvent) {
+        self.queue.push(event);
+    }
+
+    /// Dispatch all queued events in emission order to every subscriber in *subscribers*, and clear
+    /// the queue.
+    pub fn dispatch(&mut self, subscribers: &mut [&mut dyn Subscriber]) {
+        for e in self.queue.drain(..) {
+            for s in subscribers.iter_mut() {
+                s.on_event(&e);
+            }
+        }
+    }
+}
+
+/// Trait for types, which want to receive gameplay events.
+pub trait Subscriber {
+    /// Called for every event drained from the queue.
+    fn on_event(&mut self, event: &GameEvent);
+}
+
+/// Subscriber, which forwards all events to an optional JS callback function.
+pub struct JsCallback {
+    callback: Option<Function>,
+}
+
+impl JsCallback {
+    /// Create new subscriber without a callback.
+    pub fn new() -> Self {
+        Self { callback: None }
+    }
+
+    /// Set the callback, which receives the events (or remove it with `None`).
+    pub fn set(&mut self, callback: Option<Function>) {
+        self.callback = callback;
+    }
+}
+
+impl Subscriber for JsCallback {
+    /// Call the JS callback with the event converted to an object. Errors thrown by the callback
+    /// are ignored.
+    fn on_event(&mut self, event: &GameEvent) {
+        if let Some(cb) = &self.callback {
+            let _ = cb.call1(&JsValue::NULL, &event.to_js());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::achievements::{Achievements, Tracker};
+    use crate::entites::Lives;
+    use crate::feedback::{Feedback, FeedbackSettings};
+    use crate::waves::WaveResult;
+    use std::cell::RefCell;
+
+    const KILL: GameEvent = GameEvent::EnemyKilled {
+        x: 40,
+        y: 30,
+        kind: EnemyType::Star,
+    };
+
+    /// Subscriber, which records the names of the received events into the shared *log*, together
+    /// with its *id*.
+    struct Recorder<'a> {
+        id: usize,
+        log: &'a RefCell<Vec<(usize, &'static str)>>,
+    }
+
+    impl Subscriber for Recorder<'_> {
+        fn on_event(&mut self, event: &GameEvent) {
+            self.log.borrow_mut().push((self.id, event.name()));
+        }
+    }
+
+    #[test]
+    fn dispatch_order() {
+        let log = RefCell::new(Vec::new());
+        let (mut first, mut second) =
+            (Recorder { id: 0, log: &log }, Recorder { id: 1, log: &log });
+        let mut events = Events::new();
+        events.emit(GameEvent::ShotFired);
+        events.emit(KILL);
+        events.emit(GameEvent::ProjectileMissed);
+        events.dispatch(&mut [&mut first, &mut second]);
+
+        // Event by event, to the subscribers in their order.
+        // --------------------------------------------------
+        assert_eq!(
+            log.into_inner(),
+            [
+                (0, "ShotFired"),
+                (1, "ShotFired"),
+                (0, "EnemyKilled"),
+                (1, "EnemyKilled"),
+                (0, "ProjectileMissed"),
+                (1, "ProjectileMissed"),
+            ]
+        );
+    }
+
+    #[test]
+    fn dispatched_once() {
+        let log = RefCell::new(Vec::new());
+        let (mut first, mut second) =
+            (Recorder { id: 0, log: &log }, Recorder { id: 1, log: &log });
+        let mut events = Events::new();
+        events.emit(GameEvent::EnemyEscaped);
+        events.dispatch(&mut [&mut first, &mut second]);
+        events.dispatch(&mut [&mut first, &mut second]); //<- Nothing emitted in between.
+        assert_eq!(log.borrow().len(), 2);
+
+        // The next tick only hands out its own events.
+        // --------------------------------------------
+        events.emit(GameEvent::ShotFired);
+        events.dispatch(&mut [&mut first, &mut second]);
+        assert_eq!(
+            log.into_inner(),
+            [
+                (0, "EnemyEscaped"),
+                (1, "EnemyEscaped"),
+                (0, "ShotFired"),
+                (1, "ShotFired"),
+            ]
+        );
+    }
+
+    #[test]
+    fn player_hit_subscribers() {
+        let mut lives = Lives::new(3);
+        let mut feedback = Feedback::new(FeedbackSettings::default());
+        let mut achievements = Achievements::new(Tracker::new(None));
+        let mut events = Events::new();
+        events.emit(GameEvent::PlayerHit { x: 40, y: 55 });
+        events.dispatch(&mut [&mut lives, &mut feedback, &mut achievements]);
+        assert_eq!(lives.count(), 2);
+        assert!(feedback.shake() > 0.0);
+
+        // The hit broke the flawless wave.
+        // --------------------------------
+        events.emit(GameEvent::WaveCleared(WaveResult {
+            number: 1,
+            escaped: 0,
+        }));
+        events.dispatch(&mut [&mut lives, &mut feedback, &mut achievements]);
+        assert!(achievements.next_unlock().is_none());
+    }
+}
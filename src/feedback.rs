@@ -0,0 +1,288 @@
+//! Feedback module. Game-feel effects, which react to gameplay events:
+//!
+//! * Screen shake: a decaying camera offset of the game area after player hits and the explosion of the
+//!   last enemy of a wave.
+//! * Hit-stop: the simulation is frozen for a few frames after every kill.
+//! * Flash: the game area flashes up, when a wave is cleared (there are no bosses yet, the end of a wave
+//!   is the biggest defeat there is).
+//!
+//! The strength of every effect is scaled by its intensity in the **FeedbackSettings** (0 disables it).
+//! The global *reduced_motion* switch disables all of them.
+//!
+//! Like the other effects, feedback is not part of the simulation. The hit-stop does not stop the world
+//! from the inside. Instead, the frontend does not update it while it is stopped, and leaves the stopped
+//! time out of the world timestamps (see **stopped()**), so replays do not contain the frozen frames.
+
+use crate::events::{GameEvent, Subscriber};
+use crate::rendering::{
+    Backend, Footprint, Layer, Pos, Pso, Renderable, Role, Scene, SceneNode, HEIGHT, WIDTH,
+};
+use crate::utils::{self, Rng};
+
+lazy_static! {
+    /// PSO of the flash (flash fill color).
+    pub static ref FLASH_PSO: Pso = Pso {
+        fill_color: Some(Role::Flash),
+        ..Default::default()
+    };
+}
+
+/// Largest camera offset of the screen shake in virtual pixels.
+const MAX_SHAKE: f32 = 2.0;
+
+/// Shake (trauma) lost per second. A full shake lasts half a second.
+const SHAKE_DECAY: f32 = 2.0;
+
+/// Shake of a player hit, and of the explosion of the last enemy of a wave.
+const HIT_SHAKE: f32 = 1.0;
+const WAVE_SHAKE: f32 = 0.6;
+
+/// Duration of the hit-stop after a kill in ms.
+const HIT_STOP: u32 = 50;
+
+/// Duration of the flash in ms, and its opacity at the start.
+const FLASH_DURATION: u32 = 300;
+const FLASH_ALPHA: f32 = 140.0;
+
+/// Settings of the feedback effects. Intensities range from 0 (disabled) to 1 (full strength).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FeedbackSettings {
+    pub shake: f32,
+    pub hit_stop: f32,
+    pub flash: f32,
+    pub reduced_motion: bool,
+}
+
+impl Default for FeedbackSettings {
+    /// All effects with full intensity.
+    fn default() -> Self {
+        Self {
+            shake: 1.0,
+            hit_stop: 1.0,
+            flash: 1.0,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl FeedbackSettings {
+    /// The effective *intensity* of an effect. Zero with reduced motion.
+    fn scale(&self, intensity: f32) -> f32 {
+        if self.reduced_motion {
+            0.0
+        } else {
+            utils::cap(intensity, 0.0, 1.0)
+        }
+    }
+}
+
+/// Feedback state. Contains the settings, the timestamp of the last update, the shake (trauma, from 0
+/// to 1) with the current camera offset, the remaining and the total hit-stop time in ms, the opacity of
+/// the flash, and the random number generator of the shake (visual only, not part of the simulation).
+pub struct Feedback {
+    settings: FeedbackSettings,
+    last_ts: Option<u32>,
+    shake: f32,
+    offset: (f32, f32),
+    stop: u32,
+    stopped: u32,
+    flash: f32,
+    rng: Rng,
+}
+
+impl Feedback {
+    /// Create new feedback state with *settings* and no active effects.
+    pub fn new(settings: FeedbackSettings) -> Self {
+        Self {
+            settings,
+            last_ts: None,
+            shake: 0.0,
+            offset: (0.0, 0.0),
+            stop: 0,
+            stopped: 0,
+            flash: 0.0,
+            rng: Rng::new(0x5ba4_e5e7),
+        }
+    }
+
+    /// The settings.
+    pub fn settings(&self) -> FeedbackSettings {
+        self.settings
+    }
+
+    /// Change the *settings*. Effects, which are disabled by them, end right away.
+    pub fn set_settings(&mut self, settings: FeedbackSettings) {
+        self.settings = settings;
+        self.shake = self.shake.min(settings.scale(settings.shake));
+        self.flash = self.flash.min(settings.scale(settings.flash));
+        if settings.scale(settings.hit_stop) == 0.0 {
+            self.stop = 0;
+        }
+    }
+
+    /// Update all effects to timestamp *ts* (in ms). Has to be called every frame, also while the
+    /// simulation is stopped.
+    pub fn update(&mut self, ts: u32) {
+        let dt = ts.saturating_sub(self.last_ts.unwrap_or(ts));
+        self.last_ts = Some(ts);
+
+        // Count down the hit-stop.
+        // ------------------------
+        let stop = dt.min(self.stop);
+        self.stop -= stop;
+        self.stopped += stop;
+
+        // Let the shake and the flash fade. The direction of the shake changes every frame.
+        // ---------------------------------------------------------------------------------
+        self.shake = (self.shake - SHAKE_DECAY * dt as f32 / 1000.0).max(0.0);
+        self.flash = (self.flash - dt as f32 / FLASH_DURATION as f32).max(0.0);
+
+        let max = MAX_SHAKE * self.shake * self.shake; //<- Squared, so it calms down smoothly.
+        self.offset = (self.random(max), self.random(max));
+    }
+
+    /// Random number from -*max* to *max*.
+    fn random(&mut self, max: f32) -> f32 {
+        max * (self.rng.next_u32() as f32 / u32::MAX as f32 * 2.0 - 1.0)
+    }
+
+    /// Checks, if the simulation is stopped (hit-stop) in the current frame.
+    pub fn hit_stop(&self) -> bool {
+        self.stop > 0
+    }
+
+    /// Total time in ms the simulation was stopped so far. The world timestamps have to leave it out.
+    pub fn stopped(&self) -> u32 {
+        self.stopped
+    }
+
+    /// Current shake (trauma, from 0 to 1).
+    #[cfg(test)]
+    pub fn shake(&self) -> f32 {
+        self.shake
+    }
+
+    /// Add *amount* of shake.
+    fn add_shake(&mut self, amount: f32) {
+        self.shake = (self.shake + amount * self.settings.scale(self.settings.shake)).min(1.0);
+    }
+}
+
+impl Subscriber for Feedback {
+    /// Stop the simulation after kills, shake after player hits, and shake and flash after a cleared wave.
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::EnemyKilled { .. } => {
+                let stop = HIT_STOP as f32 * self.settings.scale(self.settings.hit_stop);
+                self.stop = self.stop.max(stop as u32);
+            }
+            GameEvent::PlayerHit { .. } => self.add_shake(HIT_SHAKE),
+            GameEvent::WaveCleared(_) => {
+                self.add_shake(WAVE_SHAKE);
+                self.flash = self.settings.scale(self.settings.flash);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl SceneNode for Feedback {
+    /// Set the camera offset of the shake, and submit the flash, while it is visible.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        scene.set_camera(Pos::new(self.offset.0 as f64, self.offset.1 as f64));
+        if self.flash > 0.0 {
+            scene.submit(Layer::Flash, &FLASH_PSO, self);
+        }
+    }
+}
+
+impl Renderable for Feedback {
+    /// Draw the flash over the whole game area.
+    fn draw(&self, backend: &mut dyn Backend) {
+        backend.save();
+        backend.set_alpha((self.flash * FLASH_ALPHA) as u8);
+        backend.draw_rect(Pos::new(0.0, 0.0), WIDTH as f64, HEIGHT as f64);
+        backend.restore();
+    }
+
+    /// The whole game area, keyed by the opacity.
+    fn footprint(&self) -> Option<Footprint> {
+        let alpha = (self.flash * FLASH_ALPHA) as u8;
+        Some(Footprint::new(0, 0, WIDTH, HEIGHT, alpha as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entites::EnemyType;
+
+    const KILL: GameEvent = GameEvent::EnemyKilled {
+        x: 40,
+        y: 30,
+        kind: EnemyType::Star,
+    };
+
+    /// Feedback with full intensities, updated once at 0 ms.
+    fn feedback() -> Feedback {
+        let mut feedback = Feedback::new(FeedbackSettings::default());
+        feedback.update(0);
+        feedback
+    }
+
+    #[test]
+    fn shake_decays() {
+        let mut feedback = feedback();
+        feedback.on_event(&GameEvent::PlayerHit { x: 40, y: 55 });
+        assert_eq!(feedback.shake, HIT_SHAKE);
+
+        feedback.update(250);
+        assert_eq!(feedback.shake, 0.5);
+        let max = MAX_SHAKE * 0.25;
+        assert!(feedback.offset.0.abs() <= max && feedback.offset.1.abs() <= max);
+
+        feedback.update(500);
+        assert_eq!(feedback.shake, 0.0);
+        assert_eq!(feedback.offset, (0.0, 0.0));
+    }
+
+    #[test]
+    fn hit_stop_duration() {
+        let mut feedback = feedback();
+        feedback.on_event(&KILL);
+        for ts in [16, 32, 48] {
+            feedback.update(ts);
+            assert!(feedback.hit_stop());
+        }
+        feedback.update(64);
+        assert!(!feedback.hit_stop());
+        assert_eq!(feedback.stopped(), HIT_STOP);
+
+        // Kills during a hit-stop do not add up, and the intensity scales the duration.
+        // -----------------------------------------------------------------------------
+        feedback.on_event(&KILL);
+        feedback.on_event(&KILL);
+        feedback.update(164);
+        assert_eq!(feedback.stopped(), HIT_STOP * 2);
+
+        feedback.set_settings(FeedbackSettings {
+            hit_stop: 0.5,
+            ..Default::default()
+        });
+        feedback.on_event(&KILL);
+        feedback.update(264);
+        assert_eq!(feedback.stopped(), HIT_STOP * 2 + HIT_STOP / 2);
+    }
+
+    #[test]
+    fn reduced_motion() {
+        let mut feedback = Feedback::new(FeedbackSettings {
+            reduced_motion: true,
+            ..Default::default()
+        });
+        feedback.on_event(&KILL);
+        feedback.on_event(&GameEvent::PlayerHit { x: 40, y: 55 });
+        assert!(!feedback.hit_stop());
+        assert_eq!(feedback.shake, 0.0);
+    }
+}
@@ -0,0 +1,94 @@
+//! Ghost module.
+//!
+//! The ghost is the personal best run of the player, played back from its replay in a second
+//! **World** in lockstep with the live one. Only the ghost player ship is drawn (translucently). Its
+//! score, and how many points the live run is ahead or behind, are shown by the HUD.
+//!
+//! The personal best run (score and encoded replay) is persisted in local storage.
+
+use crate::entites::Score;
+use crate::events::Subscriber;
+use crate::rendering::{Layer, Pso, Role, Scene, SceneNode};
+use crate::replay::{Replay, ReplayPlayer};
+use crate::storage;
+use crate::world::World;
+
+/// Local storage key of the personal best run. The value is `<score>:<encoded replay>`.
+const STORAGE_KEY: &str = "rinvaders.best_run";
+
+/// Load the personal best run from local storage.
+fn load_best() -> Option<(i32, Replay)> {
+    let stored = storage::load(STORAGE_KEY)?;
+    let mut parts = stored.splitn(2, ':');
+    let score = parts.next()?.parse().ok()?;
+    let replay = Replay::decode(parts.next()?)?;
+    Some((score, replay))
+}
+
+/// Store the run (*replay* with the final *score*) as personal best, if it beats the stored one (or
+/// there is none yet).
+pub fn save_if_best(replay: &Replay, score: i32) {
+    if let Some((best, _)) = load_best() {
+        if best >= score {
+            return;
+        }
+    }
+
+    storage::store(STORAGE_KEY, &format!("{}:{}", score, replay.encode()));
+}
+
+/// The ghost. Contains its world, the replay player stepping it, its score, the final score of the
+/// personal best run, and the PSO for translucent (tinted) drawing.
+pub struct Ghost {
+    world: World,
+    player: ReplayPlayer,
+    score: Score,
+    best: i32,
+    pso: Pso,
+}
+
+impl Ghost {
+    /// Create the ghost of the personal best run. Returns `None`, when there is no personal best yet.
+    pub fn load() -> Option<Self> {
+        let (best, replay) = load_best()?;
+
+        Some(Self {
+            world: World::new(replay.seed()),
+            player: ReplayPlayer::new(replay),
+            score: Score::new(),
+            best,
+            pso: Pso {
+                fill_color: Some(Role::Accent),
+                alpha: Some(90),
+                ..Default::default()
+            },
+        })
+    }
+
+    /// The current score of the ghost.
+    pub fn score(&self) -> i32 {
+        self.score.value()
+    }
+
+    /// The final score of the personal best run.
+    pub fn best(&self) -> i32 {
+        self.best
+    }
+
+    /// Update the ghost every game loop. Steps the ghost world up to the relative timestamp *ts* of
+    /// the live world.
+    pub fn update(&mut self, ts: u32) {
+        self.player.step_until(&mut self.world, ts);
+        self.world
+            .dispatch(&mut [&mut self.score as &mut dyn Subscriber]);
+    }
+}
+
+impl SceneNode for Ghost {
+    /// Submit the ghost ship translucently (tinted), until its run is over.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        if !self.player.finished() {
+            scene.submit(Layer::Ghost, &self.pso, self.world.player());
+        }
+    }
+}
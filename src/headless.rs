@@ -0,0 +1,309 @@
+//! Headless module.
+//!
+//! Runs the game without a browser. A **Replay** can be played back, and the frames drawn into any
+//! **Backend**, e.g. the software **Framebuffer**. The frames look like the ones of the live game
+//! (background, world, effects and HUD), so they can be written as PNG screenshots, and compared in
+//! golden-image tests (e.g. "after this replay, frame 500 looks like this"). The statistics of a
+//! played back session can be read natively as well (e.g. to compare balance changes across builds).
+//!
+//! A game can also be played live, with input supplied by a native frontend (like the terminal binary),
+//! which draws it through its own **Backend**. Live games are recorded, so they can be played back
+//! later. Hit-stops only happen in live games (replays do not contain the stopped frames).
+//!
+//! A range of frames can also be captured into an animated GIF (e.g. for bug reports, or to share a
+//! highlight), see **capture_gif()**.
+
+use crate::daily;
+use crate::feedback::FeedbackSettings;
+use crate::hud::{Hud, HudLayout};
+use crate::input::Input;
+use crate::rendering::{
+    Backend, Damage, Framebuffer, GifEncoder, Palette, Role, Scene, SceneNode, HEIGHT, WIDTH,
+};
+use crate::replay::{Replay, ReplayPlayer};
+use crate::rules::Rules;
+use crate::session::Session;
+use crate::starfield::StarfieldSettings;
+use crate::stats::SessionStats;
+use bumpalo::Bump;
+use std::cell::RefCell;
+use std::ops::Range;
+
+/// Shortest time between two frames of a GIF in ms (browsers slow down GIFs with shorter delays).
+const MIN_GIF_DELAY: u32 = 20;
+
+/// How long the last frame of a GIF is shown in ms, before the animation starts over.
+const LAST_GIF_DELAY: u32 = 1000;
+
+/// Largest GIF scale (pixels per virtual pixel).
+const MAX_GIF_SCALE: u32 = 10;
+
+/// Settings of a GIF capture (see **Headless::capture_gif()**). Frames are drawn with *scale* pixels per
+/// virtual pixel, and *frame_skip* frames are skipped after every captured one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GifSettings {
+    pub scale: u32,
+    pub frame_skip: u32,
+}
+
+impl Default for GifSettings {
+    /// 160x120 pixels with every other frame (~30 FPS).
+    fn default() -> Self {
+        Self {
+            scale: 2,
+            frame_skip: 1,
+        }
+    }
+}
+
+/// A game with the classic rules, or the ones of a daily challenge (played back from a replay, or live),
+/// together with everything that is drawn on top of the world, and the palette it is drawn with.
+pub struct Headless {
+    session: Session,
+    player: ReplayPlayer,
+    frame: u32,
+    ts: u32,
+    hud: Hud,
+    palette: &'static Palette,
+    arena: RefCell<Bump>,
+}
+
+impl Headless {
+    /// Create new headless playback of *replay*. It is driven by **step()**.
+    pub fn new(replay: Replay) -> Self {
+        Self::with_rules(replay, Rules::default())
+    }
+
+    /// Create new headless playback of *replay* of the daily challenge of *date* (`yyyymmdd`, it is
+    /// played with the rule modifiers of that day).
+    pub fn daily(replay: Replay, date: u32) -> Self {
+        Self::with_rules(replay, daily::rules(date))
+    }
+
+    /// Create new headless playback of *replay* with *rules*.
+    fn with_rules(replay: Replay, rules: Rules) -> Self {
+        Self {
+            session: Session::new(
+                replay.seed(),
+                rules,
+                FeedbackSettings::default(),
+                StarfieldSettings::default(),
+            ),
+            player: ReplayPlayer::new(replay),
+            frame: 0,
+            ts: 0,
+            hud: Hud::new(HudLayout::default()),
+            palette: Palette::default_theme(),
+            arena: RefCell::new(Bump::new()),
+        }
+    }
+
+    /// Create new live game with a world initialized by *seed*. It is driven by **update()**.
+    pub fn live(seed: u32) -> Self {
+        Self::new(Replay::new(seed))
+    }
+
+    /// Number of frames simulated so far.
+    pub fn frame(&self) -> u32 {
+        self.frame
+    }
+
+    /// Checks, if the playback is over (all frames were played back, or the game is over).
+    pub fn finished(&self) -> bool {
+        self.player.finished() || self.game_over()
+    }
+
+    /// Checks, if the game is over (the player has no lives left).
+    pub fn game_over(&self) -> bool {
+        self.session.game_over().is_some()
+    }
+
+    /// The current score.
+    pub fn score(&self) -> i32 {
+        self.session.score().value()
+    }
+
+    /// The statistics of the session so far (e.g. to export them as JSON after a replay).
+    pub fn stats(&self) -> &SessionStats {
+        self.session.stats()
+    }
+
+    /// The recording of all frames simulated so far.
+    pub fn replay(&self) -> &Replay {
+        self.session.recording()
+    }
+
+    /// Play back the next frame of the replay. Returns false, when the playback was already finished.
+    pub fn step(&mut self) -> bool {
+        if self.game_over() {
+            return false;
+        }
+
+        match self.player.next_frame() {
+            Some(f) => {
+                self.session.begin_frame(f.ts);
+                self.simulate(f.ts, &f.input());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Simulate a live frame at timestamp *ts* (in ms since the start of the game) with the input state
+    /// (*left*, *right* and *shoot*). Only the feedback effects are updated, when the game is over, or
+    /// during a hit-stop.
+    pub fn update(&mut self, ts: u32, left: bool, right: bool, shoot: bool) {
+        self.session.begin_frame(ts);
+        if !self.session.frozen() {
+            let ts = ts - self.session.feedback().stopped(); //<- The world does not see the stopped time.
+            self.simulate(ts, &Input::from_state(left, right, shoot));
+        }
+    }
+
+    /// Simulate a single frame (the same way **Game::update()** does), and lay out the HUD.
+    fn simulate(&mut self, ts: u32, input: &Input) {
+        self.session.simulate(ts, input, &mut []);
+        self.hud.update(&self.session.hud_state());
+        #[cfg(feature = "debug-overlay")]
+        self.session.update_debug(None); //<- The backend is not known here.
+
+        self.ts = ts;
+        self.frame += 1;
+    }
+
+    /// Play back frames until *frame* frames were played back (or the playback is finished). Returns the
+    /// number of frames played back.
+    pub fn step_to(&mut self, frame: u32) -> u32 {
+        while self.frame < frame && self.step() {}
+        self.frame
+    }
+
+    /// Play back the frames in *frames* (the end excluded), and capture them into an animated GIF with
+    /// *settings*. The range is in recorded frames of the replay (like **frame()**), not in game ticks.
+    /// Frames before the range are played back without drawing, and the playback stays at the end of
+    /// the range (or where it finished). Returns the GIF file.
+    ///
+    /// The delays of the GIF follow the recorded timestamps, so it plays in real time. Frames less than
+    /// 20 ms after the last captured one are skipped as well, and the last one is shown for a second.
+    pub fn capture_gif(&mut self, frames: Range<u32>, settings: &GifSettings) -> Vec<u8> {
+        self.step_to(frames.start);
+
+        let scale = settings.scale.clamp(1, MAX_GIF_SCALE);
+        let mut screen = Framebuffer::new(WIDTH * scale, HEIGHT * scale);
+        let mut gif = GifEncoder::new(screen.width(), screen.height());
+
+        // The delay of a frame is only known, when the next one is captured, so the last captured frame
+        // is held back with its timestamp.
+        // -------------------------------------------------------------------------------------------------
+        let mut held: Option<(Vec<u8>, u32)> = None;
+        while self.frame < frames.end {
+            let due = held
+                .as_ref()
+                .map(|(_, ts)| self.ts >= ts + MIN_GIF_DELAY)
+                .unwrap_or(true);
+            if due {
+                self.draw(&mut screen);
+                if let Some((pixels, ts)) = held.replace((screen.pixels().to_vec(), self.ts)) {
+                    gif.add_frame(&pixels, self.ts / 10 - ts / 10); //<- Hundredths of a second.
+                }
+            }
+
+            let before = self.frame;
+            if self.step_to(before + 1 + settings.frame_skip) == before {
+                break;
+            }
+        }
+
+        if let Some((pixels, _)) = held {
+            gif.add_frame(&pixels, LAST_GIF_DELAY / 10);
+        }
+        gif.finish()
+    }
+
+    /// The palette the game is drawn with.
+    pub fn palette(&self) -> &'static Palette {
+        self.palette
+    }
+
+    /// Draw the game with *palette* from now on.
+    pub fn set_palette(&mut self, palette: &'static Palette) {
+        self.palette = palette;
+    }
+
+    /// The settings of the feedback effects (screen shake, hit-stop and flash).
+    pub fn feedback_settings(&self) -> FeedbackSettings {
+        self.session.feedback().settings()
+    }
+
+    /// Change the *settings* of the feedback effects.
+    pub fn set_feedback_settings(&mut self, settings: FeedbackSettings) {
+        self.session.feedback_mut().set_settings(settings);
+    }
+
+    /// Checks, if the debug overlay is shown.
+    #[cfg(feature = "debug-overlay")]
+    pub fn debug_overlay(&self) -> bool {
+        self.session.debug_overlay()
+    }
+
+    /// Show or hide the debug overlay. It shows the state from the next simulated frame on.
+    #[cfg(feature = "debug-overlay")]
+    pub fn set_debug_overlay(&mut self, on: bool) {
+        self.session.set_debug_overlay(on);
+    }
+
+    /// Checks, if moving entities are drawn on the grid instead of smoothly between the game ticks.
+    pub fn snap_to_grid(&self) -> bool {
+        self.session.snap_to_grid()
+    }
+
+    /// Switch snapping moving entities to the grid *on* or off.
+    pub fn set_snap_to_grid(&mut self, on: bool) {
+        self.session.set_snap_to_grid(on);
+    }
+
+    /// The layout of the HUD.
+    pub fn hud_layout(&self) -> &HudLayout {
+        self.hud.layout()
+    }
+
+    /// Change the *layout* of the HUD. It is used from the next simulated frame on.
+    pub fn set_hud_layout(&mut self, layout: HudLayout) {
+        self.hud.set_layout(layout);
+    }
+
+    /// The settings of the starfield in the background.
+    pub fn starfield_settings(&self) -> &StarfieldSettings {
+        self.session.starfield().settings()
+    }
+
+    /// Change the *settings* of the starfield.
+    pub fn set_starfield_settings(&mut self, settings: StarfieldSettings) {
+        self.session.starfield_mut().set_settings(settings);
+    }
+
+    /// Draw the current frame (like **Game::render()**) into *backend*. Clears it first.
+    pub fn draw(&self, backend: &mut dyn Backend) {
+        backend.clear(self.palette.color(Role::Background));
+        let mut arena = self.arena.borrow_mut();
+        arena.reset();
+        self.scene(&arena).draw(backend, self.palette);
+    }
+
+    /// Draw only the regions of the current frame, which changed since the last frame drawn with *damage*
+    /// into *backend* (dirty rects, like **Game::render()** with them enabled).
+    pub fn draw_damaged(&self, backend: &mut dyn Backend, damage: &mut Damage) {
+        let background = self.palette.color(Role::Background);
+        let mut arena = self.arena.borrow_mut();
+        arena.reset();
+        self.scene(&arena)
+            .draw_damaged(backend, self.palette, background, damage);
+    }
+
+    /// The scene of the current frame (allocated in the reset frame *arena*).
+    fn scene<'a>(&'a self, arena: &'a Bump) -> Scene<'a> {
+        let mut scene = Scene::new(arena);
+        scene.add(&[&self.session as &dyn SceneNode, &self.hud as &dyn SceneNode]);
+        scene
+    }
+}
@@ -0,0 +1,674 @@
+//! HUD module. The head-up display on top of the game: score, lives, wave and the other widgets.
+//!
+//! Every **Widget** is placed at an **Anchor** (a corner, or the middle of the top or bottom edge of the
+//! game area) by the **HudLayout**. The widgets of an anchor are stacked from the edge inwards in the
+//! order of the layout, and are aligned like the anchor (left, centered or right). Hidden widgets (e.g.
+//! the high score, while there is none) leave no gap, so widgets never overlap, whatever they show.
+//!
+//! The layout adapts to the viewport. On outputs smaller than the virtual grid, the text is enlarged, so
+//! a font pixel still covers a whole pixel of the output (see **Hud::fit()**). All colors are roles of
+//! the palette, so the HUD follows the theme.
+//!
+//! Widgets are placed at whole virtual pixels, so their text and icons are on the grid of the sprites.
+//!
+//! The HUD does not keep any game state. The frontend collects the values to show into a
+//! **HudState** every frame, and hands them to **Hud::update()**. Values, which are not set, hide
+//! their widgets. The widgets are only laid out again, when the values, the layout or the viewport
+//! changed. The game has no score multipliers, power-ups and bosses yet, so their values are never
+//! set, and their widgets stay hidden.
+
+use crate::rendering::{
+    self, Align, Backend, CssFont, Footprint, Layer, Pos, Pso, Renderable, Role, Scene, SceneNode,
+    Viewport, HEIGHT, WIDTH,
+};
+use crate::sprites::{self, Sprite};
+use crate::utils;
+
+/// Distance of the anchors from the edges of the game area in virtual pixels.
+const MARGIN: f64 = 1.0;
+
+/// Space between stacked widgets in virtual pixels.
+const GAP: f64 = 1.0;
+
+/// Font size of the labels (a font pixel per virtual pixel).
+const FONT: u32 = 10;
+
+/// Space between the icons of the lives in virtual pixels.
+const ICON_SPACING: u32 = 1;
+
+/// Opacity of the ghost score (like the ghost ship).
+const GHOST_ALPHA: u8 = 90;
+
+/// Width and height of the boss bar in virtual pixels.
+const BOSS_BAR: (f64, f64) = (30.0, 1.0);
+
+/// Anchors of the HUD. The corners, and the middle of the top and the bottom edge of the game area.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    /// Horizontal alignment of the widgets at the anchor.
+    fn align(self) -> Align {
+        match self {
+            Anchor::TopLeft | Anchor::BottomLeft => Align::Left,
+            Anchor::TopCenter | Anchor::BottomCenter => Align::Center,
+            Anchor::TopRight | Anchor::BottomRight => Align::Right,
+        }
+    }
+
+    /// Checks, if the anchor is at the top edge (widgets are stacked downwards), or at the bottom edge
+    /// (upwards).
+    fn top(self) -> bool {
+        match self {
+            Anchor::TopLeft | Anchor::TopCenter | Anchor::TopRight => true,
+            Anchor::BottomLeft | Anchor::BottomCenter | Anchor::BottomRight => false,
+        }
+    }
+
+    /// Position of the anchor. The edge of the first widget, inside of the margin.
+    fn origin(self) -> Pos {
+        let x = match self.align() {
+            Align::Left => MARGIN,
+            Align::Center => WIDTH as f64 / 2.0,
+            Align::Right => WIDTH as f64 - MARGIN,
+        };
+        let y = if self.top() {
+            MARGIN
+        } else {
+            HEIGHT as f64 - MARGIN
+        };
+        Pos::new(x, y)
+    }
+}
+
+/// The widgets of the HUD.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Widget {
+    /// The frames per second.
+    Fps,
+    /// The score (in the warning color, while it is negative).
+    Score,
+    /// The high score (hidden, when there is none).
+    HighScore,
+    /// The score of the ghost, and how far the live run is ahead of it (hidden without a ghost).
+    Ghost,
+    /// An icon of the player ship for every remaining life.
+    Lives,
+    /// The number of the current wave.
+    Wave,
+    /// The score multiplier (hidden, unless there is one).
+    Multiplier,
+    /// A row for every active power-up with its remaining seconds (hidden, when there are none).
+    PowerUps,
+    /// The health of the boss (hidden, unless there is one).
+    BossBar,
+}
+
+/// Layout of the HUD. The widgets at every anchor, from the edge of the game area inwards. Widgets, which
+/// are not in the layout, are not shown.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HudLayout {
+    pub anchors: Vec<(Anchor, Vec<Widget>)>,
+}
+
+impl Default for HudLayout {
+    /// FPS, wave, lives and multiplier in the top left corner, the boss bar at the top center, the
+    /// scores in the top right corner, and the power-ups at the bottom center.
+    fn default() -> Self {
+        Self {
+            anchors: vec![
+                (
+                    Anchor::TopLeft,
+                    vec![Widget::Fps, Widget::Wave, Widget::Lives, Widget::Multiplier],
+                ),
+                (Anchor::TopCenter, vec![Widget::BossBar]),
+                (
+                    Anchor::TopRight,
+                    vec![Widget::Score, Widget::HighScore, Widget::Ghost],
+                ),
+                (Anchor::BottomCenter, vec![Widget::PowerUps]),
+            ],
+        }
+    }
+}
+
+/// The values shown by the HUD in a frame. The *fps* label, the *score*, the *high_score*, the
+/// current score of the *ghost*, the remaining *lives*, the *wave* number, the score *multiplier*,
+/// the active *power_ups* (name and remaining time in ms), and the health of the *boss* (from 0 to
+/// 1).
+#[derive(Default)]
+pub struct HudState<'a> {
+    pub fps: &'a str,
+    pub score: i32,
+    pub high_score: Option<i32>,
+    pub ghost: Option<i32>,
+    pub lives: u32,
+    pub wave: u32,
+    pub multiplier: Option<u32>,
+    pub power_ups: &'a [(&'a str, u32)],
+    pub boss: Option<f32>,
+}
+
+/// The values of the **HudState**, which the widgets were laid out with, and the font size of the
+/// viewport then (*min_font*). Power-ups are kept with their remaining seconds, and the boss with
+/// the filled width of its bar, like they are shown.
+#[derive(Default)]
+struct Shown {
+    fps: String,
+    score: i32,
+    high_score: Option<i32>,
+    ghost: Option<i32>,
+    lives: u32,
+    wave: u32,
+    multiplier: Option<u32>,
+    power_ups: Vec<(String, u32)>,
+    boss: Option<f64>,
+    min_font: u32,
+}
+
+impl Shown {
+    /// Checks, if *state* with *min_font* is what is shown.
+    fn equals(&self, state: &HudState, min_font: u32) -> bool {
+        self.fps == state.fps
+            && self.score == state.score
+            && self.high_score == state.high_score
+            && self.ghost == state.ghost
+            && self.lives == state.lives
+            && self.wave == state.wave
+            && self.multiplier == state.multiplier
+            && self.power_ups.len() == state.power_ups.len()
+            && self
+                .power_ups
+                .iter()
+                .zip(state.power_ups.iter())
+                .all(|(a, b)| a.0 == b.0 && a.1 == seconds(b.1))
+            && self.boss == state.boss.map(boss_bar)
+            && self.min_font == min_font
+    }
+
+    /// Remember *state* with *min_font* (the label keeps its buffer).
+    fn set(&mut self, state: &HudState, min_font: u32) {
+        self.fps.clear();
+        self.fps.push_str(state.fps);
+        self.score = state.score;
+        self.high_score = state.high_score;
+        self.ghost = state.ghost;
+        self.lives = state.lives;
+        self.wave = state.wave;
+        self.multiplier = state.multiplier;
+        self.power_ups.clear();
+        self.power_ups.extend(
+            state
+                .power_ups
+                .iter()
+                .map(|(name, ms)| (name.to_string(), seconds(*ms))),
+        );
+        self.boss = state.boss.map(boss_bar);
+        self.min_font = min_font;
+    }
+}
+
+/// Remaining time *ms* in whole seconds (rounded up), like it is shown.
+fn seconds(ms: u32) -> u32 {
+    ms.div_ceil(1000)
+}
+
+/// Filled width of the boss bar in virtual pixels with the *health* of the boss.
+fn boss_bar(health: f32) -> f64 {
+    (BOSS_BAR.0 * utils::cap(health, 0.0, 1.0) as f64).round()
+}
+
+/// What a part of a widget draws: a line of text with its font size, a row of sprite icons, or a
+/// filled rect.
+enum Shape {
+    Text(String, u32),
+    Icons(&'static Sprite, u32),
+    Rect,
+}
+
+/// A laid out part of a widget. Its PSO, its shape, and its top left corner and size in virtual pixels.
+struct Part {
+    pso: Pso,
+    shape: Shape,
+    pos: Pos,
+    width: f64,
+    height: f64,
+}
+
+impl Part {
+    /// New part with *shape* drawn with the fill color *role* (and *alpha*). Its position is set by the
+    /// layout.
+    fn new(role: Role, alpha: Option<u8>, shape: Shape) -> Self {
+        let (width, height, font) = match &shape {
+            Shape::Text(text, size) => {
+                let (w, h) = rendering::measure_text(text, *size);
+                (w, h, Some(CssFont::monospace(*size)))
+            }
+            Shape::Icons(sprite, count) => {
+                let (w, h) = sprite.bounds();
+                let width = (w + ICON_SPACING) * count - ICON_SPACING;
+                (width as f64, h as f64, None)
+            }
+            Shape::Rect => (0.0, 0.0, None),
+        };
+
+        Self {
+            pso: Pso {
+                fill_color: Some(role),
+                font,
+                alpha,
+            },
+            shape,
+            pos: Pos::new(0.0, 0.0),
+            width,
+            height,
+        }
+    }
+
+    /// New filled rect with *width* and *height* in the color *role*.
+    fn rect(role: Role, width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            ..Self::new(role, None, Shape::Rect)
+        }
+    }
+}
+
+impl Renderable for Part {
+    fn draw(&self, backend: &mut dyn Backend) {
+        match &self.shape {
+            Shape::Text(text, _) => rendering::draw_text(backend, text, self.pos, Align::Left),
+            Shape::Icons(sprite, count) => {
+                let step = (sprite.bounds().0 + ICON_SPACING) as f64;
+                for i in 0..*count {
+                    let pos = Pos::new(self.pos.x() + i as f64 * step, self.pos.y());
+                    sprite.draw(backend, 0, pos);
+                }
+            }
+            Shape::Rect => backend.draw_rect(self.pos, self.width, self.height),
+        }
+    }
+
+    /// The area of the part. Text is keyed by its content, icons by their count, and rects by their
+    /// width.
+    fn footprint(&self) -> Option<Footprint> {
+        Some(match &self.shape {
+            Shape::Text(text, size) => Footprint::text(text, self.pos, *size, Align::Left),
+            Shape::Icons(_, count) => {
+                Footprint::covering(self.pos, self.width, self.height, *count as u64)
+            }
+            Shape::Rect => {
+                Footprint::covering(self.pos, self.width, self.height, self.width.to_bits())
+            }
+        })
+    }
+}
+
+/// The HUD. Contains the layout, the smallest readable font size of the output, the sprite of the lives
+/// icons, and the parts of the widgets laid out by the last update with the values they show (`None`,
+/// when they have to be laid out again).
+pub struct Hud {
+    layout: HudLayout,
+    min_font: u32,
+    ship: &'static Sprite,
+    parts: Vec<Part>,
+    shown: Option<Shown>,
+}
+
+impl Hud {
+    /// Create new empty HUD with *layout*.
+    pub fn new(layout: HudLayout) -> Self {
+        Self {
+            layout,
+            min_font: 0,
+            ship: sprites::get("player"),
+            parts: vec![],
+            shown: None,
+        }
+    }
+
+    /// The layout.
+    pub fn layout(&self) -> &HudLayout {
+        &self.layout
+    }
+
+    /// Change the *layout*. It is used from the next update on.
+    pub fn set_layout(&mut self, layout: HudLayout) {
+        self.layout = layout;
+        self.shown = None;
+    }
+
+    /// Adapt the text to *viewport*. Font sizes are enlarged (up to twice their size), until a font pixel
+    /// covers at least a pixel of the output. Without a fit, the HUD keeps the sizes of the reference
+    /// scale.
+    pub fn fit(&mut self, viewport: &Viewport) {
+        self.min_font = (1.0 / viewport.scale()).ceil() as u32 * FONT;
+    }
+
+    /// The font size, which is used for labels with the font *size*.
+    fn font(&self, size: u32) -> u32 {
+        size.max(self.min_font).min(size * 2)
+    }
+
+    /// Lay out all widgets of the layout with the values of *state*. Keeps the last layout, when nothing
+    /// changed since.
+    pub fn update(&mut self, state: &HudState) {
+        let min_font = self.min_font;
+        if self
+            .shown
+            .as_ref()
+            .is_some_and(|s| s.equals(state, min_font))
+        {
+            return;
+        }
+
+        let mut parts = std::mem::take(&mut self.parts);
+        parts.clear();
+
+        for (anchor, widgets) in self.layout.anchors.iter() {
+            let origin = anchor.origin();
+            let mut y = origin.y();
+
+            for row in widgets.iter().flat_map(|w| self.rows(*w, state)) {
+                // Parts of a row are placed side by side, and the row is aligned as a whole.
+                // --------------------------------------------------------------------------
+                let width: f64 = row.iter().map(|p| p.width).sum();
+                let height = row.iter().map(|p| p.height).fold(0.0, f64::max);
+                let mut x = match anchor.align() {
+                    Align::Left => origin.x(),
+                    Align::Center => origin.x() - width / 2.0,
+                    Align::Right => origin.x() - width,
+                };
+                let top = if anchor.top() { y } else { y - height };
+
+                for mut part in row {
+                    part.pos = Pos::new(x.round(), top.round()); //<- On the grid.
+                    x += part.width;
+                    parts.push(part);
+                }
+
+                y = if anchor.top() {
+                    y + height + GAP
+                } else {
+                    y - height - GAP
+                };
+            }
+        }
+
+        self.parts = parts;
+        self.shown
+            .get_or_insert_with(Shown::default)
+            .set(state, min_font);
+    }
+
+    /// The rows of *widget* showing *state* (none, when it is hidden).
+    fn rows(&self, widget: Widget, state: &HudState) -> Vec<Vec<Part>> {
+        let size = self.font(FONT);
+        let text = |role: Role, text: String| vec![Part::new(role, None, Shape::Text(text, size))];
+
+        match widget {
+            Widget::Fps if !state.fps.is_empty() => {
+                vec![text(Role::Ui, state.fps.to_owned())]
+            }
+            Widget::Score => {
+                let role = if state.score < 0 {
+                    Role::Warning
+                } else {
+                    Role::Accent
+                };
+                vec![text(role, format!("Score: {}", state.score))]
+            }
+            Widget::HighScore => match state.high_score {
+                Some(h) => vec![text(Role::UiDim, format!("Best: {}", h))],
+                None => vec![],
+            },
+            Widget::Ghost => match state.ghost {
+                Some(g) => {
+                    let score = Shape::Text(format!("Ghost: {}", g), size);
+                    vec![
+                        vec![Part::new(Role::Accent, Some(GHOST_ALPHA), score)],
+                        text(Role::Ui, format!("vs Ghost: {:+}", state.score - g)),
+                    ]
+                }
+                None => vec![],
+            },
+            Widget::Lives if state.lives > 0 => {
+                let icons = Shape::Icons(self.ship, state.lives);
+                vec![vec![Part::new(Role::Player, None, icons)]]
+            }
+            Widget::Wave if state.wave > 0 => {
+                vec![text(Role::Ui, format!("Wave {}", state.wave))]
+            }
+            Widget::Multiplier => match state.multiplier {
+                Some(m) => vec![text(Role::Accent, format!("x{}", m))],
+                None => vec![],
+            },
+            Widget::PowerUps => state
+                .power_ups
+                .iter()
+                .map(|(name, ms)| text(Role::Accent, format!("{} {}s", name, seconds(*ms))))
+                .collect(),
+            Widget::BossBar => match state.boss {
+                Some(health) => {
+                    // The health in the warning color, and the lost health dimmed (empty parts are
+                    // left out).
+                    // ----------------------------------------------------------------------------
+                    let filled = boss_bar(health);
+                    let bar = vec![
+                        Part::rect(Role::Warning, filled, BOSS_BAR.1),
+                        Part::rect(Role::UiDim, BOSS_BAR.0 - filled, BOSS_BAR.1),
+                    ];
+                    vec![bar.into_iter().filter(|p| p.width > 0.0).collect()]
+                }
+                None => vec![],
+            },
+            _ => vec![],
+        }
+    }
+}
+
+impl SceneNode for Hud {
+    /// Submit all parts of the widgets on the HUD layer.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        for part in self.parts.iter() {
+            scene.submit(Layer::Hud, &part.pso, part);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A HUD with the widgets *widgets* at *anchor*, laid out with *state*.
+    fn hud(anchor: Anchor, widgets: Vec<Widget>, state: &HudState) -> Hud {
+        let mut hud = Hud::new(HudLayout {
+            anchors: vec![(anchor, widgets)],
+        });
+        hud.update(state);
+        hud
+    }
+
+    /// The text of every laid out part with its top and bottom edge.
+    fn rows(hud: &Hud) -> Vec<(&str, f64, f64)> {
+        hud.parts
+            .iter()
+            .map(|p| match &p.shape {
+                Shape::Text(text, _) => (text.as_str(), p.pos.y(), p.pos.y() + p.height),
+                Shape::Icons(..) => ("icons", p.pos.y(), p.pos.y() + p.height),
+                Shape::Rect => ("rect", p.pos.y(), p.pos.y() + p.height),
+            })
+            .collect()
+    }
+
+    /// Checks, that *a* and *b* are the same (up to rounding errors).
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn stacked_downwards() {
+        let state = HudState {
+            fps: "60 FPS",
+            wave: 2,
+            ..Default::default()
+        };
+        let hud = hud(Anchor::TopLeft, vec![Widget::Fps, Widget::Wave], &state);
+        let rows = rows(&hud);
+        assert_eq!((rows[0].0, rows[1].0), ("60 FPS", "Wave 2"));
+        assert_close(rows[0].1, MARGIN);
+        assert_close(rows[1].1, rows[0].2 + GAP);
+        assert!(hud.parts.iter().all(|p| p.pos.x() == MARGIN));
+    }
+
+    #[test]
+    fn hidden_widgets_leave_no_gap() {
+        let widgets = vec![Widget::Score, Widget::HighScore, Widget::Ghost];
+        let mut state = HudState {
+            score: 120,
+            ghost: Some(100),
+            ..Default::default()
+        };
+        let hidden = hud(Anchor::TopRight, widgets.clone(), &state);
+        let rows = rows(&hidden);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].0, "Ghost: 100");
+        assert_close(rows[1].1, rows[0].2 + GAP);
+
+        state.high_score = Some(500);
+        let shown = hud(Anchor::TopRight, widgets, &state);
+        assert_eq!(shown.parts.len(), 4);
+        assert!(shown.parts[2].pos.y() > hidden.parts[1].pos.y());
+
+        // Right aligned to the margin.
+        // ----------------------------
+        for p in hidden.parts.iter() {
+            assert_close(p.pos.x() + p.width, WIDTH as f64 - MARGIN);
+        }
+    }
+
+    #[test]
+    fn bottom_anchors_grow_upwards() {
+        let state = HudState {
+            fps: "60 FPS",
+            lives: 3,
+            wave: 2,
+            ..Default::default()
+        };
+        let widgets = vec![Widget::Fps, Widget::Wave, Widget::Lives];
+        let hud = hud(Anchor::BottomCenter, widgets, &state);
+        let rows = rows(&hud);
+        assert_eq!(
+            rows.iter().map(|r| r.0).collect::<Vec<_>>(),
+            ["60 FPS", "Wave 2", "icons"]
+        );
+        assert_close(rows[0].2, HEIGHT as f64 - MARGIN);
+        assert_close(rows[1].2, rows[0].1 - GAP);
+        assert_eq!(rows[2].2, (rows[1].1 - GAP).round()); //<- Sprites are on the grid.
+        for p in hud.parts.iter() {
+            let center = p.pos.x() + p.width / 2.0;
+            assert!((center - WIDTH as f64 / 2.0).abs() <= 0.5); //<- Rounded to the grid.
+        }
+    }
+
+    #[test]
+    fn laid_out_only_on_changes() {
+        let mut state = HudState {
+            fps: "60 FPS",
+            ..Default::default()
+        };
+        let mut hud = hud(Anchor::TopLeft, vec![Widget::Fps, Widget::Score], &state);
+        let label = |hud: &Hud| match &hud.parts[0].shape {
+            Shape::Text(text, size) => (text.as_ptr(), *size),
+            Shape::Icons(..) | Shape::Rect => unreachable!(),
+        };
+        let first = label(&hud);
+
+        hud.update(&state);
+        assert_eq!(label(&hud), first); //<- The same parts.
+
+        // New values, a new layout, or a smaller viewport lay the widgets out again.
+        // --------------------------------------------------------------------------
+        state.score = 10;
+        hud.update(&state);
+        assert_eq!(rows(&hud)[1].0, "Score: 10");
+
+        hud.set_layout(HudLayout {
+            anchors: vec![(Anchor::TopLeft, vec![Widget::Score])],
+        });
+        hud.update(&state);
+        assert_eq!(rows(&hud).len(), 1);
+
+        hud.fit(&Viewport::fit(40, 30));
+        hud.update(&state);
+        assert_eq!(label(&hud).1, FONT * 2);
+    }
+
+    #[test]
+    fn placeholder_widgets() {
+        let widgets = vec![Widget::Multiplier, Widget::PowerUps, Widget::BossBar];
+        let mut state = HudState::default();
+        assert!(hud(Anchor::TopCenter, widgets.clone(), &state)
+            .parts
+            .is_empty());
+
+        let power_ups = [("Shield", 2500), ("Rapid", 900)];
+        state.multiplier = Some(3);
+        state.power_ups = &power_ups;
+        state.boss = Some(0.5);
+        let shown = hud(Anchor::TopCenter, widgets, &state);
+        assert_eq!(
+            rows(&shown).iter().map(|r| r.0).collect::<Vec<_>>(),
+            ["x3", "Shield 3s", "Rapid 1s", "rect", "rect"]
+        );
+
+        // The boss bar is centered as a whole, and split into the health and the lost health.
+        // -----------------------------------------------------------------------------------
+        let bar = &shown.parts[3..];
+        assert_eq!((bar[0].width, bar[1].width), (15.0, 15.0));
+        assert_eq!(bar[0].pos.x(), WIDTH as f64 / 2.0 - BOSS_BAR.0 / 2.0);
+        assert_eq!(bar[1].pos.x(), bar[0].pos.x() + 15.0);
+        assert_eq!(bar[0].pso.fill_color, Some(Role::Warning));
+    }
+
+    #[test]
+    fn power_ups_laid_out_by_second() {
+        let widgets = vec![Widget::PowerUps, Widget::BossBar];
+        let mut power_ups = [("Shield", 2500)];
+        let state = HudState {
+            power_ups: &power_ups,
+            ..Default::default()
+        };
+        let mut hud = hud(Anchor::BottomCenter, widgets, &state);
+
+        // Only a new second (or a boss) changes what is shown.
+        // ----------------------------------------------------
+        power_ups[0].1 = 2100;
+        let state = HudState {
+            power_ups: &power_ups,
+            ..Default::default()
+        };
+        assert!(hud.shown.as_ref().unwrap().equals(&state, 0));
+
+        power_ups[0].1 = 1900;
+        let state = HudState {
+            power_ups: &power_ups,
+            boss: Some(1.0),
+            ..Default::default()
+        };
+        assert!(!hud.shown.as_ref().unwrap().equals(&state, 0));
+        hud.update(&state);
+        assert_eq!(
+            rows(&hud).iter().map(|r| r.0).collect::<Vec<_>>(),
+            ["Shield 2s", "rect"] //<- A full bar has no lost health.
+        );
+    }
+}
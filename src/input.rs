@@ -35,6 +35,16 @@ impl Input {
         }
     }
 
+    /// Create new **Input** object with a fixed input state. Used to feed recorded input into the
+    /// simulation.
+    pub fn from_state(left: bool, right: bool, shoot: bool) -> Self {
+        let mut input = Self::new();
+        input.go_left = left;
+        input.go_right = right;
+        input.shoot = shoot;
+        input
+    }
+
     /// Signals if player should go left.
     pub fn left(&self) -> bool {
         self.gp_go_left | self.go_left
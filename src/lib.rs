@@ -14,119 +14,175 @@ extern crate wee_alloc;
 mod entites;
 #[macro_use]
 mod logging;
+mod achievements;
+mod animation;
+mod bench;
+mod daily;
+#[cfg(feature = "debug-overlay")]
+mod debug;
+mod effects;
+mod events;
+mod feedback;
+mod ghost;
+pub mod headless;
+mod hud;
 mod input;
+mod particles;
 mod rendering;
+mod replay;
+mod rules;
+mod session;
+mod sprites;
+mod starfield;
+pub mod stats;
+mod storage;
 mod utils;
+mod waves;
+mod world;
 
-use self::rendering::{CssColor, CssFont, Pos, Renderable};
-use self::utils::Timer;
+pub use self::entites::EnemyType;
+use self::events::Subscriber;
+pub use self::feedback::FeedbackSettings;
+use self::headless::GifSettings;
+pub use self::hud::{Anchor, HudLayout, Widget};
+pub use self::rendering::{
+    draw_line, draw_text, measure_text, Align, Backend, CssColor, CssFont, Damage, Framebuffer,
+    Palette, Pos, Role, Viewport, THEMES,
+};
+use self::rendering::{Scene, SceneNode};
+pub use self::replay::Replay;
+pub use self::starfield::{StarLayer, StarfieldSettings};
+use bumpalo::Bump;
 use wasm_bindgen::prelude::*;
 use web_sys::{HtmlCanvasElement, KeyboardEvent};
 
 // Global allocator. Will override the toolchain / system default.
-// Shrinks the code size of the compiled wasm module.
-// ---------------------------------------------------------------
+// Shrinks the code size of the compiled wasm module. Native builds (headless
+// rendering and tests) keep the system allocator.
+// ---------------------------------------------------------------------------
+#[cfg(target_arch = "wasm32")]
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 /// Main type that holds all game state and provides functionality to update and
 /// render the game.
+///
+/// The simulation itself happens in the **Session** (shared with the headless frontend). The game feeds
+/// it with input, and adds what only the browser has: achievements, the ghost, toasts and JS callbacks.
 #[wasm_bindgen]
 pub struct Game {
-    fps_counter: entites::FpsCounter,
     renderer: rendering::PixelScreen,
-    player: entites::Player,
     input: input::Input,
-    projectiles: Vec<entites::Projectile>,
-    enemies: Vec<entites::Enemy>,
-    game_tick: Timer,
-    enemy_tick: u8,
-    enemy_spawn_tick: u8,
-    explosions: Vec<entites::Explosion>,
-    explosion_tick: u8,
-    score: entites::Score,
+    session: session::Session,
+    start_ts: Option<u32>,
+    ghost: Option<ghost::Ghost>,
+    achievements: achievements::Achievements,
+    toast: entites::Toast,
+    js_events: events::JsCallback,
+    hud: hud::Hud,
+    daily: Option<u32>,
+    palette: &'static Palette,
+    damage: Option<rendering::Damage>,
+    arena: Bump,
 }
 
-/// Game tick. All 10 ms.
-/// ---------------------
-const TICK: u32 = 10;
+/// Local storage key of the name of the selected theme.
+const THEME_KEY: &str = "rinvaders.theme";
+
+/// Local storage key of the reduced motion switch ("true" or "false").
+const REDUCED_MOTION_KEY: &str = "rinvaders.reduced_motion";
+
+/// Local storage key of the starfield switch ("true" or "false").
+const STARFIELD_KEY: &str = "rinvaders.starfield";
+
+/// Local storage key of the snap to grid switch ("true" or "false").
+const SNAP_TO_GRID_KEY: &str = "rinvaders.snap_to_grid";
+
+/// How much of the end of the current run is captured by **Game::capture_gif()** (in ms).
+const GIF_DURATION: u32 = 10000;
 
 #[wasm_bindgen]
 impl Game {
     /// Create an instance of the **Game** type. Requires *canvas* parameter which should be the DOM
     /// node of the canvas, into which the game should be rendered.
     ///
-    /// Initializes the game.
+    /// Initializes the game with a random world and the classic rules, and loads the ghost of the
+    /// personal best run.
     #[wasm_bindgen(constructor)]
     pub fn new(canvas: HtmlCanvasElement) -> Self {
-        Self {
-            fps_counter: entites::FpsCounter::new(),
-            player: entites::Player::new(),
-            renderer: rendering::PixelScreen::new(canvas),
-            input: input::Input::new(),
-            projectiles: vec![],
-            enemies: vec![],
-            game_tick: Timer::interval(0, TICK),
-            enemy_tick: 0,
-            enemy_spawn_tick: 0,
-            explosions: vec![],
-            explosion_tick: 0,
-            score: entites::Score::new(
-                CssColor::new(0, 50, 200),
-                CssColor::new(200, 50, 0),
-                CssFont::monospace(20),
-                "Score".to_owned(),
-                Pos::new(600.0, 20.0),
-            ),
-        }
+        let seed = (js_sys::Math::random() * u32::MAX as f64) as u32;
+
+        let mut game = Self::with_rules(canvas, seed, rules::Rules::default());
+        game.ghost = ghost::Ghost::load();
+        game
     }
 
-    /// Forwards keyboard events to the **input** module.
+    /// Create an instance of the **Game** type for the daily challenge of today. The seed of the world,
+    /// and the rule modifiers are derived from the date.
+    pub fn daily(canvas: HtmlCanvasElement) -> Self {
+        let date = daily::today();
+
+        let mut game = Self::with_rules(canvas, daily::seed(date), daily::rules(date));
+        game.daily = Some(date);
+        game
+    }
+
+    /// Forwards keyboard events to the **input** module. With the `debug-overlay` feature, the backquote
+    /// key toggles the debug overlay.
     pub fn keyboard_event(&mut self, is_down: bool, e: KeyboardEvent) {
+        #[cfg(feature = "debug-overlay")]
+        {
+            if is_down && !e.repeat() && e.key() == "`" {
+                self.set_debug_overlay(!self.session.debug_overlay());
+                return;
+            }
+        }
         self.input.keyboard_event(is_down, e);
     }
 
-    /// Render the game. This will clear the canvas, and then redraw the whole scene.
-    /// Does not need mutable access, because it immutably reads game date, and draws
-    /// to the canvas. Canvas draw operations have some kind of interior mutability,
-    /// which allows usage through non mutable references.
-    pub fn render(&self) {
-        // Clear the screen.
-        // -----------------
-        self.renderer.clear();
-
-        // Draw all the projectile entites, and bind the PROJECTILE_PSO before doing so.
-        // -----------------------------------------------------------------------------
-        entites::Projectile::pso().bind(&self.renderer);
-        for p in self.projectiles.iter() {
-            p.draw(&self.renderer);
+    /// Render the game. This will clear the canvas, and then redraw the whole scene (or only the regions,
+    /// which changed, with dirty rects). It only reads the game data, but needs mutable access to the
+    /// rendering backend.
+    pub fn render(&mut self) {
+        let background = self.palette.color(Role::Background);
+        #[cfg(feature = "debug-overlay")]
+        {
+            let render = (self.renderer.bind_counters(), self.renderer.draw_counters());
+            self.session.update_debug(Some(render));
         }
 
-        // Bind enemy PSO, and then draw all enemies.
-        // ------------------------------------------
-        entites::Enemy::pso().bind(&self.renderer);
-        for e in self.enemies.iter() {
-            e.draw(&self.renderer);
-        }
+        // Lay out the HUD with the current values. The high score is the personal best of the ghost
+        // (or the live score, once it is beaten).
+        // ------------------------------------------------------------------------------------------
+        let state = self.session.hud_state();
+        self.hud.fit(self.renderer.viewport());
+        self.hud.update(&hud::HudState {
+            high_score: self.ghost.as_ref().map(|g| g.best().max(state.score)),
+            ghost: self.ghost.as_ref().map(|g| g.score()),
+            ..state
+        });
 
-        // Bind the explosion PSO, and then draw all explosions.
-        // ----------------------------------------------------
-        self::entites::Explosion::pso().bind(&self.renderer);
-        for x in self.explosions.iter() {
-            x.draw(&self.renderer);
+        // Every scene object submits its drawables with their PSOs and layers. The scene takes care of
+        // the drawing order and PSO binding. It reuses the memory of the last frame in the arena.
+        // --------------------------------------------------------------------------------------------
+        self.arena.reset();
+        let mut scene = Scene::new(&self.arena);
+        scene.add(&[
+            &self.session as &dyn SceneNode,
+            &self.hud as &dyn SceneNode,
+            &self.toast as &dyn SceneNode,
+        ]);
+        if let Some(g) = &self.ghost {
+            g.submit(&mut scene);
         }
-
-        // Set up a list with various other drawables, which all take care of PSO
-        // binding themselves (they are unique, so binding PSO for a batch is not
-        // an improvement).
-        // Then draw the list by iterating over it.
-        // ----------------------------------------------------------------------
-        let display_list: &[&dyn Renderable] = &[
-            &self.player as &dyn Renderable,
-            &self.fps_counter as &dyn Renderable,
-            &self.score as &dyn Renderable,
-        ];
-        self.renderer.draw(&display_list);
+        match &mut self.damage {
+            Some(d) => scene.draw_damaged(&mut self.renderer, self.palette, background, d),
+            None => {
+                self.renderer.clear(background);
+                scene.draw(&mut self.renderer, self.palette);
+            }
+        }
+        self.renderer.present();
     }
 
     /// Update the game.
@@ -134,132 +190,281 @@ impl Game {
     /// This processes all input, and calculates the next state of the game depending on
     /// previous state, current input, and the current timestamp.
     pub fn update(&mut self, ts: u32) {
-        // Update the FPS counter.
-        // -----------------------
-        self.fps_counter.update(ts);
+        // Update the FPS counter and the feedback effects (they also fade out after the game is over).
+        // --------------------------------------------------------------------------------------------
+        self.session.begin_frame(ts);
+
+        // Show a toast for freshly unlocked achievements (one after another).
+        // --------------------------------------------------------------------
+        self.toast.update(ts);
+        if self.toast.is_idle() {
+            if let Some(a) = self.achievements.next_unlock() {
+                self.toast.show(ts, &format!("Achievement:\n{}", a.name));
+            }
+        }
+
+        // When the game is over, nothing moves anymore.
+        // ---------------------------------------------
+        if self.session.game_over().is_some() {
+            return;
+        }
+
+        // Announce the rule modifiers on the first frame of a daily challenge.
+        // --------------------------------------------------------------------
+        if let (None, Some(date)) = (self.start_ts, self.daily) {
+            let names: Vec<&str> = daily::modifiers(date).iter().map(|m| m.name()).collect();
+            self.toast
+                .show(ts, &format!("Daily challenge: {}", names.join(", ")));
+        }
 
         // Process input.
         // --------------
         self.input.update(ts);
 
-        // Update the player depending on input. This needs
-        // a mutable reference to projectiles, so it can spawn new ones on shoot.
-        // ----------------------------------------------------------------------
-        self.player.update(ts, &self.input, &mut self.projectiles);
-
-        // Get all the mutable references for fields from **Game** into lifetimes scope.
-        // This has to be done to prevent confusing the closures lifetime below with the lifetime
-        // of the fields. I think it is related to the fact, that the closure does not get any gurantee
-        // from `game_tick.check()` regarding outliving any of the mutable resources in **Game**.
-        //
-        // TODO: Maybe it is possible to annotate this update function, the Game type, or the closure
-        // with lifetimes.
-        let game_tick = &mut self.game_tick;
-        let projectiles = &mut self.projectiles;
-        let enemies = &mut self.enemies;
-        let enemy_tick = &mut self.enemy_tick;
-        let enemy_spawn_tick = &mut self.enemy_spawn_tick;
-        let explosions = &mut self.explosions;
-        let explosion_tick = &mut self.explosion_tick;
-        let score = &mut self.score;
-
-        // This will check if the gametick is fired. When yes, it executes the given closure.
-        // The *off* parameter for the closure tells, how many ms the timer may have missed.
-        // ----------------------------------------------------------------------------------
-        game_tick.check(ts, |off| {
-            // Calculate how many ticks we missed (should be 1 for no misses almost always),
-            // and perform all of them right here in the loop body.
-            // -----------------------------------------------------------------------------
-            for _ in 0..(off + TICK) / TICK {
-                // Check if projectile would move into the void. The *retain()* iterator is used,
-                // to signal which projectiles survive, or die in this game tick.
-                // ------------------------------------------------------------------------------
-                projectiles.retain(|e| {
-                    if e.needs_removal() {
-                        score.add(-1); //<- We lose one point when the projectile missed all enemies.
-                        false //<- Also please die.
-                    } else {
-                        true //<- Allowed to live another day.
-                    }
-                });
+        // During a hit-stop, the world is not updated at all.
+        // ----------------------------------------------------
+        if self.session.frozen() {
+            return;
+        }
 
-                // Now actually update the projectile.
-                // -----------------------------------
-                for p in projectiles.iter_mut() {
-                    p.tick();
-                }
+        // The world runs on timestamps relative to the first update (without the time it was stopped),
+        // so replays of it do not depend on the time the page was loaded. The session records the frame,
+        // updates the world with it, and hands the events to its subscribers and the ones of the game.
+        // ----------------------------------------------------------------------------------------------
+        let world_ts = ts - *self.start_ts.get_or_insert(ts) - self.session.feedback().stopped();
+        let ended = self.session.simulate(
+            world_ts,
+            &self.input,
+            &mut [
+                &mut self.achievements as &mut dyn Subscriber,
+                &mut self.js_events as &mut dyn Subscriber,
+            ],
+        );
 
-                // When the enemy tick is here, update them all.
-                // ---------------------------------------------
-                if *enemy_tick == 10 {
-                    for e in enemies.iter_mut() {
-                        e.tick();
-                    }
-                    enemies.retain(|e| {
-                        if e.needs_removal() {
-                            //<- Check if they have to die because of the void.
-                            score.add(-10); //<- We loose points if they get past our players defenses.
-                            false
-                        } else {
-                            true
-                        }
-                    });
-                    *enemy_tick = 0;
-                } else {
-                    *enemy_tick += 1;
-                }
+        // Step the ghost in lockstep with the live world.
+        // -----------------------------------------------
+        if let Some(g) = &mut self.ghost {
+            g.update(world_ts);
+        }
 
-                // Update all those beautiful explosions when the tick says so.
-                // ------------------------------------------------------------
-                if *explosion_tick == 5 {
-                    for x in explosions.iter_mut() {
-                        x.tick();
+        // When the game is over, a new personal best run is stored for the next ghost. Daily challenges
+        // show their share code instead (in two lines, after the date, so it fits into the grid).
+        // ---------------------------------------------------------------------------------------------
+        if ended {
+            match self.share_code() {
+                Some(code) => {
+                    if let Some(g) = self.session.game_over_mut() {
+                        let split = code.match_indices(':').nth(1).map_or(0, |(i, _)| i + 1);
+                        let (date, result) = code.split_at(split);
+                        g.add_line(date.to_owned());
+                        g.add_line(result.to_owned());
                     }
-                    explosions.retain(|x| !x.needs_removal()); //<- Eliminate the ones with enough progress.
-                    *explosion_tick = 0;
-                } else {
-                    *explosion_tick += 1;
                 }
+                None => ghost::save_if_best(self.session.recording(), self.session.score().value()),
+            }
+        }
+    }
 
-                // Every enemy spawn tick, spawn new enemies (currently only one per spawn tick).
-                // They are spawned along the horizontal axis on the top of the canvas.
-                // ------------------------------------------------------------------------------
-                if *enemy_spawn_tick == 200 {
-                    enemies.push(entites::Enemy::new_random());
-                    *enemy_spawn_tick = 0;
-                } else {
-                    *enemy_spawn_tick += 1;
-                }
+    /// Get the share code of a finished daily challenge run. Returns `undefined` for classic games, and
+    /// while the run is not over yet.
+    pub fn share_code(&self) -> Option<String> {
+        if self.session.lives().count() > 0 {
+            return None;
+        }
 
-                // Collison check and removal for every projectile by retaining iterator.
-                // ----------------------------------------------------------------------
-                projectiles.retain(|p| {
-                    if let Some(e) = enemies
-                        .iter()
-                        .enumerate()
-                        // Find collisiony between current projectile and all enemies.
-                        // -----------------------------------------------------------
-                        .find(|(_, e)| e.intersects_with(p.pos_x, p.pos_y))
-                        // Get only the index of the enemy.
-                        // --------------------------------
-                        .map(|(i, _)| i)
-                    {
-                        // Collision found. Remove enemy by index, create explosion on this position, and
-                        // then mark the projectile for removal. Also add some points to the score.
-                        // ------------------------------------------------------------------------------
-                        enemies.remove(e);
-                        explosions.push(entites::Explosion::new(
-                            utils::cap(p.pos_x as i32 - 1, 0, 79) as u32,
-                            utils::cap(p.pos_y as i32 + 1, 0, 59) as u32,
-                        ));
-                        score.add(5);
-                        false
-                    } else {
-                        true
-                    }
-                });
+        let s = &self.session;
+        self.daily.map(|date| {
+            daily::DailyResult::new(date, s.score(), s.stats(), s.recording()).share_code()
+        })
+    }
+
+    /// Get the replay of the current run as encoded string. Together with the share code it can be
+    /// checked with **verify_daily()**.
+    pub fn replay(&self) -> String {
+        self.session.recording().encode()
+    }
+
+    /// Capture the last 10 seconds of the current run into an animated GIF with *scale* pixels per
+    /// virtual pixel, skipping *frame_skip* frames after every captured one. The run is played back
+    /// headless from its replay (with the same theme, reduced motion, starfield and snap to grid switch),
+    /// so the game itself is not disturbed. Returns the GIF file (as `Uint8Array`).
+    pub fn capture_gif(&self, scale: u32, frame_skip: u32) -> Vec<u8> {
+        let replay = self.session.recording().clone();
+        let start = replay.frame_at(replay.duration().saturating_sub(GIF_DURATION)) as u32;
+        let end = replay.len() as u32 + 1; //<- Including the state after the last frame.
+
+        let mut headless = match self.daily {
+            Some(date) => headless::Headless::daily(replay, date),
+            None => headless::Headless::new(replay),
+        };
+        headless.set_palette(self.palette);
+        headless.set_feedback_settings(self.session.feedback().settings());
+        headless.set_starfield_settings(self.session.starfield().settings().clone());
+        headless.set_snap_to_grid(self.session.snap_to_grid());
+        headless.capture_gif(start..end, &GifSettings { scale, frame_skip })
+    }
+
+    /// Get the statistics of the current session as JS object. Contains the fields `shots_fired`,
+    /// `hits`, `accuracy`, `kills` (object with count per enemy type), `escaped`, `longest_combo`,
+    /// `time_survived_ms`, `fps_avg` and `fps_min`.
+    pub fn stats(&self) -> JsValue {
+        js_sys::JSON::parse(&self.session.stats().to_json()).unwrap()
+    }
+
+    /// Get the statistics of the current session as JSON string (same content as **stats()**).
+    pub fn stats_json(&self) -> String {
+        self.session.stats().to_json()
+    }
+
+    /// Register a JS *callback*, which receives every gameplay event as an object with a `type` field
+    /// (`ShotFired`, `EnemyKilled`, `ProjectileMissed`, `EnemyEscaped`, `PlayerHit` or `WaveCleared`) and
+    /// additional fields depending on the type. Pass `null` to unregister it again.
+    pub fn set_event_callback(&mut self, callback: Option<js_sys::Function>) {
+        self.js_events.set(callback);
+    }
+
+    /// Get the list of all achievements as JS array. Every entry is an object with the fields
+    /// `id`, `name`, `description` and `unlocked`.
+    pub fn achievements(&self) -> js_sys::Array {
+        self.achievements.to_js()
+    }
+
+    /// Number of PSO state bindings (fill color, font or alpha), which changed the drawing context in the
+    /// last rendered frame.
+    pub fn binds_issued(&self) -> u32 {
+        self.renderer.bind_counters().issued
+    }
+
+    /// Number of redundant PSO state bindings, which were skipped in the last rendered frame.
+    pub fn binds_skipped(&self) -> u32 {
+        self.renderer.bind_counters().skipped
+    }
+
+    /// Number of draw calls issued to the canvas in the last rendered frame.
+    pub fn draw_calls(&self) -> u32 {
+        self.renderer.draw_counters().calls
+    }
+
+    /// Number of rects (virtual pixels and font pixels) drawn in the last rendered frame.
+    pub fn rects_drawn(&self) -> u32 {
+        self.renderer.draw_counters().rects
+    }
+
+    /// Get the name of the render mode (`fill_rect`, `path` or `image_data`).
+    pub fn render_mode(&self) -> String {
+        self.renderer.mode().name().to_owned()
+    }
+
+    /// Switch to the render mode called *name*: `fill_rect` fills every virtual pixel on the canvas,
+    /// `path` fills them in batches (the default), and `image_data` draws them into an image first.
+    /// Returns false, when there is no such mode.
+    pub fn set_render_mode(&mut self, name: &str) -> bool {
+        match rendering::RenderMode::find(name) {
+            Some(m) => {
+                self.renderer.set_mode(m);
+                true
             }
-        });
+            None => false,
+        }
+    }
+
+    /// Checks, if only the regions of the canvas, which changed since the last frame, are rendered (dirty
+    /// rects).
+    pub fn dirty_rects(&self) -> bool {
+        self.damage.is_some()
+    }
+
+    /// Enable or disable dirty rects. They are disabled by default.
+    pub fn set_dirty_rects(&mut self, enabled: bool) {
+        self.damage = if enabled {
+            Some(rendering::Damage::new())
+        } else {
+            None
+        };
+    }
+
+    /// Get the name of the active theme.
+    pub fn theme(&self) -> String {
+        self.palette.name.to_owned()
+    }
+
+    /// Switch to the built-in theme called *name* (see **themes()**), and remember it for the next
+    /// sessions. Returns false, when there is no such theme.
+    pub fn set_theme(&mut self, name: &str) -> bool {
+        match Palette::find(name) {
+            Some(p) => {
+                self.palette = p;
+                storage::store(THEME_KEY, name);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks, if reduced motion is switched on (no screen shake, hit-stop and flashes).
+    pub fn reduced_motion(&self) -> bool {
+        self.session.feedback().settings().reduced_motion
+    }
+
+    /// Switch reduced motion *on* or off, and remember it for the next sessions. Until it is set, it
+    /// follows the `prefers-reduced-motion` setting of the browser.
+    pub fn set_reduced_motion(&mut self, on: bool) {
+        let mut settings = self.session.feedback().settings();
+        settings.reduced_motion = on;
+        self.session.feedback_mut().set_settings(settings);
+        storage::store(REDUCED_MOTION_KEY, if on { "true" } else { "false" });
+    }
+
+    /// Set the *intensity* (0 is off, 1 is full strength) of the feedback *effect* (`shake`, `hit_stop`
+    /// or `flash`). Returns false, when there is no such effect.
+    pub fn set_feedback_intensity(&mut self, effect: &str, intensity: f32) -> bool {
+        let mut settings = self.session.feedback().settings();
+        match effect {
+            "shake" => settings.shake = intensity,
+            "hit_stop" => settings.hit_stop = intensity,
+            "flash" => settings.flash = intensity,
+            _ => return false,
+        }
+        self.session.feedback_mut().set_settings(settings);
+        true
+    }
+
+    /// Checks, if the debug overlay is shown (only with the `debug-overlay` feature).
+    #[cfg(feature = "debug-overlay")]
+    pub fn debug_overlay(&self) -> bool {
+        self.session.debug_overlay()
+    }
+
+    /// Show or hide the debug overlay (only with the `debug-overlay` feature).
+    #[cfg(feature = "debug-overlay")]
+    pub fn set_debug_overlay(&mut self, on: bool) {
+        self.session.set_debug_overlay(on);
+    }
+
+    /// Checks, if the starfield is drawn in the background.
+    pub fn starfield(&self) -> bool {
+        self.session.starfield().settings().enabled
+    }
+
+    /// Switch the starfield *on* or off, and remember it for the next sessions.
+    pub fn set_starfield(&mut self, on: bool) {
+        let mut settings = self.session.starfield().settings().clone();
+        settings.enabled = on;
+        self.session.starfield_mut().set_settings(settings);
+        storage::store(STARFIELD_KEY, if on { "true" } else { "false" });
+    }
+
+    /// Checks, if moving entities are drawn on the grid (whole virtual pixels) instead of smoothly between
+    /// the game ticks.
+    pub fn snap_to_grid(&self) -> bool {
+        self.session.snap_to_grid()
+    }
+
+    /// Switch snapping to the grid *on* or off (for a pure retro look), and remember it for the next
+    /// sessions.
+    pub fn set_snap_to_grid(&mut self, on: bool) {
+        self.session.set_snap_to_grid(on);
+        storage::store(SNAP_TO_GRID_KEY, if on { "true" } else { "false" });
     }
 
     /// Set the current gamepad state. Axis movement and shoot.
@@ -270,6 +475,90 @@ impl Game {
     }
 }
 
+/// Native (not exported to JS) API of the **Game**.
+impl Game {
+    /// Create an instance of the **Game** type, which simulates a world with *seed* according to
+    /// *rules*.
+    fn with_rules(canvas: HtmlCanvasElement, seed: u32, rules: rules::Rules) -> Self {
+        let mut session = session::Session::new(
+            seed,
+            rules,
+            FeedbackSettings {
+                reduced_motion: storage::load(REDUCED_MOTION_KEY)
+                    .map(|v| v == "true")
+                    .unwrap_or_else(prefers_reduced_motion),
+                ..Default::default()
+            },
+            StarfieldSettings {
+                enabled: storage::load(STARFIELD_KEY)
+                    .map(|v| v == "true")
+                    .unwrap_or(true),
+                ..Default::default()
+            },
+        );
+        session.set_snap_to_grid(
+            storage::load(SNAP_TO_GRID_KEY)
+                .map(|v| v == "true")
+                .unwrap_or(false),
+        );
+
+        Self {
+            renderer: rendering::PixelScreen::new(canvas),
+            input: input::Input::new(),
+            session,
+            start_ts: None,
+            ghost: None,
+            achievements: achievements::Achievements::load(),
+            toast: entites::Toast::new(),
+            js_events: events::JsCallback::new(),
+            hud: hud::Hud::new(HudLayout::default()),
+            daily: None,
+            palette: storage::load(THEME_KEY)
+                .and_then(|name| Palette::find(&name))
+                .unwrap_or_else(Palette::default_theme),
+            damage: None,
+            arena: Bump::new(),
+        }
+    }
+
+    /// The statistics of the current session.
+    pub fn session_stats(&self) -> &stats::SessionStats {
+        self.session.stats()
+    }
+}
+
+/// Checks, if the browser asks for reduced motion (the `prefers-reduced-motion` media query).
+fn prefers_reduced_motion() -> bool {
+    web_sys::window()
+        .and_then(|w| w.match_media("(prefers-reduced-motion: reduce)").ok()?)
+        .is_some_and(|m| m.matches())
+}
+
+/// Verify a daily challenge share *code* with the encoded *replay* of the run. Simulates the replay
+/// again, and returns true, when it results in the score and accuracy claimed by the code.
+#[wasm_bindgen]
+pub fn verify_daily(code: &str, replay: &str) -> bool {
+    daily::verify(code, replay).is_ok()
+}
+
+/// Benchmark the render modes. Draws *entities* enemies into *canvas* for *frames* frames with every mode,
+/// and returns a JS object with the average time per frame in ms by mode name (e.g. `fill_rect`, and
+/// `path+dirty_rects` for the path mode with dirty rects).
+#[wasm_bindgen]
+pub fn benchmark(canvas: HtmlCanvasElement, entities: u32, frames: u32) -> JsValue {
+    let o = js_sys::Object::new();
+    for (name, ms) in bench::run(canvas, entities, frames) {
+        let _ = js_sys::Reflect::set(&o, &name.into(), &ms.into());
+    }
+    o.into()
+}
+
+/// Get the names of all built-in themes as JS array (the first one is the default).
+#[wasm_bindgen]
+pub fn themes() -> js_sys::Array {
+    THEMES.iter().map(|p| JsValue::from(p.name)).collect()
+}
+
 /// Test exported rust function (to wasm module).
 #[wasm_bindgen]
 pub fn hello(name: &str) {
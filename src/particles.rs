@@ -0,0 +1,343 @@
+//! Particles module.
+//!
+//! A simple particle system for debris. Particles have sub-pixel positions, velocities, and a lifetime,
+//! are pulled down by gravity, and fade out while they get older. They are emitted in bursts, which are
+//! configured by a **Burst** (e.g. one for destroyed enemies, and a larger one for player hits).
+//!
+//! The fade out is an opacity ramp in a few steps. All particles are submitted as one drawable with
+//! a single PSO (the explosion color), and the opacity is set inside the batch. The particles of
+//! every step are kept in a group of their own, so the opacity only changes once per step.
+//! Particles move to the next group, while they get older.
+//!
+//! All groups have a fixed capacity (the maximum number of particles alive), so emitting and updating
+//! particles never allocates. When the system is full, further particles of a burst are dropped.
+
+use crate::rendering::{Backend, Footprint, Layer, Pos, Pso, Renderable, Role, Scene, SceneNode};
+use crate::utils::Rng;
+
+lazy_static! {
+    /// PSO of all particles.
+    static ref PSO: Pso = Pso {
+        fill_color: Some(Role::Explosion),
+        ..Default::default()
+    };
+}
+
+/// Maximum number of particles alive at the same time.
+const CAPACITY: usize = 256;
+
+/// Number of opacity steps of the fade out. Particles in the same step are drawn together.
+const FADE_STEPS: u32 = 4;
+
+/// Size of a particle in virtual pixels.
+const SIZE: f64 = 0.5;
+
+/// Configuration of a burst of particles. Speeds are in virtual pixels per second, the gravity in
+/// virtual pixels per second², and lifetimes in ms. Every particle gets a random direction, and a random
+/// speed and lifetime within the given ranges.
+pub struct Burst {
+    pub count: u32,
+    pub speed: (f32, f32),
+    pub lifetime: (u32, u32),
+    pub gravity: f32,
+}
+
+/// Burst of a destroyed enemy.
+pub const KILL_BURST: Burst = Burst {
+    count: 12,
+    speed: (10.0, 25.0),
+    lifetime: (300, 600),
+    gravity: 40.0,
+};
+
+/// Burst of an enemy crashing into the player ship.
+pub const HIT_BURST: Burst = Burst {
+    count: 32,
+    speed: (15.0, 40.0),
+    lifetime: (400, 900),
+    gravity: 60.0,
+};
+
+/// A single particle. Position and velocity, gravity, its age and lifetime in ms.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+struct Particle {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    gravity: f32,
+    age: u32,
+    lifetime: u32,
+}
+
+impl Particle {
+    /// The fade step (1 to **FADE_STEPS**) of the particle depending on its remaining lifetime.
+    fn fade_step(&self) -> u32 {
+        let remaining = self.lifetime - self.age;
+        (remaining * FADE_STEPS).div_ceil(self.lifetime)
+    }
+}
+
+/// The opacity of the fade *step* (1 to **FADE_STEPS**, the last one is opaque).
+fn alpha(step: u32) -> u8 {
+    (255 * step / FADE_STEPS) as u8
+}
+
+/// The particles of a fade step.
+struct Group(Vec<Particle>);
+
+/// The particle system. Contains a group per fade step (the first one has the oldest particles), and
+/// the random number generator for the bursts (visual only, not part of the simulation).
+pub struct Particles {
+    groups: Vec<Group>,
+    rng: Rng,
+}
+
+impl Particles {
+    /// Create new particle system without particles.
+    pub fn new() -> Self {
+        Self {
+            groups: (0..FADE_STEPS)
+                .map(|_| Group(Vec::with_capacity(CAPACITY)))
+                .collect(),
+            rng: Rng::new(0x5eed_d00d),
+        }
+    }
+
+    /// All particles alive.
+    fn particles(&self) -> impl Iterator<Item = &Particle> {
+        self.groups.iter().flat_map(|g| g.0.iter())
+    }
+
+    /// Number of particles alive.
+    fn count(&self) -> usize {
+        self.groups.iter().map(|g| g.0.len()).sum()
+    }
+
+    /// Random number from *min* to *max*.
+    fn random(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * (self.rng.next_u32() as f32 / u32::MAX as f32)
+    }
+
+    /// Emit *burst* at the virtual position (*x*, *y*).
+    pub fn emit(&mut self, burst: &Burst, x: f32, y: f32) {
+        let free = CAPACITY - self.count(); //<- When the system is full, the rest is dropped.
+        for _ in 0..(burst.count as usize).min(free) {
+            let angle = self.random(0.0, std::f32::consts::PI * 2.0);
+            let speed = self.random(burst.speed.0, burst.speed.1);
+            let lifetime = self.random(burst.lifetime.0 as f32, burst.lifetime.1 as f32) as u32;
+
+            self.push(Particle {
+                x,
+                y,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                gravity: burst.gravity,
+                age: 0,
+                lifetime: lifetime.max(1),
+            });
+        }
+    }
+
+    /// Add *particle* to the group of its fade step.
+    fn push(&mut self, particle: Particle) {
+        self.groups[particle.fade_step() as usize - 1]
+            .0
+            .push(particle);
+    }
+
+    /// Advance all particles by *dt* ms. Particles at the end of their lifetime are removed, and the ones,
+    /// which reached the next fade step, move to its group (the last particle of the group takes their
+    /// place in both cases).
+    pub fn tick(&mut self, dt: u32) {
+        let secs = dt as f32 / 1000.0;
+
+        // Particles only move to groups of older particles, which were updated already (the oldest
+        // group comes first).
+        // ----------------------------------------------------------------------------------------
+        for step in 0..self.groups.len() {
+            let mut i = 0;
+            while i < self.groups[step].0.len() {
+                let p = &mut self.groups[step].0[i];
+                p.age += dt;
+                if p.age >= p.lifetime {
+                    self.groups[step].0.swap_remove(i);
+                    continue;
+                }
+
+                p.vy += p.gravity * secs;
+                p.x += p.vx * secs;
+                p.y += p.vy * secs;
+                if p.fade_step() as usize - 1 != step {
+                    let p = self.groups[step].0.swap_remove(i);
+                    self.push(p);
+                    continue;
+                }
+                i += 1;
+            }
+        }
+    }
+}
+
+impl Renderable for Particles {
+    /// Draw all particles with the bound PSO, every group with the opacity of its fade step.
+    fn draw(&self, backend: &mut dyn Backend) {
+        backend.save();
+        for (step, group) in (1..).zip(self.groups.iter()) {
+            if group.0.is_empty() {
+                continue;
+            }
+
+            backend.set_alpha(alpha(step));
+            for p in group.0.iter() {
+                backend.draw_rect(
+                    Pos::new(p.x as f64 - SIZE / 2.0, p.y as f64 - SIZE / 2.0),
+                    SIZE,
+                    SIZE,
+                );
+            }
+        }
+        backend.restore();
+    }
+
+    /// The bounding box of the particles. Particles move every update, so the key is their total age.
+    fn footprint(&self) -> Option<Footprint> {
+        let (mut x0, mut y0, mut x1, mut y1) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for p in self.particles() {
+            x0 = x0.min(p.x as f64);
+            y0 = y0.min(p.y as f64);
+            x1 = x1.max(p.x as f64);
+            y1 = y1.max(p.y as f64);
+        }
+
+        let key = self.particles().map(|p| p.age as u64).sum::<u64>() + self.count() as u64;
+        Some(Footprint::covering(
+            Pos::new(x0 - SIZE / 2.0, y0 - SIZE / 2.0),
+            (x1 - x0).max(0.0) + SIZE,
+            (y1 - y0).max(0.0) + SIZE,
+            key,
+        ))
+    }
+}
+
+impl SceneNode for Particles {
+    /// Submit all particles with the single PSO (unless there are none).
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        if self.count() > 0 {
+            scene.submit(Layer::Effects, &PSO, self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Call, Palette, RecordingBackend};
+    use bumpalo::Bump;
+
+    /// A particle at (*x*, 0) without velocity, with *lifetime* ms.
+    fn particle(x: f32, lifetime: u32) -> Particle {
+        Particle {
+            x,
+            lifetime,
+            ..Default::default()
+        }
+    }
+
+    /// The x positions of the particles in every group.
+    fn groups(particles: &Particles) -> Vec<Vec<f32>> {
+        particles
+            .groups
+            .iter()
+            .map(|g| g.0.iter().map(|p| p.x).collect())
+            .collect()
+    }
+
+    #[test]
+    fn capacity() {
+        let mut particles = Particles::new();
+        particles.emit(&HIT_BURST, 40.0, 30.0);
+        for _ in 0..CAPACITY {
+            particles.emit(&KILL_BURST, 40.0, 30.0);
+        }
+        assert_eq!(particles.count(), CAPACITY);
+
+        // The groups never grow (emitting and updating does not allocate).
+        // ----------------------------------------------------------------
+        particles.tick(100);
+        particles.emit(&KILL_BURST, 40.0, 30.0);
+        assert!(particles.groups.iter().all(|g| g.0.capacity() == CAPACITY));
+    }
+
+    #[test]
+    fn swap_remove() {
+        let mut particles = Particles::new();
+        for (x, lifetime) in [(0.0, 1000), (1.0, 10), (2.0, 1000), (3.0, 1000)] {
+            particles.push(particle(x, lifetime));
+        }
+        particles.tick(10);
+        assert_eq!(groups(&particles)[3], [0.0, 3.0, 2.0]); //<- The last particle took the place.
+    }
+
+    #[test]
+    fn fade_steps() {
+        let mut particles = Particles::new();
+        particles.push(particle(0.0, 100));
+        particles.push(particle(1.0, 1000));
+        assert_eq!(groups(&particles), [vec![], vec![], vec![], vec![0.0, 1.0]]);
+
+        particles.tick(30);
+        assert_eq!(groups(&particles), [vec![], vec![], vec![0.0], vec![1.0]]);
+
+        particles.tick(50);
+        assert_eq!(groups(&particles), [vec![0.0], vec![], vec![], vec![1.0]]);
+
+        particles.tick(20);
+        assert_eq!(groups(&particles), [vec![], vec![], vec![], vec![1.0]]);
+    }
+
+    #[test]
+    fn draw_opacity_ramp() {
+        let mut particles = Particles::new();
+        for (i, lifetime) in [100, 60, 40, 25, 24].iter().enumerate() {
+            particles.push(particle(i as f32, *lifetime));
+        }
+        particles.tick(20); //<- One particle in every fade step, and two in the first one.
+
+        let arena = Bump::new();
+        let mut scene = Scene::new(&arena);
+        particles.submit(&mut scene);
+        let mut backend = RecordingBackend::new();
+        let palette = Palette::default_theme();
+        scene.draw(&mut backend, palette);
+
+        // A single PSO binding with the explosion color, and the opacity of every step set inside
+        // the batch (once per step, from the oldest particles to the new ones), which is restored
+        // after it.
+        // ---------------------------------------------------------------------------------------
+        let calls = backend.take();
+        let colors: Vec<_> = calls
+            .iter()
+            .filter_map(|c| match c {
+                Call::FillColor(rgb) => Some(*rgb),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(colors, [palette.color(Role::Explosion).rgb()]);
+        let save = calls.iter().position(|c| matches!(c, Call::Save)).unwrap();
+        let steps: Vec<_> = calls[save..]
+            .iter()
+            .filter_map(|c| match c {
+                Call::Alpha(a) => Some(*a),
+                Call::Rect(..) => Some(0),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(steps, [63, 0, 0, 127, 0, 191, 0, 255, 0]);
+        let last_rect = calls
+            .iter()
+            .rposition(|c| matches!(c, Call::Rect(..)))
+            .unwrap();
+        assert!(matches!(calls[last_rect + 1], Call::Restore));
+    }
+}
@@ -0,0 +1,491 @@
+//! Canvas2D backend module. The default rendering backend, which draws into a canvas element of the
+//! DOM through its **CanvasRenderingContext2d**.
+//!
+//! Setting properties of the drawing context is expensive (colors have to be converted into CSS
+//! strings, and cross the wasm boundary). So the backend keeps track of the bound state, and skips
+//! bindings, which would not change anything. Converted colors are interned in a cache, so once all of
+//! them were seen, drawing a frame does not allocate anymore.
+//!
+//! Text is not drawn with the text API of the context (its fonts differ from OS to OS), but with the
+//! built-in bitmap font. So the font is only tracked for its size, and never set on the context.
+//!
+//! The size of the canvas follows its size on the page and the device pixel ratio, so it is drawn 1:1 to
+//! physical pixels (sharp on HiDPI screens). The virtual grid is letterboxed into it by the viewport.
+//!
+//! Filling every rect with `fillRect()` crosses the wasm boundary for every virtual pixel. With many
+//! entities, these are hundreds of calls per frame. So there are other **RenderMode**s:
+//!
+//! * Path (the default): all rects drawn with the same bound state are collected into one **Path2d**,
+//!   which is filled with a single call, when the state changes, and at the end of the frame. Overlapping
+//!   rects of a batch are filled only once (only visible with translucent drawing).
+//! * Image data: the rects are rasterized into a **Framebuffer** with **BLIT_SCALE** pixels per virtual
+//!   pixel. It is uploaded with `putImageData()`, and scaled onto the canvas without smoothing, when text
+//!   is drawn on top, and at the end of the frame. Sub-pixel positions (particles, screen shake and the
+//!   interpolation between ticks) are rounded to the pixels of the framebuffer, so this mode is lossy: they
+//!   move in steps of 1 / **BLIT_SCALE** virtual pixels instead of canvas pixels. Text is always filled
+//!   directly, because the pixels of the bitmap font are smaller than the ones of the framebuffer.
+//!
+//! Both need **present()** to be called at the end of the frame. The draw calls issued to the context
+//! are counted in **DrawCounters**.
+//!
+//! Dirty rects are drawn with a clip path of the damaged regions, which stays on the context until the
+//! next clear. The image data mode always draws the whole frame.
+
+use super::font;
+use super::viewport::{HEIGHT, WIDTH};
+use super::{Backend, BindCounters, CssColor, CssFont, DrawCounters, Framebuffer, Pos, Viewport};
+use std::collections::HashMap;
+use wasm_bindgen::{Clamped, JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, Path2d};
+
+/// Pixels of the image data framebuffer per virtual pixel. More pixels keep sub-pixel positions finer,
+/// but every one of them has to be rasterized and uploaded.
+const BLIT_SCALE: u32 = 4;
+
+/// The ways of the **PixelScreen** to get rects onto the canvas.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Every rect is filled on the canvas with its own `fillRect()` call.
+    FillRect,
+    /// Rects are collected into a path, which is filled once per bound state.
+    Path,
+    /// Rects are rasterized into a framebuffer of the virtual grid, which is drawn onto the canvas as an
+    /// image.
+    ImageData,
+}
+
+impl RenderMode {
+    /// All variants as a static slice.
+    pub const VARIANTS: &'static [Self] = &[
+        RenderMode::FillRect,
+        RenderMode::Path,
+        RenderMode::ImageData,
+    ];
+
+    /// Name of the variant.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RenderMode::FillRect => "fill_rect",
+            RenderMode::Path => "path",
+            RenderMode::ImageData => "image_data",
+        }
+    }
+
+    /// Get the variant called *name*.
+    pub fn find(name: &str) -> Option<Self> {
+        Self::VARIANTS.iter().copied().find(|m| m.name() == name)
+    }
+}
+
+/// State of the image data mode. The framebuffer of the virtual grid, the canvas (not part of the page),
+/// which it is uploaded to, and if something was drawn into it since the last upload.
+struct Blit {
+    buffer: Framebuffer,
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+    dirty: bool,
+}
+
+impl Blit {
+    /// Create new image data state with an erased framebuffer.
+    fn new() -> Self {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas = document
+            .create_element("canvas")
+            .unwrap()
+            .dyn_into::<HtmlCanvasElement>()
+            .unwrap();
+        canvas.set_width(WIDTH * BLIT_SCALE);
+        canvas.set_height(HEIGHT * BLIT_SCALE);
+        let ctx = canvas.get_context("2d").unwrap();
+        let ctx = ctx.unwrap().dyn_into::<CanvasRenderingContext2d>().unwrap();
+
+        let mut buffer = Framebuffer::new(WIDTH * BLIT_SCALE, HEIGHT * BLIT_SCALE);
+        buffer.erase();
+        Self {
+            buffer,
+            canvas,
+            ctx,
+            dirty: false,
+        }
+    }
+}
+
+/// The state of the drawing context set by PSOs. `None` when it is not known (nothing bound yet).
+#[derive(Copy, Clone, Default)]
+struct BoundState {
+    fill_color: Option<CssColor>,
+    font: Option<CssFont>,
+    alpha: Option<u8>,
+}
+
+/// An abstraction over the **CanvasRenderingContext2d**. Thus is essentially all the state, which the game needs for
+/// drawing the scene (except the input from the **update()** function from the **Game** object).
+///
+/// Also contains the canvas itself with its size in pixels and the viewport into it, the bound state
+/// (and a stack of it mirroring **save()** / **restore()** of the context), the cache of converted
+/// colors, the bind and draw counters of the current and the last frame, the render mode, the path of
+/// the current batch (path mode), and the state of the image data mode. While *direct* is set, rects
+/// are not drawn into the image. *clip* is the bound state from before the clip of the dirty rects was
+/// set (while it is set).
+pub struct PixelScreen {
+    canvas: HtmlCanvasElement,
+    canvas_ctx: CanvasRenderingContext2d,
+    width: u32,
+    height: u32,
+    viewport: Viewport,
+    bound: BoundState,
+    bound_stack: Vec<BoundState>,
+    colors: HashMap<CssColor, JsValue>,
+    counters: BindCounters,
+    last_counters: BindCounters,
+    draws: DrawCounters,
+    last_draws: DrawCounters,
+    mode: RenderMode,
+    batch: Option<Path2d>,
+    blit: Option<Blit>,
+    direct: bool,
+    clip: Option<BoundState>,
+}
+
+impl PixelScreen {
+    /// Create a new PXS by creating a drawing cotext atop the **HtmlCanvasElement**.
+    pub fn new(canvas: HtmlCanvasElement) -> Self {
+        // **get_context()** returns a untyped **JsValue**. **dyn_into::<T>()** performs an
+        // upcast into the real type. (Can error when the type does not match)
+        // --------------------------------------------------------------------------------
+        let ctx = canvas.get_context("2d").unwrap();
+        let ctx = ctx.unwrap().dyn_into::<CanvasRenderingContext2d>().unwrap();
+
+        let mut screen = Self {
+            canvas_ctx: ctx,
+            width: canvas.width(),
+            height: canvas.height(),
+            viewport: Viewport::fit(canvas.width(), canvas.height()),
+            canvas,
+            bound: BoundState::default(),
+            bound_stack: vec![],
+            colors: HashMap::new(),
+            counters: BindCounters::default(),
+            last_counters: BindCounters::default(),
+            draws: DrawCounters::default(),
+            last_draws: DrawCounters::default(),
+            mode: RenderMode::Path,
+            batch: None,
+            blit: None,
+            direct: false,
+            clip: None,
+        };
+        screen.fit();
+        screen
+    }
+
+    /// Resize the canvas to its size on the page multiplied by the device pixel ratio, when it changed
+    /// (e.g. the window was resized, or moved to another screen). Returns true, when it was resized.
+    fn fit(&mut self) -> bool {
+        let ratio = web_sys::window().map_or(1.0, |w| w.device_pixel_ratio());
+        let (css_width, css_height) = (self.canvas.client_width(), self.canvas.client_height());
+        if css_width <= 0 || css_height <= 0 {
+            return false; //<- Not laid out (e.g. hidden), keep the current size.
+        }
+
+        let width = (css_width as f64 * ratio).round() as u32;
+        let height = (css_height as f64 * ratio).round() as u32;
+        if width == self.width && height == self.height {
+            return false;
+        }
+
+        // Resizing the canvas resets the drawing context, so nothing is bound anymore.
+        // ----------------------------------------------------------------------------
+        self.canvas.set_width(width);
+        self.canvas.set_height(height);
+        self.width = width;
+        self.height = height;
+        self.viewport = Viewport::fit(width, height);
+        self.bound = BoundState::default();
+        self.bound_stack.clear();
+        self.clip = None;
+        true
+    }
+
+    /// Start a new frame for the bind and draw counters.
+    fn next_frame(&mut self) {
+        self.last_counters = self.counters;
+        self.counters = BindCounters::default();
+        self.last_draws = self.draws;
+        self.draws = DrawCounters::default();
+        self.batch = None; //<- Would be cleared anyway.
+    }
+
+    /// Remove the clip of the dirty rects (if there is one), together with the bound state set since.
+    fn unclip(&mut self) {
+        if let Some(bound) = self.clip.take() {
+            self.flush();
+            self.canvas_ctx.restore();
+            self.bound = bound;
+        }
+    }
+
+    /// The bind counters of the last frame (counted from one **clear()** to the next).
+    pub fn bind_counters(&self) -> BindCounters {
+        self.last_counters
+    }
+
+    /// The draw counters of the last frame (counted from one **clear()** to the next).
+    pub fn draw_counters(&self) -> DrawCounters {
+        self.last_draws
+    }
+
+    /// The viewport into the canvas.
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    /// The render mode.
+    pub fn mode(&self) -> RenderMode {
+        self.mode
+    }
+
+    /// Switch to render *mode*. Should be done between frames.
+    pub fn set_mode(&mut self, mode: RenderMode) {
+        self.present();
+        self.mode = mode;
+        match mode {
+            RenderMode::ImageData if self.blit.is_none() => {
+                let mut blit = Blit::new();
+                blit.buffer
+                    .set_fill_color(self.bound.fill_color.unwrap_or(CssColor::new(0, 0, 0)));
+                blit.buffer.set_alpha(self.bound.alpha.unwrap_or(255));
+                self.blit = Some(blit);
+            }
+            RenderMode::ImageData => {}
+            _ => self.blit = None,
+        }
+    }
+
+    /// Fill the rect at *x*, *y* with *w* and *h* (in canvas pixels) on the canvas. In path mode, it is
+    /// added to the current batch instead.
+    fn fill(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.draws.rects += 1;
+        if self.mode == RenderMode::Path {
+            self.batch
+                .get_or_insert_with(|| Path2d::new().unwrap())
+                .rect(x, y, w, h);
+        } else {
+            self.canvas_ctx.fill_rect(x, y, w, h);
+            self.draws.calls += 1;
+        }
+    }
+
+    /// Fill the current batch of the path mode (if there is one). Has to be done before the state of the
+    /// context changes.
+    fn flush(&mut self) {
+        if let Some(path) = self.batch.take() {
+            self.canvas_ctx.fill_with_path_2d(&path);
+            self.draws.calls += 1;
+        }
+    }
+
+    /// Draw everything, which is still batched (path mode), or in the framebuffer (image data mode) onto
+    /// the canvas. Has to be called at the end of every frame.
+    pub fn present(&mut self) {
+        self.flush();
+
+        let blit = match &mut self.blit {
+            Some(b) if b.dirty => b,
+            _ => return,
+        };
+
+        // Upload the framebuffer (one call), and scale it onto the grid area of the canvas. The canvas
+        // composites its transparent pixels, so everything drawn before stays visible.
+        // ---------------------------------------------------------------------------------------------
+        let data = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(blit.buffer.pixels()),
+            WIDTH * BLIT_SCALE,
+            HEIGHT * BLIT_SCALE,
+        )
+        .unwrap();
+        blit.ctx.put_image_data(&data, 0.0, 0.0).unwrap();
+
+        let grid = Viewport::fit(self.width, self.height); //<- Without camera, it is in the framebuffer.
+        let (x, y, w, h) = grid.rect(Pos::new(0.0, 0.0), WIDTH as f64, HEIGHT as f64);
+        self.canvas_ctx.save();
+        self.canvas_ctx.set_global_alpha(1.0);
+        self.canvas_ctx.set_image_smoothing_enabled(false);
+        self.canvas_ctx
+            .draw_image_with_html_canvas_element_and_dw_and_dh(&blit.canvas, x, y, w, h)
+            .unwrap();
+        self.canvas_ctx.restore();
+        self.draws.calls += 1;
+
+        blit.buffer.erase();
+        blit.dirty = false;
+    }
+
+    /// Set *color* as fill style of the context (converted through the cache).
+    fn set_fill_style(&mut self, color: CssColor) {
+        let value = self.colors.entry(color).or_insert_with(|| color.into());
+        self.canvas_ctx.set_fill_style(value);
+    }
+
+    /// Count a binding. Returns true, when it is redundant (*current* already equals *new*), and can be
+    /// skipped. Otherwise *current* is updated.
+    fn check_bind<T: PartialEq>(
+        counters: &mut BindCounters,
+        current: &mut Option<T>,
+        new: T,
+    ) -> bool {
+        if current.as_ref() == Some(&new) {
+            counters.skipped += 1;
+            true
+        } else {
+            counters.issued += 1;
+            *current = Some(new);
+            false
+        }
+    }
+}
+
+impl Backend for PixelScreen {
+    /// Clear the screen helper. This starts a new frame for the bind and draw counters, and adapts the
+    /// canvas to size changes. The whole canvas (also the letterbox bars) is filled with *background*.
+    fn clear(&mut self, background: CssColor) {
+        self.unclip();
+        self.next_frame();
+        self.fit();
+
+        // Fill in a saved state of the context, so the bound state is untouched.
+        // ------------------------------------------------------------------------
+        self.canvas_ctx.save();
+        self.set_fill_style(background);
+        self.canvas_ctx.set_global_alpha(1.0);
+        self.canvas_ctx
+            .fill_rect(0.0, 0.0, self.width as f64, self.height as f64);
+        self.canvas_ctx.restore();
+        self.draws.calls += 1;
+
+        if let Some(b) = &mut self.blit {
+            b.buffer.erase();
+            b.dirty = false;
+        }
+    }
+
+    /// Clear the damaged *regions* with *background*, and clip the following drawing to them. Clears the
+    /// whole canvas instead in image data mode, and when the canvas was resized.
+    fn clear_regions(&mut self, background: CssColor, regions: &[(u32, u32, u32, u32)]) -> bool {
+        self.unclip();
+        if self.mode == RenderMode::ImageData || self.fit() {
+            self.clear(background);
+            return false;
+        }
+        self.next_frame();
+
+        let path = Path2d::new().unwrap();
+        for &(x, y, w, h) in regions {
+            let (x, y, w, h) = self
+                .viewport
+                .rect(Pos::new(x as f64, y as f64), w as f64, h as f64);
+            path.rect(x, y, w, h);
+        }
+
+        // Clip in a saved state, which is restored by the next clear. The bound state stays untouched.
+        // ---------------------------------------------------------------------------------------------
+        self.clip = Some(self.bound);
+        self.canvas_ctx.save();
+        self.canvas_ctx.clip_with_path_2d(&path);
+
+        self.canvas_ctx.save();
+        self.set_fill_style(background);
+        self.canvas_ctx.set_global_alpha(1.0);
+        self.canvas_ctx.fill_with_path_2d(&path);
+        self.canvas_ctx.restore();
+        self.draws.calls += 1;
+        true
+    }
+
+    /// Draw rectangle at *pos* with *width* and *height* helper. Rasterized into the framebuffer in image
+    /// data mode.
+    fn draw_rect(&mut self, pos: Pos, width: f64, height: f64) {
+        match &mut self.blit {
+            Some(b) if !self.direct => {
+                b.buffer.draw_rect(pos, width, height);
+                b.dirty = true;
+            }
+            _ => {
+                let (x, y, w, h) = self.viewport.rect(pos, width, height);
+                self.fill(x, y, w, h);
+            }
+        }
+    }
+
+    /// Draw text at *pos* with the bitmap font in the bound font size. It is always filled directly onto
+    /// the canvas (on top of the rects drawn so far).
+    fn draw_text(&mut self, text: &str, pos: Pos) {
+        let size = self.font_size();
+        if self.mode == RenderMode::ImageData {
+            self.present();
+        }
+        self.direct = true;
+        font::draw_line(self, text, pos, size);
+        self.direct = false;
+    }
+
+    fn set_camera(&mut self, offset: Pos) {
+        self.viewport.set_camera(offset);
+        if let Some(b) = &mut self.blit {
+            b.buffer.set_camera(offset);
+        }
+    }
+
+    fn set_fill_color(&mut self, color: CssColor) {
+        if let Some(b) = &mut self.blit {
+            b.buffer.set_fill_color(color);
+        }
+        if Self::check_bind(&mut self.counters, &mut self.bound.fill_color, color) {
+            return;
+        }
+
+        self.flush();
+        self.set_fill_style(color);
+    }
+
+    /// Track the font. Only its size is used by the bitmap font.
+    fn set_font(&mut self, font: CssFont) {
+        Self::check_bind(&mut self.counters, &mut self.bound.font, font);
+    }
+
+    fn font_size(&self) -> u32 {
+        self.bound.font.map_or(10, |f| f.size())
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        if let Some(b) = &mut self.blit {
+            b.buffer.set_alpha(alpha);
+        }
+        if Self::check_bind(&mut self.counters, &mut self.bound.alpha, alpha) {
+            return;
+        }
+
+        self.flush();
+        self.canvas_ctx.set_global_alpha(alpha as f64 / 255.0);
+    }
+
+    fn save(&mut self) {
+        self.bound_stack.push(self.bound);
+        self.canvas_ctx.save();
+        if let Some(b) = &mut self.blit {
+            b.buffer.save();
+        }
+    }
+
+    fn restore(&mut self) {
+        self.flush();
+        if let Some(b) = self.bound_stack.pop() {
+            self.bound = b;
+        }
+        self.canvas_ctx.restore();
+        if let Some(b) = &mut self.blit {
+            b.buffer.restore();
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! Damage module. Dirty rect rendering: only the parts of the screen, which changed since the last
+//! frame, are cleared and drawn again.
+//!
+//! Every drawable reports its **Footprint**: the cells (virtual pixels) it covers, and a key, which
+//! changes whenever its look changes (e.g. the frame of an animation, or a text). The **Damage** tracker
+//! remembers the footprints of the last frame. Drawables, which are not in both frames with the same
+//! footprint, PSO and layer, have moved, changed, appeared or vanished, so the cells they covered before,
+//! and cover now, are damaged. Only the damaged cells are cleared, and only drawables touching them are
+//! drawn again (clipped to them).
+//!
+//! The whole screen is drawn, when a drawable does not report a footprint, when the camera of the scene
+//! is moved (screen shake), when the palette changes, and when the backend can not draw partially (see
+//! **Backend::clear_regions()**).
+
+use super::font::{self, Align};
+use super::viewport::{HEIGHT, WIDTH};
+use super::{Palette, Pos};
+use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// The cells a drawable covers (a rect at *x*, *y* with *w* x *h* cells), and the *key* of its look.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Footprint {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32,
+    pub key: u64,
+}
+
+impl Footprint {
+    /// Footprint of the cells at *x*, *y* with *w* x *h* cells and *key*.
+    pub fn new(x: i32, y: i32, w: u32, h: u32, key: u64) -> Self {
+        Self { x, y, w, h, key }
+    }
+
+    /// Footprint of the area at *pos* with *width* and *height* in virtual pixels (all cells it touches).
+    /// Positions between whole cells are mixed into the key, so moving within the same cells (e.g. when
+    /// interpolated) still changes the footprint.
+    pub fn covering(pos: Pos, width: f64, height: f64, key: u64) -> Self {
+        let (x0, y0) = (pos.x().floor(), pos.y().floor());
+        let (x1, y1) = ((pos.x() + width).ceil(), (pos.y() + height).ceil());
+        let offset = (pos.x() - x0, pos.y() - y0);
+        let key = if offset == (0.0, 0.0) {
+            key
+        } else {
+            let mut hasher = DefaultHasher::new();
+            (key, offset.0.to_bits(), offset.1.to_bits()).hash(&mut hasher);
+            hasher.finish()
+        };
+        Self::new(
+            x0 as i32,
+            y0 as i32,
+            (x1 - x0).max(0.0) as u32,
+            (y1 - y0).max(0.0) as u32,
+            key,
+        )
+    }
+
+    /// Footprint of *text* laid out like **draw_text()** does (at *pos* with the font *size*, aligned by
+    /// *align*). The key is the hash of the text, and there is a margin of a cell for rounding.
+    pub fn text(text: &str, pos: Pos, size: u32, align: Align) -> Self {
+        let (width, height) = font::measure_text(text, size);
+        let left = match align {
+            Align::Left => pos.x(),
+            Align::Center => pos.x() - width / 2.0,
+            Align::Right => pos.x() - width,
+        };
+
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        Self::covering(
+            Pos::new(left - 1.0, pos.y() - 1.0),
+            width + 2.0,
+            height + 2.0,
+            hasher.finish(),
+        )
+    }
+
+    /// Mark the cells of the footprint (clipped to the grid) in *cells*.
+    fn mark(&self, cells: &mut [bool]) {
+        let clip = |start: i32, len: u32, max: u32| {
+            let from = start.max(0).min(max as i32) as u32;
+            let to = (start + len as i32).max(0).min(max as i32) as u32;
+            from..to
+        };
+
+        for y in clip(self.y, self.h, HEIGHT) {
+            for x in clip(self.x, self.w, WIDTH) {
+                cells[(y * WIDTH + x) as usize] = true;
+            }
+        }
+    }
+
+    /// Checks, if the footprint touches the *region* (cells at x, y with width and height).
+    pub fn touches(&self, region: (u32, u32, u32, u32)) -> bool {
+        let (x, y, w, h) = region;
+        self.x < (x + w) as i32
+            && self.x + self.w as i32 > x as i32
+            && self.y < (y + h) as i32
+            && self.y + self.h as i32 > y as i32
+    }
+}
+
+/// Damage tracker. Contains the footprints of the last frame (with the hash of their layer and PSO,
+/// sorted), if the last frame had to be drawn completely, and the palette it was drawn with. The
+/// footprints of the current frame, the damaged cells and the regions are buffers, which are
+/// cleared and reused every frame.
+pub struct Damage {
+    last: Vec<(u64, Footprint)>,
+    last_full: bool,
+    last_palette: Option<&'static str>,
+    current: Vec<(u64, Footprint)>,
+    cells: Vec<bool>,
+    regions: Vec<(u32, u32, u32, u32)>,
+}
+
+impl Default for Damage {
+    /// Same as **new()**.
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Damage {
+    /// Create new damage tracker. The first frame is drawn completely.
+    pub fn new() -> Self {
+        Self {
+            last: vec![],
+            last_full: true,
+            last_palette: None,
+            current: vec![],
+            cells: vec![false; (WIDTH * HEIGHT) as usize],
+            regions: vec![],
+        }
+    }
+
+    /// Forget the last frame, so the next one is drawn completely (e.g. after the screen was cleared).
+    pub fn reset(&mut self) {
+        self.last_full = true;
+    }
+
+    /// Compare the footprints of the frame (*footprints*, with the hash of their layer and PSO, `None`
+    /// for unknown footprints) with the last frame. Returns the damaged regions (cells at x, y with width
+    /// and height), or `None`, when the whole screen has to be drawn. *moved* tells, if the camera is
+    /// moved.
+    pub fn update(
+        &mut self,
+        footprints: impl Iterator<Item = (u64, Option<Footprint>)>,
+        moved: bool,
+        palette: &Palette,
+    ) -> Option<&[(u32, u32, u32, u32)]> {
+        self.current.clear();
+        let mut unknown = false;
+        for (hash, footprint) in footprints {
+            match footprint {
+                Some(f) => self.current.push((hash, f)),
+                None => unknown = true,
+            }
+        }
+        self.current.sort_by_key(sort_key);
+
+        let full = self.last_full || unknown || moved || self.last_palette != Some(palette.name);
+        std::mem::swap(&mut self.last, &mut self.current); //<- The last frame is in current now.
+        self.last_full = unknown || moved;
+        self.last_palette = Some(palette.name);
+        if full {
+            return None;
+        }
+
+        // Walk through both sorted frames. Footprints, which are only in one of them, are damaged.
+        // ----------------------------------------------------------------------------------------
+        self.cells.fill(false);
+        let (mut a, mut b) = (self.current.iter().peekable(), self.last.iter().peekable());
+        loop {
+            let order = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) => sort_key(x).cmp(&sort_key(y)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+            match order {
+                Ordering::Less => a.next().unwrap().1.mark(&mut self.cells),
+                Ordering::Greater => b.next().unwrap().1.mark(&mut self.cells),
+                Ordering::Equal => {
+                    a.next();
+                    b.next();
+                }
+            }
+        }
+
+        regions(&self.cells, &mut self.regions);
+        Some(&self.regions)
+    }
+}
+
+/// Sort key of a footprint with the hash of its layer and PSO.
+fn sort_key(entry: &(u64, Footprint)) -> (u64, i32, i32, u32, u32, u64) {
+    let (h, f) = entry;
+    (*h, f.x, f.y, f.w, f.h, f.key)
+}
+
+/// Merge the damaged *cells* into *regions* (cleared first). Runs of damaged cells in a row are merged
+/// with the same run in the row above.
+fn regions(cells: &[bool], regions: &mut Vec<(u32, u32, u32, u32)>) {
+    regions.clear();
+    let mut open = 0; //<- Regions from this index on may still grow downwards.
+
+    for y in 0..HEIGHT {
+        let row = &cells[(y * WIDTH) as usize..((y + 1) * WIDTH) as usize];
+        let first_of_row = regions.len();
+
+        let mut x = 0;
+        while x < WIDTH {
+            if !row[x as usize] {
+                x += 1;
+                continue;
+            }
+            let start = x;
+            while x < WIDTH && row[x as usize] {
+                x += 1;
+            }
+
+            match regions[open..first_of_row]
+                .iter_mut()
+                .find(|r| r.0 == start && r.2 == x - start && r.1 + r.3 == y)
+            {
+                Some(r) => r.3 += 1,
+                None => regions.push((start, y, x - start, 1)),
+            }
+        }
+
+        // Regions, which did not grow in this row, are closed.
+        // -----------------------------------------------------
+        regions[open..].sort_by_key(|r| r.1 + r.3 == y + 1);
+        open += regions[open..]
+            .iter()
+            .take_while(|r| r.1 + r.3 != y + 1)
+            .count();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buffers_cleared_every_frame() {
+        let palette = Palette::default_theme();
+        let mut damage = Damage::new();
+        let frame = |x| std::iter::once((0, Some(Footprint::new(x, 0, 1, 1, 0))));
+
+        // The first frame is drawn completely, and frames without changes have no damage, even
+        // after one with damage (the cells and regions of the last frame are not left over).
+        // ------------------------------------------------------------------------------------
+        assert_eq!(damage.update(frame(3), false, palette), None);
+        assert_eq!(
+            damage.update(frame(4), false, palette),
+            Some(&[(3, 0, 2, 1)][..])
+        );
+        assert_eq!(damage.update(frame(4), false, palette), Some(&[][..]));
+        assert_eq!(
+            damage.update(frame(6), false, palette),
+            Some(&[(4, 0, 1, 1), (6, 0, 1, 1)][..])
+        );
+        assert_eq!(damage.update(frame(6), true, palette), None);
+    }
+}
@@ -0,0 +1,303 @@
+//! Bitmap font module. A tiny built-in 3x5 pixel font for the printable ASCII characters. All text
+//! of the game is drawn with it, so it looks the same on every OS and every backend.
+//!
+//! Every glyph is 5 rows, and every row is a 3 bit mask (the highest bit is the leftmost pixel).
+//! Lower case letters are drawn with the glyphs of the upper case ones (there is no room for both
+//! in 3x5).
+//!
+//! Font pixels are drawn as filled rects on the virtual grid of the sprites: every font pixel
+//! covers whole virtual pixels, and text is placed at whole virtual pixels. Font sizes are px at
+//! the reference scale of 10px per virtual pixel (an 800x600 canvas), so a 10px font draws a font
+//! pixel per virtual pixel, and a 20px font draws font pixels of 2x2 virtual pixels (sizes are
+//! rounded down to multiples of 10px, but are at least 10px). At a font pixel per virtual pixel, a
+//! line of the 80x60 grid fits 20 characters.
+//!
+//! Besides drawing single lines, the module provides text measurement, and the layout of multi-line
+//! text with left, centered or right alignment (**measure_text()** and **draw_text()**).
+
+use super::{Backend, Pos};
+
+/// Width of a glyph in font pixels.
+pub const GLYPH_WIDTH: u32 = 3;
+
+/// Height of a glyph in font pixels.
+pub const GLYPH_HEIGHT: u32 = 5;
+
+/// Horizontal distance between two glyphs in font pixels (glyph width and one pixel spacing).
+pub const ADVANCE: u32 = 4;
+
+/// Vertical distance between the tops of two lines in font pixels (glyph height and one pixel
+/// spacing).
+pub const LINE_HEIGHT: u32 = 6;
+
+/// Horizontal alignment of text relative to its anchor position.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Align {
+    /// The anchor is the left end of the lines.
+    Left,
+    /// The anchor is the center of the lines.
+    Center,
+    /// The anchor is the right end of the lines.
+    Right,
+}
+
+/// Glyphs of the ASCII characters 32 (space) to 96 (backtick), followed by the ones of 123 (left
+/// brace) to 126 (tilde).
+#[rustfmt::skip]
+const GLYPHS: [[u8; 5]; 69] = [
+    [0b000, 0b000, 0b000, 0b000, 0b000], // space
+    [0b010, 0b010, 0b010, 0b000, 0b010], // !
+    [0b101, 0b101, 0b000, 0b000, 0b000], // "
+    [0b101, 0b111, 0b101, 0b111, 0b101], // #
+    [0b011, 0b110, 0b111, 0b011, 0b110], // $
+    [0b101, 0b001, 0b010, 0b100, 0b101], // %
+    [0b010, 0b101, 0b010, 0b101, 0b011], // &
+    [0b010, 0b010, 0b000, 0b000, 0b000], // '
+    [0b001, 0b010, 0b010, 0b010, 0b001], // (
+    [0b100, 0b010, 0b010, 0b010, 0b100], // )
+    [0b000, 0b101, 0b010, 0b101, 0b000], // *
+    [0b000, 0b010, 0b111, 0b010, 0b000], // +
+    [0b000, 0b000, 0b000, 0b010, 0b100], // ,
+    [0b000, 0b000, 0b111, 0b000, 0b000], // -
+    [0b000, 0b000, 0b000, 0b000, 0b010], // .
+    [0b001, 0b001, 0b010, 0b100, 0b100], // /
+    [0b111, 0b101, 0b101, 0b101, 0b111], // 0
+    [0b010, 0b110, 0b010, 0b010, 0b111], // 1
+    [0b111, 0b001, 0b111, 0b100, 0b111], // 2
+    [0b111, 0b001, 0b011, 0b001, 0b111], // 3
+    [0b101, 0b101, 0b111, 0b001, 0b001], // 4
+    [0b111, 0b100, 0b111, 0b001, 0b111], // 5
+    [0b111, 0b100, 0b111, 0b101, 0b111], // 6
+    [0b111, 0b001, 0b001, 0b001, 0b001], // 7
+    [0b111, 0b101, 0b111, 0b101, 0b111], // 8
+    [0b111, 0b101, 0b111, 0b001, 0b111], // 9
+    [0b000, 0b010, 0b000, 0b010, 0b000], // :
+    [0b000, 0b010, 0b000, 0b010, 0b100], // ;
+    [0b001, 0b010, 0b100, 0b010, 0b001], // <
+    [0b000, 0b111, 0b000, 0b111, 0b000], // =
+    [0b100, 0b010, 0b001, 0b010, 0b100], // >
+    [0b111, 0b001, 0b011, 0b000, 0b010], // ?
+    [0b010, 0b101, 0b101, 0b100, 0b011], // @
+    [0b111, 0b101, 0b111, 0b101, 0b101], // A
+    [0b110, 0b101, 0b110, 0b101, 0b110], // B
+    [0b011, 0b100, 0b100, 0b100, 0b011], // C
+    [0b110, 0b101, 0b101, 0b101, 0b110], // D
+    [0b111, 0b100, 0b110, 0b100, 0b111], // E
+    [0b111, 0b100, 0b110, 0b100, 0b100], // F
+    [0b011, 0b100, 0b101, 0b101, 0b011], // G
+    [0b101, 0b101, 0b111, 0b101, 0b101], // H
+    [0b111, 0b010, 0b010, 0b010, 0b111], // I
+    [0b001, 0b001, 0b001, 0b101, 0b010], // J
+    [0b101, 0b101, 0b110, 0b101, 0b101], // K
+    [0b100, 0b100, 0b100, 0b100, 0b111], // L
+    [0b101, 0b111, 0b111, 0b101, 0b101], // M
+    [0b110, 0b101, 0b101, 0b101, 0b101], // N
+    [0b010, 0b101, 0b101, 0b101, 0b010], // O
+    [0b110, 0b101, 0b110, 0b100, 0b100], // P
+    [0b010, 0b101, 0b101, 0b110, 0b011], // Q
+    [0b110, 0b101, 0b110, 0b101, 0b101], // R
+    [0b011, 0b100, 0b010, 0b001, 0b110], // S
+    [0b111, 0b010, 0b010, 0b010, 0b010], // T
+    [0b101, 0b101, 0b101, 0b101, 0b111], // U
+    [0b101, 0b101, 0b101, 0b101, 0b010], // V
+    [0b101, 0b101, 0b111, 0b111, 0b101], // W
+    [0b101, 0b101, 0b010, 0b101, 0b101], // X
+    [0b101, 0b101, 0b010, 0b010, 0b010], // Y
+    [0b111, 0b001, 0b010, 0b100, 0b111], // Z
+    [0b011, 0b010, 0b010, 0b010, 0b011], // [
+    [0b100, 0b100, 0b010, 0b001, 0b001], // \
+    [0b110, 0b010, 0b010, 0b010, 0b110], // ]
+    [0b010, 0b101, 0b000, 0b000, 0b000], // ^
+    [0b000, 0b000, 0b000, 0b000, 0b111], // _
+    [0b100, 0b010, 0b000, 0b000, 0b000], // `
+    [0b011, 0b010, 0b110, 0b010, 0b011], // {
+    [0b010, 0b010, 0b010, 0b010, 0b010], // |
+    [0b110, 0b010, 0b011, 0b010, 0b110], // }
+    [0b000, 0b000, 0b011, 0b110, 0b000], // ~
+];
+
+/// Get the glyph of *c*. Lower case letters are drawn as upper case ones, and characters without a
+/// glyph as `?`.
+pub fn glyph(c: char) -> &'static [u8; 5] {
+    match c.to_ascii_uppercase() {
+        c @ ' '..='`' => &GLYPHS[c as usize - 32],
+        c @ '{'..='~' => &GLYPHS[c as usize - 123 + 65],
+        _ => &GLYPHS['?' as usize - 32],
+    }
+}
+
+/// Size of a font pixel in (whole) virtual pixels for the font *size*.
+fn scale(size: u32) -> f64 {
+    (size / 10).max(1) as f64
+}
+
+/// Width of the single *line* in virtual pixels with the font *size* (without the spacing after the
+/// last glyph).
+fn line_width(line: &str, size: u32) -> f64 {
+    let glyphs = line.chars().count() as u32;
+    (glyphs * ADVANCE).saturating_sub(ADVANCE - GLYPH_WIDTH) as f64 * scale(size)
+}
+
+/// Measure *text* (lines separated by `\n`) with the font *size*. Returns the width of the longest
+/// line and the height of all lines in virtual pixels.
+pub fn measure_text(text: &str, size: u32) -> (f64, f64) {
+    let width = text
+        .split('\n')
+        .map(|l| line_width(l, size))
+        .fold(0.0, f64::max);
+    let lines = text.split('\n').count() as u32;
+    let height = ((lines - 1) * LINE_HEIGHT + GLYPH_HEIGHT) as f64 * scale(size);
+    (width, height)
+}
+
+/// Draw *text* (lines separated by `\n`) through **Backend::draw_text()**. The top of the first
+/// line is at the anchor *pos*, and every line is aligned to it by *align*.
+///
+/// The text is laid out in the font size of the backend (bound by the PSO of the text).
+pub fn draw_text(backend: &mut dyn Backend, text: &str, pos: Pos, align: Align) {
+    let size = backend.font_size();
+    let scale = scale(size);
+
+    for (i, line) in text.split('\n').enumerate() {
+        // Centered lines are moved by whole font pixels.
+        // ----------------------------------------------
+        let left = match align {
+            Align::Left => pos.x,
+            Align::Center => pos.x - (line_width(line, size) / 2.0 / scale).round() * scale,
+            Align::Right => pos.x - line_width(line, size),
+        };
+        let baseline = pos.y + (i as u32 * LINE_HEIGHT + GLYPH_HEIGHT) as f64 * scale;
+        backend.draw_text(line, Pos::new(left, baseline));
+    }
+}
+
+/// Draw the single *line* with its left end of the baseline at *pos* as font pixels (with the font
+/// *size*) through **Backend::draw_rect()**. The bottom of the glyphs sits on the baseline, and the
+/// line is moved to the closest whole virtual pixel, so the font pixels are on the grid.
+///
+/// This is how backends implement **Backend::draw_text()**.
+pub fn draw_line(backend: &mut dyn Backend, line: &str, pos: Pos, size: u32) {
+    let scale = scale(size);
+    let top = (pos.y - GLYPH_HEIGHT as f64 * scale).round();
+
+    for (i, c) in line.chars().enumerate() {
+        let left = pos.x.round() + (i as u32 * ADVANCE) as f64 * scale;
+
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for col in 0..GLYPH_WIDTH {
+                if bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                    backend.draw_rect(
+                        Pos::new(left + col as f64 * scale, top + row as f64 * scale),
+                        scale,
+                        scale,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Call, CssFont, RecordingBackend};
+
+    #[test]
+    fn measure() {
+        assert_eq!(measure_text("AB", 10), (7.0, 5.0)); //<- No spacing after the last glyph.
+        assert_eq!(measure_text("A\nBCD", 10), (11.0, 11.0));
+        assert_eq!(measure_text("", 10), (0.0, 5.0));
+        assert_eq!(measure_text("AB", 20), (14.0, 10.0));
+        assert_eq!(measure_text("AB", 5), measure_text("AB", 10)); //<- At least a virtual pixel.
+    }
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(glyph('a'), glyph('A'));
+        assert_eq!(glyph('~'), &[0b000, 0b000, 0b011, 0b110, 0b000]);
+        assert_eq!(glyph('é'), glyph('?'));
+    }
+
+    /// The rects of *line* drawn at *pos* with the font *size*.
+    fn rects(line: &str, pos: Pos, size: u32) -> Vec<Call> {
+        let mut backend = RecordingBackend::new();
+        draw_line(&mut backend, line, pos, size);
+        backend.take()
+    }
+
+    #[test]
+    fn pixels_on_grid() {
+        let rect = |x, y, s| Call::Rect((x, y), s, s);
+        assert_eq!(
+            rects("-1", Pos::new(2.0, 10.0), 10),
+            [
+                rect(2.0, 7.0, 1.0), //<- The dash.
+                rect(3.0, 7.0, 1.0),
+                rect(4.0, 7.0, 1.0),
+                rect(7.0, 5.0, 1.0), //<- The one.
+                rect(6.0, 6.0, 1.0),
+                rect(7.0, 6.0, 1.0),
+                rect(7.0, 7.0, 1.0),
+                rect(7.0, 8.0, 1.0),
+                rect(6.0, 9.0, 1.0),
+                rect(7.0, 9.0, 1.0),
+                rect(8.0, 9.0, 1.0),
+            ]
+        );
+
+        // Lines between virtual pixels are moved onto the grid, and larger fonts draw whole
+        // virtual pixels.
+        // ---------------------------------------------------------------------------------
+        assert_eq!(
+            rects("-", Pos::new(2.4, 9.6), 10),
+            rects("-", Pos::new(2.0, 10.0), 10)
+        );
+        assert_eq!(
+            rects("-", Pos::new(2.0, 10.0), 25),
+            [
+                rect(2.0, 4.0, 2.0),
+                rect(4.0, 4.0, 2.0),
+                rect(6.0, 4.0, 2.0)
+            ]
+        );
+    }
+
+    /// The lines drawn for *text* at 40, 10 aligned by *align*, with a font of *size* bound.
+    fn lines(text: &str, size: u32, align: Align) -> Vec<Call> {
+        let mut backend = RecordingBackend::new();
+        backend.set_font(CssFont::monospace(size));
+        backend.take();
+        draw_text(&mut backend, text, Pos::new(40.0, 10.0), align);
+        backend.take()
+    }
+
+    #[test]
+    fn align_lines() {
+        let text = |s: &str, x, y| Call::Text(s.to_owned(), (x, y));
+        assert_eq!(
+            lines("AB\nC", 10, Align::Left),
+            [text("AB", 40.0, 15.0), text("C", 40.0, 21.0)]
+        );
+        assert_eq!(
+            lines("AB\nC", 10, Align::Center),
+            [text("AB", 36.0, 15.0), text("C", 38.0, 21.0)] //<- Rounded to whole font pixels.
+        );
+        assert_eq!(
+            lines("AB\nC", 10, Align::Right),
+            [text("AB", 33.0, 15.0), text("C", 37.0, 21.0)]
+        );
+    }
+
+    #[test]
+    fn align_uses_bound_font_size() {
+        let text = |s: &str, x, y| Call::Text(s.to_owned(), (x, y));
+        assert_eq!(
+            lines("AB\nC", 20, Align::Center),
+            [text("AB", 32.0, 20.0), text("C", 36.0, 32.0)]
+        );
+        assert_eq!(
+            lines("AB\nC", 20, Align::Right),
+            [text("AB", 26.0, 20.0), text("C", 34.0, 32.0)]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Software framebuffer backend module. A headless rendering backend, which rasterizes the scene into
+//! an in-memory RGBA buffer. It works without a browser (e.g. for screenshots and golden-image tests).
+//!
+//! Rasterization follows the rules of the Canvas2D API, so the result looks (close to) like the canvas:
+//! a pixel is filled, when its center lies inside a rect (after the transform by the viewport, which fits
+//! the virtual grid into the framebuffer). Text is drawn with the built-in bitmap font
+//! from the **font** module, scaled to the font size (like on every other backend).
+//!
+//! Dirty rects are drawn with a clip mask of the damaged pixels, which is removed by the next clear.
+//!
+//! The framebuffer can also be erased to transparent pixels. Drawing onto them composites like the canvas
+//! does, so a framebuffer can be drawn onto a canvas as an image (see the blit mode of the **PixelScreen**).
+
+use super::font;
+use super::{png, Backend, CssColor, CssFont, Pos, Viewport};
+use std::ops::Range;
+
+/// Drawing state set by PSOs, which is pushed by **save()** and popped by **restore()**.
+#[derive(Copy, Clone)]
+struct State {
+    fill_color: CssColor,
+    font_size: u32,
+    alpha: u8,
+}
+
+impl Default for State {
+    /// Same defaults like the Canvas2D context (black, 10px font, opaque).
+    fn default() -> Self {
+        Self {
+            fill_color: CssColor::new(0, 0, 0),
+            font_size: 10,
+            alpha: 255,
+        }
+    }
+}
+
+/// In-memory RGBA framebuffer with *width* x *height* pixels, and the viewport into it. *clip* marks the
+/// pixels, which can be drawn (all, when it is `None`).
+pub struct Framebuffer {
+    width: u32,
+    height: u32,
+    viewport: Viewport,
+    pixels: Vec<u8>,
+    state: State,
+    state_stack: Vec<State>,
+    clip: Option<Vec<bool>>,
+}
+
+impl Framebuffer {
+    /// Create new framebuffer with *width* and *height* in pixels. It starts cleared to white.
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut fb = Self {
+            width,
+            height,
+            viewport: Viewport::fit(width, height),
+            pixels: vec![0; width as usize * height as usize * 4],
+            state: State::default(),
+            state_stack: vec![],
+            clip: None,
+        };
+        fb.clear(CssColor::new(255, 255, 255));
+        fb
+    }
+
+    /// Width in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Height in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// The raw pixel data. 4 bytes (RGBA) per pixel, row by row starting at the top left.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// The RGBA value of the pixel at *x*, *y*.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y * self.width + x) as usize * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Encode the current content as PNG image.
+    pub fn to_png(&self) -> Vec<u8> {
+        png::encode(self.width, self.height, &self.pixels)
+    }
+
+    /// Make all pixels fully transparent (e.g. to draw sprites, which are composited onto something
+    /// else later).
+    pub fn erase(&mut self) {
+        for p in self.pixels.iter_mut() {
+            *p = 0;
+        }
+        self.clip = None;
+    }
+
+    /// The pixels (columns and rows), which have their centers inside the rect at *x*, *y* with *w* and
+    /// *h* (clamped to the framebuffer).
+    fn span(&self, x: f64, y: f64, w: f64, h: f64) -> (Range<u32>, Range<u32>) {
+        let span = |start: f64, len: f64, max: u32| {
+            let from = (start - 0.5).ceil().max(0.0).min(max as f64) as u32;
+            let to = (start + len - 0.5).ceil().max(0.0).min(max as f64) as u32;
+            from..to
+        };
+        (span(x, w, self.width), span(y, h, self.height))
+    }
+
+    /// Fill the pixels covered by the rect at *x*, *y* with *w* and *h* with the current fill color
+    /// (blended with the current alpha). Pixels outside of the clip mask are left alone.
+    fn fill(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        let (xs, ys) = self.span(x, y, w, h);
+
+        let CssColor { r, g, b } = self.state.fill_color;
+        let a = self.state.alpha as u32;
+        let blend =
+            |dst: u8, src: u8| ((src as u32 * a + dst as u32 * (255 - a) + 127) / 255) as u8;
+
+        for py in ys {
+            for px in xs.clone() {
+                let i = (py * self.width + px) as usize * 4;
+                if self.clip.as_ref().is_some_and(|c| !c[i / 4]) {
+                    continue;
+                }
+                if self.pixels[i + 3] == 255 {
+                    self.pixels[i] = blend(self.pixels[i], r);
+                    self.pixels[i + 1] = blend(self.pixels[i + 1], g);
+                    self.pixels[i + 2] = blend(self.pixels[i + 2], b);
+                } else {
+                    self.blend_over(i, [r, g, b], a);
+                }
+            }
+        }
+    }
+
+    /// Blend the color *src* with alpha *a* over the (translucent) pixel at byte offset *i*, like the
+    /// canvas composites images ("source-over").
+    fn blend_over(&mut self, i: usize, src: [u8; 3], a: u32) {
+        let dst_a = self.pixels[i + 3] as u32 * (255 - a) / 255;
+        let out_a = a + dst_a;
+        if out_a == 0 {
+            return;
+        }
+
+        for (c, s) in src.iter().enumerate() {
+            let d = self.pixels[i + c] as u32;
+            self.pixels[i + c] = ((*s as u32 * a + d * dst_a + out_a / 2) / out_a) as u8;
+        }
+        self.pixels[i + 3] = out_a as u8;
+    }
+}
+
+impl Backend for Framebuffer {
+    /// Clear to the opaque *background*.
+    fn clear(&mut self, background: CssColor) {
+        let CssColor { r, g, b } = background;
+        for p in self.pixels.chunks_mut(4) {
+            p.copy_from_slice(&[r, g, b, 255]);
+        }
+        self.clip = None;
+    }
+
+    /// Clear the pixels of the *regions* to the opaque *background*, and make them the clip mask.
+    fn clear_regions(&mut self, background: CssColor, regions: &[(u32, u32, u32, u32)]) -> bool {
+        let CssColor { r, g, b } = background;
+        let mut clip = vec![false; (self.width * self.height) as usize];
+        for &(x, y, w, h) in regions {
+            let (x, y, w, h) = self
+                .viewport
+                .rect(Pos::new(x as f64, y as f64), w as f64, h as f64);
+            let (xs, ys) = self.span(x, y, w, h);
+            for py in ys {
+                for px in xs.clone() {
+                    let i = (py * self.width + px) as usize;
+                    clip[i] = true;
+                    self.pixels[i * 4..i * 4 + 4].copy_from_slice(&[r, g, b, 255]);
+                }
+            }
+        }
+        self.clip = Some(clip);
+        true
+    }
+
+    fn draw_rect(&mut self, pos: Pos, width: f64, height: f64) {
+        let (x, y, w, h) = self.viewport.rect(pos, width, height);
+        self.fill(x, y, w, h);
+    }
+
+    /// Draw *text* with the bitmap font in the current font size.
+    fn draw_text(&mut self, text: &str, pos: Pos) {
+        let size = self.state.font_size;
+        font::draw_line(self, text, pos, size);
+    }
+
+    fn set_camera(&mut self, offset: Pos) {
+        self.viewport.set_camera(offset);
+    }
+
+    fn set_fill_color(&mut self, color: CssColor) {
+        self.state.fill_color = color;
+    }
+
+    fn set_font(&mut self, font: CssFont) {
+        self.state.font_size = font.size;
+    }
+
+    fn font_size(&self) -> u32 {
+        self.state.font_size
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        self.state.alpha = alpha;
+    }
+
+    fn save(&mut self) {
+        self.state_stack.push(self.state);
+    }
+
+    fn restore(&mut self) {
+        if let Some(s) = self.state_stack.pop() {
+            self.state = s;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A framebuffer with one pixel per virtual pixel, erased to transparent pixels.
+    fn erased() -> Framebuffer {
+        let mut fb = Framebuffer::new(80, 60);
+        fb.erase();
+        fb
+    }
+
+    /// Fill the virtual pixel at *x*, 0 of *fb* with *color* and *alpha*.
+    fn dot(fb: &mut Framebuffer, x: f64, color: CssColor, alpha: u8) {
+        fb.set_fill_color(color);
+        fb.set_alpha(alpha);
+        fb.draw_rect(Pos::new(x, 0.0), 1.0, 1.0);
+    }
+
+    #[test]
+    fn erase() {
+        let mut fb = Framebuffer::new(80, 60);
+        fb.clear_regions(CssColor::new(0, 0, 0), &[(0, 0, 1, 1)]);
+        fb.erase();
+        assert!(fb.pixels().iter().all(|p| *p == 0));
+
+        dot(&mut fb, 5.0, CssColor::new(255, 0, 0), 255); //<- The clip mask is gone as well.
+        assert_eq!(fb.pixel(5, 0), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn blend_over_transparent() {
+        let mut fb = erased();
+        dot(&mut fb, 0.0, CssColor::new(200, 100, 0), 128);
+        assert_eq!(fb.pixel(0, 0), [200, 100, 0, 128]);
+
+        dot(&mut fb, 1.0, CssColor::new(200, 100, 0), 0);
+        assert_eq!(fb.pixel(1, 0), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn blend_over_translucent() {
+        // Red with alpha 128 over blue with alpha 128 (source-over): alpha 128 + 128 * 127 / 255 = 191,
+        // and the colors weighted by 128 and 63.
+        // ---------------------------------------------------------------------------------------------
+        let mut fb = erased();
+        dot(&mut fb, 0.0, CssColor::new(0, 0, 255), 128);
+        dot(&mut fb, 0.0, CssColor::new(255, 0, 0), 128);
+        assert_eq!(fb.pixel(0, 0), [171, 0, 84, 191]);
+
+        // Opaque drawing covers everything, and opaque pixels blend like on a cleared framebuffer.
+        // ----------------------------------------------------------------------------------------
+        dot(&mut fb, 0.0, CssColor::new(0, 255, 0), 255);
+        assert_eq!(fb.pixel(0, 0), [0, 255, 0, 255]);
+        dot(&mut fb, 0.0, CssColor::new(0, 0, 255), 51);
+        assert_eq!(fb.pixel(0, 0), [0, 204, 51, 255]);
+    }
+
+    #[test]
+    fn sub_pixel_positions() {
+        // With 4 pixels per virtual pixel, a quarter virtual pixel moves a rect by one pixel.
+        // -----------------------------------------------------------------------------------
+        let mut fb = Framebuffer::new(320, 240);
+        fb.erase();
+        dot(&mut fb, 0.25, CssColor::new(255, 255, 255), 255);
+        let row: Vec<u8> = (0..6).map(|x| fb.pixel(x, 0)[3]).collect();
+        assert_eq!(row, [0, 255, 255, 255, 255, 0]);
+    }
+}
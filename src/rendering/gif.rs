@@ -0,0 +1,523 @@
+//! GIF encoder module. A small dependency free encoder for animated GIFs, used to capture gameplay from
+//! the **Framebuffer** (see **Headless::capture_gif()**).
+//!
+//! Every frame gets its own color table. Game frames use only a handful of colors, so they fit into the
+//! 256 entries exactly. Frames with more colors (e.g. blended ones) are reduced by dropping low color
+//! bits, until they fit.
+//!
+//! Only the part of a frame, which changed since the previous one, is stored (the previous frame stays
+//! visible below it). Frames without any change are merged into the previous one by adding up their
+//! delays. The animation loops forever.
+
+use std::collections::HashMap;
+
+/// The 6 byte GIF file signature and version.
+const SIGNATURE: &[u8; 6] = b"GIF89a";
+
+/// Largest LZW code size (the code table has 4096 entries).
+const MAX_CODE_SIZE: u32 = 12;
+
+/// Encodes RGBA frames with *width* and *height* into an animated GIF. The last frame added is kept
+/// back, until its delay is known for sure (see **add_frame()**).
+pub struct GifEncoder {
+    width: u16,
+    height: u16,
+    out: Vec<u8>,
+    previous: Option<Vec<u8>>,
+    pending: Option<(Vec<u8>, u32)>,
+}
+
+impl GifEncoder {
+    /// Create new encoder for frames with *width* and *height* in pixels. Writes the header right away.
+    pub fn new(width: u32, height: u32) -> Self {
+        let (width, height) = (width.min(0xffff) as u16, height.min(0xffff) as u16);
+
+        let mut out = Vec::new();
+        out.extend_from_slice(SIGNATURE);
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.extend_from_slice(&[0, 0, 0]); //<- No global color table, background 0, square pixels.
+
+        // Netscape application extension, which makes the animation loop forever.
+        // -----------------------------------------------------------------------
+        out.extend_from_slice(&[0x21, 0xff, 11]);
+        out.extend_from_slice(b"NETSCAPE2.0");
+        out.extend_from_slice(&[3, 1, 0, 0, 0]);
+
+        Self {
+            width,
+            height,
+            out,
+            previous: None,
+            pending: None,
+        }
+    }
+
+    /// Add a frame from *rgba* pixel data (4 bytes per pixel, row by row, alpha is ignored), which is
+    /// shown for *delay* hundredths of a second. A frame, which looks exactly like the one before it, only
+    /// extends the delay of that one.
+    pub fn add_frame(&mut self, rgba: &[u8], delay: u32) {
+        assert_eq!(rgba.len(), self.width as usize * self.height as usize * 4);
+
+        match &mut self.pending {
+            Some((pixels, d)) if pixels[..] == rgba[..] => *d += delay,
+            _ => {
+                self.flush_pending();
+                self.pending = Some((rgba.to_vec(), delay));
+            }
+        }
+    }
+
+    /// Write the remaining frame and the trailer. Returns the GIF file.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.flush_pending();
+        self.out.push(0x3b);
+        self.out
+    }
+
+    /// Write the frame, which was kept back, if there is one.
+    fn flush_pending(&mut self) {
+        if let Some((pixels, delay)) = self.pending.take() {
+            self.write_frame(&pixels, delay);
+            self.previous = Some(pixels);
+        }
+    }
+
+    /// Write the part of the frame with *rgba* pixel data, which changed since the previous frame (all
+    /// of the first one), with *delay*.
+    fn write_frame(&mut self, rgba: &[u8], delay: u32) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (x0, y0, x1, y1) = match &self.previous {
+            Some(previous) => changed_rect(previous, rgba, width, height).unwrap_or((0, 0, 1, 1)),
+            None => (0, 0, width, height),
+        };
+
+        // Graphics control extension: keep the frame below (disposal 1), no transparency.
+        // -------------------------------------------------------------------------------
+        let delay = delay.min(0xffff) as u16;
+        self.out.extend_from_slice(&[0x21, 0xf9, 4, 0x04]);
+        self.out.extend_from_slice(&delay.to_le_bytes());
+        self.out.extend_from_slice(&[0, 0]);
+
+        let mut colors = vec![];
+        for y in y0..y1 {
+            let row = &rgba[(y * width + x0) * 4..(y * width + x1) * 4];
+            colors.extend(row.chunks(4).map(|p| [p[0], p[1], p[2]]));
+        }
+        let (table, indices) = quantize(&colors);
+
+        // Image descriptor with the local color table (its size is a power of 2, at least 4 entries).
+        // -------------------------------------------------------------------------------------------
+        let bits = (32 - (table.len() as u32 - 1).leading_zeros()).max(2);
+        self.out.push(0x2c);
+        for v in [x0, y0, x1 - x0, y1 - y0].iter() {
+            self.out.extend_from_slice(&(*v as u16).to_le_bytes());
+        }
+        self.out.push(0x80 | (bits - 1) as u8);
+        for i in 0..1 << bits {
+            self.out
+                .extend_from_slice(table.get(i).map(|c| &c[..]).unwrap_or(&[0, 0, 0]));
+        }
+
+        // The image data is LZW compressed, and split into sub-blocks of up to 255 bytes.
+        // -------------------------------------------------------------------------------
+        self.out.push(bits as u8);
+        for block in lzw(&indices, bits).chunks(255) {
+            self.out.push(block.len() as u8);
+            self.out.extend_from_slice(block);
+        }
+        self.out.push(0);
+    }
+}
+
+/// The rect (*x0*, *y0* to *x1*, *y1* exclusive) around all pixels, which differ between *previous* and
+/// *current* with *width* and *height*. Returns `None`, when they are the same.
+fn changed_rect(
+    previous: &[u8],
+    current: &[u8],
+    width: usize,
+    height: usize,
+) -> Option<(usize, usize, usize, usize)> {
+    let mut rect: Option<(usize, usize, usize, usize)> = None;
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) * 4;
+            if previous[i..i + 3] != current[i..i + 3] {
+                rect = Some(match rect {
+                    Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x + 1), y1.max(y + 1)),
+                    None => (x, y, x + 1, y + 1),
+                });
+            }
+        }
+    }
+    rect
+}
+
+/// Build a color table with up to 256 entries for *colors*, and the index of every color in it. Low color
+/// bits are dropped, until the colors fit.
+fn quantize(colors: &[[u8; 3]]) -> (Vec<[u8; 3]>, Vec<u8>) {
+    for shift in 0..8 {
+        let reduce = |c: &[u8; 3]| {
+            let mut c = *c;
+            for v in c.iter_mut() {
+                *v = *v >> shift << shift;
+            }
+            c
+        };
+
+        let mut table = vec![];
+        let mut lookup = HashMap::new();
+        let indices: Option<Vec<u8>> = colors
+            .iter()
+            .map(|c| {
+                let c = reduce(c);
+                if let Some(i) = lookup.get(&c) {
+                    return Some(*i);
+                }
+                if table.len() == 256 {
+                    return None;
+                }
+                table.push(c);
+                lookup.insert(c, (table.len() - 1) as u8);
+                Some((table.len() - 1) as u8)
+            })
+            .collect();
+
+        if let Some(indices) = indices {
+            return (table, indices);
+        }
+    }
+    unreachable!("colors with 1 bit per channel always fit");
+}
+
+/// Compress the color *indices* with GIF flavored LZW (variable code size starting at *bits* + 1, clear
+/// code, when the code table is full).
+fn lzw(indices: &[u8], bits: u32) -> Vec<u8> {
+    let clear = 1u16 << bits;
+    let end = clear + 1;
+
+    let mut w = BitWriter::new();
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = bits + 1;
+
+    w.write_bits(clear as u32, size);
+    let mut prefix = match indices.first() {
+        Some(i) => *i as u16,
+        None => {
+            w.write_bits(end as u32, size);
+            w.flush();
+            return w.out;
+        }
+    };
+
+    for &index in indices[1..].iter() {
+        if let Some(code) = codes.get(&(prefix, index)) {
+            prefix = *code;
+            continue;
+        }
+
+        w.write_bits(prefix as u32, size);
+        if next < 1 << MAX_CODE_SIZE {
+            // The decoder adds the code one step later, so the size grows after the code, which does
+            // not fit into the current size, was added.
+            // --------------------------------------------------------------------------------------
+            codes.insert((prefix, index), next);
+            if next == 1 << size {
+                size += 1;
+            }
+            next += 1;
+        } else {
+            w.write_bits(clear as u32, size);
+            codes.clear();
+            next = end + 1;
+            size = bits + 1;
+        }
+        prefix = index as u16;
+    }
+
+    w.write_bits(prefix as u32, size);
+    w.write_bits(end as u32, size);
+    w.flush();
+    w.out
+}
+
+/// Writes codes in GIF order (least significant bit first) into a byte buffer.
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Create new empty writer.
+    fn new() -> Self {
+        Self {
+            out: Vec::new(),
+            acc: 0,
+            bits: 0,
+        }
+    }
+
+    /// Write the lowest *count* bits of *value*, least significant bit first.
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.bits;
+        self.bits += count;
+        while self.bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Write the remaining bits (padded with zeros to a full byte).
+    fn flush(&mut self) {
+        if self.bits > 0 {
+            self.out.push(self.acc as u8);
+            self.acc = 0;
+            self.bits = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A decoded image of a GIF: its delay, the rect it covers (x, y, width and height), its color table,
+    /// the color index of every pixel, and the number of clear codes in its LZW stream.
+    struct Image {
+        delay: u16,
+        rect: (usize, usize, usize, usize),
+        table: Vec<[u8; 3]>,
+        indices: Vec<u8>,
+        clears: u32,
+    }
+
+    /// Read a little endian 16 bit number from *gif* at *i*.
+    fn u16_at(gif: &[u8], i: usize) -> usize {
+        u16::from_le_bytes([gif[i], gif[i + 1]]) as usize
+    }
+
+    /// Concatenate the data of the sub-blocks of *gif* starting at *i*. Returns the data and the index
+    /// after the terminating block.
+    fn sub_blocks(gif: &[u8], mut i: usize) -> (Vec<u8>, usize) {
+        let mut data = vec![];
+        while gif[i] != 0 {
+            data.extend_from_slice(&gif[i + 1..i + 1 + gif[i] as usize]);
+            i += 1 + gif[i] as usize;
+        }
+        (data, i + 1)
+    }
+
+    /// Decode the GIF *data* with code size *bits* + 1 (plain LZW, the reference for the encoder).
+    /// Returns the indices and the number of clear codes.
+    fn unlzw(data: &[u8], bits: u32) -> (Vec<u8>, u32) {
+        let (clear, end) = (1usize << bits, (1usize << bits) + 1);
+        let reset = || -> Vec<Vec<u8>> { (0..=end).map(|i| vec![i as u8]).collect() };
+        let (mut table, mut size, mut prev): (_, _, Option<Vec<u8>>) = (reset(), bits + 1, None);
+        let (mut out, mut clears, mut pos) = (vec![], 0, 0);
+        loop {
+            let code = (0..size).fold(0, |c, b| {
+                let bit = (data[(pos + b as usize) / 8] >> ((pos + b as usize) % 8)) & 1;
+                c | (bit as usize) << b
+            });
+            pos += size as usize;
+
+            if code == clear {
+                (table, size, prev) = (reset(), bits + 1, None);
+                clears += 1;
+                continue;
+            }
+            if code == end {
+                return (out, clears);
+            }
+
+            let entry = match (table.get(code), &prev) {
+                (Some(e), _) => e.clone(),
+                (None, Some(p)) => [&p[..], &p[..1]].concat(), //<- The code, which is added right now.
+                (None, None) => panic!("unknown code {}", code),
+            };
+            out.extend_from_slice(&entry);
+            if let Some(p) = prev {
+                if table.len() < 1 << MAX_CODE_SIZE {
+                    table.push([&p[..], &entry[..1]].concat());
+                }
+            }
+            if table.len() == 1 << size && size < MAX_CODE_SIZE {
+                size += 1;
+            }
+            prev = Some(entry);
+        }
+    }
+
+    /// Decode *gif*. Returns the width, the height and the images.
+    fn decode(gif: &[u8]) -> (usize, usize, Vec<Image>) {
+        assert_eq!(&gif[..6], SIGNATURE);
+        let (width, height) = (u16_at(gif, 6), u16_at(gif, 8));
+        let (mut images, mut delay, mut i) = (vec![], 0, 13);
+        loop {
+            match gif[i] {
+                0x21 => {
+                    if gif[i + 1] == 0xf9 {
+                        delay = u16_at(gif, i + 4) as u16;
+                    }
+                    i = sub_blocks(gif, i + 2).1;
+                }
+                0x2c => {
+                    let rect = (
+                        u16_at(gif, i + 1),
+                        u16_at(gif, i + 3),
+                        u16_at(gif, i + 5),
+                        u16_at(gif, i + 7),
+                    );
+                    let entries = 2 << (gif[i + 9] & 7);
+                    let table = gif[i + 10..i + 10 + entries * 3]
+                        .chunks(3)
+                        .map(|c| [c[0], c[1], c[2]])
+                        .collect();
+                    i += 10 + entries * 3;
+                    let (data, next) = sub_blocks(gif, i + 1);
+                    let (indices, clears) = unlzw(&data, gif[i] as u32);
+                    assert_eq!(indices.len(), rect.2 * rect.3);
+                    images.push(Image {
+                        delay,
+                        rect,
+                        table,
+                        indices,
+                        clears,
+                    });
+                    i = next;
+                }
+                0x3b => return (width, height, images),
+                b => panic!("unexpected block {:#x} at {}", b, i),
+            }
+        }
+    }
+
+    /// RGBA pixels of a frame with *width* and *height*, with the color *color* of every pixel.
+    fn frame<F: Fn(usize, usize) -> [u8; 3]>(width: usize, height: usize, color: F) -> Vec<u8> {
+        let mut rgba = vec![];
+        for y in 0..height {
+            for x in 0..width {
+                rgba.extend_from_slice(&color(x, y));
+                rgba.push(255);
+            }
+        }
+        rgba
+    }
+
+    /// Draw *image* onto the RGB pixels *screen* with *width*.
+    fn composite(screen: &mut [[u8; 3]], width: usize, image: &Image) {
+        let (x0, y0, w, _) = image.rect;
+        for (i, index) in image.indices.iter().enumerate() {
+            screen[(y0 + i / w) * width + x0 + i % w] = image.table[*index as usize];
+        }
+    }
+
+    /// The RGB colors of the RGBA pixels *rgba*.
+    fn rgb(rgba: &[u8]) -> Vec<[u8; 3]> {
+        rgba.chunks(4).map(|p| [p[0], p[1], p[2]]).collect()
+    }
+
+    #[test]
+    fn single_color() {
+        let mut gif = GifEncoder::new(5, 3);
+        gif.add_frame(&frame(5, 3, |_, _| [1, 2, 3]), 7);
+        let (width, height, images) = decode(&gif.finish());
+        assert_eq!((width, height, images.len()), (5, 3, 1));
+
+        let image = &images[0];
+        assert_eq!((image.delay, image.rect), (7, (0, 0, 5, 3)));
+        assert_eq!(image.table.len(), 4); //<- The smallest table.
+        assert_eq!(image.table[0], [1, 2, 3]);
+        assert!(image.indices.iter().all(|i| *i == 0));
+    }
+
+    #[test]
+    fn changed_sub_rects() {
+        let first = frame(8, 6, |x, y| [(x * 30) as u8, (y * 40) as u8, 0]);
+        let mut second = first.clone();
+        for (x, y) in [(2, 1), (4, 3)] {
+            second[(y * 8 + x) * 4..(y * 8 + x) * 4 + 3].copy_from_slice(&[255, 255, 255]);
+        }
+
+        let mut gif = GifEncoder::new(8, 6);
+        gif.add_frame(&first, 2);
+        gif.add_frame(&second, 3);
+        gif.add_frame(&second, 4); //<- Merged into the one before.
+        gif.add_frame(&second, 5);
+        gif.add_frame(&first, 6);
+        let (_, _, images) = decode(&gif.finish());
+
+        // Every image only covers the changed pixels, and the composited frames are the original ones.
+        // --------------------------------------------------------------------------------------------
+        let rects: Vec<_> = images.iter().map(|i| (i.rect, i.delay)).collect();
+        assert_eq!(
+            rects,
+            [((0, 0, 8, 6), 2), ((2, 1, 3, 3), 12), ((2, 1, 3, 3), 6)]
+        );
+        let mut screen = vec![[0; 3]; 8 * 6];
+        for (image, expected) in images.iter().zip([&first, &second, &first].iter()) {
+            composite(&mut screen, 8, image);
+            assert_eq!(screen, rgb(expected));
+        }
+    }
+
+    #[test]
+    fn quantized_colors() {
+        // 512 colors, which differ in the lowest bit of blue, do not fit into a table. Dropping the lowest
+        // bit of every channel leaves 256 of them.
+        // ------------------------------------------------------------------------------------------------
+        let color = |x: usize, y: usize| {
+            let i = y * 32 + x;
+            [
+                ((i >> 1) as u8 & 15) << 4,
+                ((i >> 5) as u8 & 15) << 4,
+                i as u8 & 1,
+            ]
+        };
+        let mut gif = GifEncoder::new(32, 16);
+        gif.add_frame(&frame(32, 16, color), 1);
+        let (_, _, images) = decode(&gif.finish());
+
+        let image = &images[0];
+        assert_eq!(image.table.len(), 256);
+        for (i, index) in image.indices.iter().enumerate() {
+            let c = color(i % 32, i / 32);
+            assert_eq!(image.table[*index as usize], [c[0], c[1], 0]);
+        }
+        let mut used: Vec<u8> = image.indices.clone();
+        used.sort_unstable();
+        used.dedup();
+        assert_eq!(used.len(), 256);
+    }
+
+    #[test]
+    fn code_table_reset() {
+        // Noise with 200 colors fills the code table of 4096 codes a few times over.
+        // --------------------------------------------------------------------------
+        let mut state = 0x1234_5678u32;
+        let mut noise = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state % 200) as u8
+        };
+        let pixels: Vec<u8> = (0..160 * 120).map(|_| noise()).collect();
+        let rgba = frame(160, 120, |x, y| [pixels[y * 160 + x], 0, 0]);
+
+        let mut gif = GifEncoder::new(160, 120);
+        gif.add_frame(&rgba, 1);
+        let (_, _, images) = decode(&gif.finish());
+
+        let image = &images[0];
+        assert!(image.clears > 2, "{} clear codes", image.clears);
+        let decoded: Vec<u8> = image
+            .indices
+            .iter()
+            .map(|i| image.table[*i as usize][0])
+            .collect();
+        assert_eq!(decoded, pixels);
+    }
+}
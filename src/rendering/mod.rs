@@ -0,0 +1,290 @@
+//! Renderer module. Provides infrastructure to render the game scene.
+//!
+//! Everything is drawn through the **Backend** trait, so **Renderable** types do not depend on a
+//! concrete output. The default backend is the **PixelScreen**, which uses the Canvas2D drawing API, and
+//! operates directly on the 2d drawing context from the DOM API (rect by rect, or through an image of the
+//! virtual grid, depending on its **RenderMode**). The **Framebuffer** is a headless software backend,
+//! which draws into memory, and can write PNG screenshots. Its frames can also be encoded into animated
+//! GIFs with the **GifEncoder**.
+//!
+//! Drawing is done in virtual coordinates (the 80x60 grid of the game). Every backend owns a **Viewport**,
+//! which transforms them into its actual size (letterboxed to keep the aspect ratio).
+//!
+//! Text is drawn with the built-in bitmap font (see **draw_text()** for the layout of aligned, multi-line
+//! text), so it looks the same everywhere.
+//!
+//! The renderer is based on a *Pipeline State Object* design. You create a PSO for different drawing
+//! styles (contains stuff like draw color, font etc...), and bind it to the drawing context before
+//! drawing the actual scene objects. This is done to simplify usage of the statefull drawing API
+//! provides by the Canvas 2d drawing context.
+//!
+//! PSOs do not contain actual colors, but color roles. They are resolved with the active **Palette** (the
+//! theme) on binding, so the theme can be switched at runtime.
+//!
+//! PSOs are not bound by hand. Scene objects submit their drawables with the assigned PSO and a layer to
+//! a **Scene** every frame, which sorts them, and binds every PSO only once per run of drawables using it.
+//!
+//! Scenes can be drawn with dirty rects. Only the regions, which changed since the last frame, are redrawn.
+//! Renderables report their **Footprint** for that.
+//!
+//! Backends are free to skip redundant state changes. The **PixelScreen** tracks the bound state, only
+//! sets properties on the drawing context, which actually changed, and counts the issued and skipped
+//! bindings in **BindCounters**. It also batches rects, and counts its draw calls in **DrawCounters**.
+
+use wasm_bindgen::JsValue;
+
+mod canvas;
+mod damage;
+mod font;
+mod framebuffer;
+mod gif;
+mod palette;
+mod png;
+#[cfg(test)]
+mod recording;
+mod scene;
+mod viewport;
+
+pub use self::canvas::{PixelScreen, RenderMode};
+pub use self::damage::{Damage, Footprint};
+pub use self::font::{draw_line, draw_text, measure_text, Align};
+pub use self::framebuffer::Framebuffer;
+pub use self::gif::GifEncoder;
+pub use self::palette::{Palette, Role, THEMES};
+#[cfg(test)]
+pub use self::recording::{Call, RecordingBackend};
+pub use self::scene::{Layer, Scene, SceneNode};
+pub use self::viewport::{Viewport, HEIGHT, WIDTH};
+
+/// Rendering backend. A backend is the output, into which the scene is drawn (e.g. the Canvas2D drawing
+/// context of the browser). It provides clearing, rect and text filling, and state binding for PSOs.
+///
+/// All positions and sizes are virtual coordinates. The backend transforms them with its **Viewport**.
+///
+/// Like the Canvas 2d drawing API, backends are stateful. The fill color, font and alpha set by a PSO
+/// binding stay active for all following drawing calls, until they are set again.
+pub trait Backend {
+    /// Clear the whole screen with the *background* color.
+    fn clear(&mut self, background: CssColor);
+
+    /// Clear only the *regions* (cells of the virtual grid at x, y with width and height) with the
+    /// *background* color, and limit all following drawing to them (until the next clear). Used to draw
+    /// dirty rects. Returns false, when the backend can not do this, and cleared the whole screen instead.
+    fn clear_regions(&mut self, background: CssColor, _regions: &[(u32, u32, u32, u32)]) -> bool {
+        self.clear(background);
+        false
+    }
+
+    /// Draw rectangle at *pos* with *width* and *height* filled with the current fill color.
+    fn draw_rect(&mut self, pos: Pos, width: f64, height: f64);
+
+    /// Draw a single line of *text* at *pos* (left end of the baseline) with the current fill color and
+    /// font size. Backends draw it with the bitmap font (**draw_line()**).
+    fn draw_text(&mut self, text: &str, pos: Pos);
+
+    /// Move all following drawing by *offset* virtual pixels (the camera offset, e.g. for screen shake).
+    fn set_camera(&mut self, offset: Pos);
+
+    /// Set the fill color for all following drawing.
+    fn set_fill_color(&mut self, color: CssColor);
+
+    /// Set the font for all following text drawing.
+    fn set_font(&mut self, font: CssFont);
+
+    /// Size of the current font in px (10px, when no font was set yet, like the default of the Canvas2D
+    /// context). Text is laid out and drawn in this size, so it only comes from the PSO of the text.
+    fn font_size(&self) -> u32;
+
+    /// Set the global opacity for all following drawing (0 is fully transparent, 255 is opaque).
+    fn set_alpha(&mut self, alpha: u8);
+
+    /// Push the current drawing state (everything set by PSOs) onto a state stack. Use it together with
+    /// **restore()** to limit the effect of PSO bindings to a section.
+    fn save(&mut self);
+
+    /// Pop the drawing state, which was pushed by the last **save()**.
+    fn restore(&mut self);
+}
+
+/// Counters of the state bindings (fill color, font and alpha) a backend received. Bindings of the state,
+/// which is already bound, are skipped.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BindCounters {
+    /// Bindings, which changed the state of the drawing context.
+    pub issued: u32,
+    /// Redundant bindings, which were skipped.
+    pub skipped: u32,
+}
+
+/// Counters of the drawing a backend did in a frame.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct DrawCounters {
+    /// Draw calls issued (e.g. fills of rects, paths or images).
+    pub calls: u32,
+    /// Rects drawn (a draw call may draw many of them).
+    pub rects: u32,
+}
+
+/// Css color type. Used as a type, which can be supplied to DOM API by calling **into()**.
+///
+/// The conversion allocates a string buffer and creates a JsValue, so backends should cache the
+/// converted values (the **PixelScreen** interns them).
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CssColor {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl CssColor {
+    /// Create new color by rgb args.
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// The red, green and blue components.
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        (self.r, self.g, self.b)
+    }
+}
+
+impl Into<JsValue> for CssColor {
+    /// Convert color into a usable CSS property string (JsValue).
+    fn into(self) -> JsValue {
+        JsValue::from(format!("rgb({},{},{})", self.r, self.g, self.b))
+    }
+}
+
+/// Css font helper. Use it to create a new font, and call **into()** to convert it into a String
+/// Css property string. Text is drawn with the bitmap font, so backends only use the *size*.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct CssFont {
+    size: u32,
+    family: &'static str,
+}
+
+impl CssFont {
+    /// New font by *family* and *size* in px.
+    pub const fn new(size: u32, family: &'static str) -> Self {
+        Self { size, family }
+    }
+
+    /// monospace font helper with *size* in px.
+    pub const fn monospace(size: u32) -> Self {
+        Self::new(size, "monospace")
+    }
+
+    /// Size in px.
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+}
+
+impl Into<String> for CssFont {
+    /// Turn the font into a usable css property string.
+    fn into(self) -> String {
+        format!("{}px {}", self.size, self.family)
+    }
+}
+
+/// Position on screen type in virtual pixels. Uses floats.
+#[derive(Copy, Clone)]
+pub struct Pos {
+    x: f64,
+    y: f64,
+}
+
+impl Pos {
+    /// Create a new position.
+    pub fn new(x: f64, y: f64) -> Self {
+        Self { x, y }
+    }
+
+    /// Horizontal position.
+    pub fn x(&self) -> f64 {
+        self.x
+    }
+
+    /// Vertical position.
+    pub fn y(&self) -> f64 {
+        self.y
+    }
+}
+
+/// Pipeline state object is used to bind to a **Backend**. On binding it will set all the required state
+/// of the backend (e.g. the canvas 2d rendering context).
+///
+/// The *fill_color* is the role of the color in the palette, which is bound together with the PSO.
+///
+/// The *alpha* is the global opacity of everything drawn (0 is fully transparent, 255 is opaque). Together
+/// with the *fill_color* it allows translucent and tinted drawing. PSOs without alpha are opaque.
+#[derive(PartialEq, Default, Hash)]
+pub struct Pso {
+    pub fill_color: Option<Role>,
+    pub font: Option<CssFont>,
+    pub alpha: Option<u8>,
+}
+
+impl Pso {
+    /// Bind to a **Backend** with the colors of *palette*.
+    /// Will only bind color and font, when they are `Some(_)`. Others are left as they were before. The alpha
+    /// is always bound, so translucency does not leak into following drawing.
+    pub fn bind(&self, backend: &mut dyn Backend, palette: &Palette) {
+        if let Some(c) = self.fill_color {
+            backend.set_fill_color(palette.color(c));
+        }
+
+        if let Some(f) = self.font {
+            backend.set_font(f);
+        }
+
+        backend.set_alpha(self.alpha.unwrap_or(255));
+    }
+}
+
+/// The point of a frame between the last two game ticks, at which moving objects are drawn. *tick* is
+/// the fraction of the current game tick, which passed (from 0, right after a tick, to 1), and *enemy*
+/// the one of the current enemy interval. With *snap*, the positions are rounded to the grid.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Interpolation {
+    pub tick: f32,
+    pub enemy: f32,
+    pub snap: bool,
+}
+
+impl Default for Interpolation {
+    /// At the last tick (everything is drawn at its current position).
+    fn default() -> Self {
+        Self {
+            tick: 1.0,
+            enemy: 1.0,
+            snap: false,
+        }
+    }
+}
+
+/// Trait for renderable types.
+pub trait Renderable {
+    /// This will draw the current object to the given **Backend**.
+    ///
+    /// Note: Drawing uses the currently bound PSO, and must not bind PSOs. The PSO is assigned, when the object
+    /// is submitted to a **Scene**.
+    fn draw(&self, backend: &mut dyn Backend);
+
+    /// The cells covered by the object, and the key of its look (see **Footprint**). Used for dirty rect
+    /// rendering. `None`, when it is not known (the whole screen is drawn, while the object is visible).
+    fn footprint(&self) -> Option<Footprint> {
+        None
+    }
+
+    /// Draw the object at the point *lerp* between the last two game ticks. Objects, which move with
+    /// the ticks, are placed between their last two positions. All others are drawn like by **draw()**.
+    fn draw_at(&self, backend: &mut dyn Backend, _lerp: &Interpolation) {
+        self.draw(backend);
+    }
+
+    /// The footprint of the object drawn by **draw_at()** with *lerp*.
+    fn footprint_at(&self, _lerp: &Interpolation) -> Option<Footprint> {
+        self.footprint()
+    }
+}
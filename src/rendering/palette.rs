@@ -0,0 +1,151 @@
+//! Palette module. The colors of the game by their role, and the built-in themes.
+//!
+//! PSOs do not contain colors, but the **Role** of their fill color. The color is looked up in the active
+//! **Palette**, when the PSO is bound, so switching the theme at runtime recolors everything with the
+//! next frame.
+
+use super::CssColor;
+
+/// Roles of the colors in a palette.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Background of the game area (the screen is cleared with it).
+    Background,
+    /// The player ship.
+    Player,
+    /// The enemies.
+    Enemy,
+    /// Projectiles.
+    Shot,
+    /// Explosions.
+    Explosion,
+    /// Regular text of the HUD and screens.
+    Ui,
+    /// Less important text (e.g. notifications).
+    UiDim,
+    /// Highlighted text and the ghost of the personal best run.
+    Accent,
+    /// Bad news (e.g. negative score, game over).
+    Warning,
+    /// Full-screen flashes.
+    Flash,
+    /// Near stars of the background.
+    Star,
+    /// Far stars of the background.
+    StarDim,
+}
+
+/// A palette (theme). Contains its name, and the color of every role.
+pub struct Palette {
+    pub name: &'static str,
+    pub background: CssColor,
+    pub player: CssColor,
+    pub enemy: CssColor,
+    pub shot: CssColor,
+    pub explosion: CssColor,
+    pub ui: CssColor,
+    pub ui_dim: CssColor,
+    pub accent: CssColor,
+    pub warning: CssColor,
+    pub flash: CssColor,
+    pub star: CssColor,
+    pub star_dim: CssColor,
+}
+
+/// The built-in themes. The first one is the default.
+pub static THEMES: [Palette; 4] = [
+    // Black on white, like the original game.
+    Palette {
+        name: "default",
+        background: CssColor::new(255, 255, 255),
+        player: CssColor::new(0, 0, 0),
+        enemy: CssColor::new(0, 0, 0),
+        shot: CssColor::new(255, 0, 0),
+        explosion: CssColor::new(128, 128, 128),
+        ui: CssColor::new(0, 0, 0),
+        ui_dim: CssColor::new(100, 100, 100),
+        accent: CssColor::new(0, 50, 200),
+        warning: CssColor::new(200, 50, 0),
+        flash: CssColor::new(255, 190, 0),
+        star: CssColor::new(170, 170, 190),
+        star_dim: CssColor::new(215, 215, 225),
+    },
+    // Green phosphor on black, like the arcade monitors.
+    Palette {
+        name: "classic",
+        background: CssColor::new(0, 0, 0),
+        player: CssColor::new(51, 255, 51),
+        enemy: CssColor::new(51, 255, 51),
+        shot: CssColor::new(200, 255, 200),
+        explosion: CssColor::new(30, 140, 30),
+        ui: CssColor::new(51, 255, 51),
+        ui_dim: CssColor::new(30, 140, 30),
+        accent: CssColor::new(150, 255, 150),
+        warning: CssColor::new(255, 176, 0),
+        flash: CssColor::new(200, 255, 200),
+        star: CssColor::new(30, 140, 30),
+        star_dim: CssColor::new(20, 80, 20),
+    },
+    // Pure colors on black, for the best readability.
+    Palette {
+        name: "high-contrast",
+        background: CssColor::new(0, 0, 0),
+        player: CssColor::new(255, 255, 255),
+        enemy: CssColor::new(255, 255, 255),
+        shot: CssColor::new(255, 255, 0),
+        explosion: CssColor::new(255, 0, 255),
+        ui: CssColor::new(255, 255, 255),
+        ui_dim: CssColor::new(255, 255, 255),
+        accent: CssColor::new(0, 255, 255),
+        warning: CssColor::new(255, 255, 0),
+        flash: CssColor::new(255, 255, 255),
+        star: CssColor::new(140, 140, 140),
+        star_dim: CssColor::new(80, 80, 80),
+    },
+    // Okabe-Ito colors, which can be told apart with all common kinds of color blindness.
+    Palette {
+        name: "colorblind",
+        background: CssColor::new(255, 255, 255),
+        player: CssColor::new(0, 114, 178),
+        enemy: CssColor::new(0, 0, 0),
+        shot: CssColor::new(213, 94, 0),
+        explosion: CssColor::new(230, 159, 0),
+        ui: CssColor::new(0, 0, 0),
+        ui_dim: CssColor::new(100, 100, 100),
+        accent: CssColor::new(86, 180, 233),
+        warning: CssColor::new(213, 94, 0),
+        flash: CssColor::new(240, 228, 66),
+        star: CssColor::new(170, 170, 170),
+        star_dim: CssColor::new(215, 215, 215),
+    },
+];
+
+impl Palette {
+    /// Get the built-in theme called *name*.
+    pub fn find(name: &str) -> Option<&'static Palette> {
+        THEMES.iter().find(|p| p.name == name)
+    }
+
+    /// The default theme.
+    pub fn default_theme() -> &'static Palette {
+        &THEMES[0]
+    }
+
+    /// The color of *role*.
+    pub fn color(&self, role: Role) -> CssColor {
+        match role {
+            Role::Background => self.background,
+            Role::Player => self.player,
+            Role::Enemy => self.enemy,
+            Role::Shot => self.shot,
+            Role::Explosion => self.explosion,
+            Role::Ui => self.ui,
+            Role::UiDim => self.ui_dim,
+            Role::Accent => self.accent,
+            Role::Warning => self.warning,
+            Role::Flash => self.flash,
+            Role::Star => self.star,
+            Role::StarDim => self.star_dim,
+        }
+    }
+}
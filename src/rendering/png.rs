@@ -0,0 +1,253 @@
+//! PNG encoder module. A small dependency free encoder for 8 bit RGBA images, used to write screenshots
+//! of the **Framebuffer**.
+//!
+//! The image data is compressed with deflate using the fixed Huffman codes and a greedy LZ77 matcher.
+//! That is far from the best compression possible, but game frames consist mostly of large single
+//! colored areas, which compress very well with it anyway.
+
+/// The 8 byte PNG file signature.
+const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+/// Size of the deflate sliding window (largest match distance).
+const WINDOW: usize = 32768;
+
+/// Shortest and longest match deflate can encode.
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+/// How many earlier positions with the same hash are tried to find a match.
+const MAX_CHAIN: usize = 64;
+
+/// Base lengths and extra bits of the length codes 257 to 285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// Base distances and extra bits of the distance codes 0 to 29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// Encode an image with *width* and *height* from *rgba* pixel data (4 bytes per pixel, row by row) as
+/// PNG file.
+pub fn encode(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    assert_eq!(rgba.len(), width as usize * height as usize * 4);
+
+    // Every scanline is prefixed with its filter type (0 = none).
+    // ------------------------------------------------------------
+    let stride = width as usize * 4;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in rgba.chunks(stride.max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[8, 6, 0, 0, 0]); //<- 8 bit depth, RGBA, no interlacing.
+
+    let mut png = Vec::new();
+    png.extend_from_slice(SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Append a chunk with *kind* and *data* (length, type, data and CRC) to *png*.
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// CRC-32 (as used by PNG) of *data*.
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |mut crc, b| {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+        crc
+    })
+}
+
+/// Adler-32 checksum (as used by zlib) of *data*.
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), x| {
+        let a = (a + *x as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    b << 16 | a
+}
+
+/// Compress *data* into a zlib stream (header, a single deflate block, and checksum).
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter::new();
+    w.out.extend_from_slice(&[0x78, 0x01]); //<- Deflate with 32K window, no dictionary.
+
+    w.write_bits(1, 1); //<- Final block.
+    w.write_bits(1, 2); //<- Fixed Huffman codes.
+
+    // Greedy LZ77. Positions with the same hash of their next 3 bytes are chained together, so earlier
+    // occurences can be searched for the longest match.
+    // --------------------------------------------------------------------------------------------------
+    let hash = |i: usize| {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize) & 0x7fff
+    };
+    let mut head = vec![usize::MAX; 0x8000];
+    let mut prev = vec![usize::MAX; data.len()];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + MIN_MATCH <= data.len() {
+            let h = hash(i);
+            prev[i] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let mut best_len = 0;
+        let mut best_dist = 0;
+
+        if i + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(i)];
+            let mut chain = 0;
+
+            while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN {
+                let len = data[candidate..]
+                    .iter()
+                    .zip(data[i..i + max_len].iter())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    best_len = len;
+                    best_dist = i - candidate;
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+                chain += 1;
+            }
+        }
+
+        if best_len >= MIN_MATCH {
+            w.write_length(best_len);
+            w.write_distance(best_dist);
+            for j in i..i + best_len {
+                insert(j, &mut head, &mut prev);
+            }
+            i += best_len;
+        } else {
+            w.write_literal(data[i] as u16);
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+
+    w.write_literal(256); //<- End of block.
+    w.flush();
+
+    let mut out = w.out;
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Writes bits in deflate order (least significant bit first) into a byte buffer.
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Create new empty writer.
+    fn new() -> Self {
+        Self {
+            out: Vec::new(),
+            acc: 0,
+            bits: 0,
+        }
+    }
+
+    /// Write the lowest *count* bits of *value*, least significant bit first.
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.acc |= value << self.bits;
+        self.bits += count;
+        while self.bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Write a Huffman *code* of *len* bits. Huffman codes are stored most significant bit first.
+    fn write_code(&mut self, code: u32, len: u32) {
+        let reversed = code.reverse_bits() >> (32 - len);
+        self.write_bits(reversed, len);
+    }
+
+    /// Write the fixed Huffman code of the literal / length *symbol*.
+    fn write_literal(&mut self, symbol: u16) {
+        let s = symbol as u32;
+        match symbol {
+            0..=143 => self.write_code(0x30 + s, 8),
+            144..=255 => self.write_code(0x190 + s - 144, 9),
+            256..=279 => self.write_code(s - 256, 7),
+            _ => self.write_code(0xc0 + s - 280, 8),
+        }
+    }
+
+    /// Write a match length (length code and extra bits).
+    fn write_length(&mut self, len: usize) {
+        let code = LENGTH_BASE
+            .iter()
+            .rposition(|b| *b as usize <= len)
+            .unwrap();
+        self.write_literal(257 + code as u16);
+        self.write_bits(
+            (len - LENGTH_BASE[code] as usize) as u32,
+            LENGTH_EXTRA[code] as u32,
+        );
+    }
+
+    /// Write a match distance (5 bit distance code and extra bits).
+    fn write_distance(&mut self, dist: usize) {
+        let code = DIST_BASE.iter().rposition(|b| *b as usize <= dist).unwrap();
+        self.write_code(code as u32, 5);
+        self.write_bits(
+            (dist - DIST_BASE[code] as usize) as u32,
+            DIST_EXTRA[code] as u32,
+        );
+    }
+
+    /// Write the remaining bits (padded with zeros to a full byte).
+    fn flush(&mut self) {
+        if self.bits > 0 {
+            self.out.push(self.acc as u8);
+            self.acc = 0;
+            self.bits = 0;
+        }
+    }
+}
@@ -0,0 +1,96 @@
+//! Recording backend module (tests only). A mock **Backend**, which draws nothing, and records every
+//! call it receives, so tests can assert the exact sequence of drawing and state changes.
+
+use super::{Backend, CssColor, CssFont, Pos};
+
+/// A call received by the **RecordingBackend**. Colors are recorded as rgb, fonts as size, and positions
+/// as (x, y).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Call {
+    Clear((u8, u8, u8)),
+    ClearRegions((u8, u8, u8), Vec<(u32, u32, u32, u32)>),
+    Rect((f64, f64), f64, f64),
+    Text(String, (f64, f64)),
+    Camera((f64, f64)),
+    FillColor((u8, u8, u8)),
+    Font(u32),
+    Alpha(u8),
+    Save,
+    Restore,
+}
+
+/// Mock backend, which records all *calls*. Partial clears (dirty rects) are supported, when *partial*
+/// is set. The font size is kept (for the layout of text), but not saved and restored.
+#[derive(Default)]
+pub struct RecordingBackend {
+    pub calls: Vec<Call>,
+    pub partial: bool,
+    font: Option<CssFont>,
+}
+
+impl RecordingBackend {
+    /// Create new backend without recorded calls.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the recorded calls (the backend starts recording from scratch).
+    pub fn take(&mut self) -> Vec<Call> {
+        std::mem::take(&mut self.calls)
+    }
+}
+
+impl Backend for RecordingBackend {
+    fn clear(&mut self, background: CssColor) {
+        self.calls.push(Call::Clear(background.rgb()));
+    }
+
+    fn clear_regions(&mut self, background: CssColor, regions: &[(u32, u32, u32, u32)]) -> bool {
+        if !self.partial {
+            self.clear(background);
+            return false;
+        }
+        self.calls
+            .push(Call::ClearRegions(background.rgb(), regions.to_vec()));
+        true
+    }
+
+    fn draw_rect(&mut self, pos: Pos, width: f64, height: f64) {
+        self.calls
+            .push(Call::Rect((pos.x(), pos.y()), width, height));
+    }
+
+    fn draw_text(&mut self, text: &str, pos: Pos) {
+        self.calls
+            .push(Call::Text(text.to_owned(), (pos.x(), pos.y())));
+    }
+
+    fn set_camera(&mut self, offset: Pos) {
+        self.calls.push(Call::Camera((offset.x(), offset.y())));
+    }
+
+    fn set_fill_color(&mut self, color: CssColor) {
+        self.calls.push(Call::FillColor(color.rgb()));
+    }
+
+    fn set_font(&mut self, font: CssFont) {
+        self.calls.push(Call::Font(font.size()));
+        self.font = Some(font);
+    }
+
+    fn font_size(&self) -> u32 {
+        self.font.map_or(10, |f| f.size())
+    }
+
+    fn set_alpha(&mut self, alpha: u8) {
+        self.calls.push(Call::Alpha(alpha));
+    }
+
+    fn save(&mut self) {
+        self.calls.push(Call::Save);
+    }
+
+    fn restore(&mut self) {
+        self.calls.push(Call::Restore);
+    }
+}
@@ -0,0 +1,352 @@
+//! Scene module. The scene API, which takes care of PSO binding and drawing order.
+//!
+//! Every frame, the scene objects submit their drawables together with the PSO to draw them with, and
+//! the **Layer** they belong to. When the **Scene** is drawn, the items are sorted by layer first, and
+//! then grouped by PSO (equal PSOs are drawn one after another). A PSO is only bound, when it differs
+//! from the previous one, so the number of state changes is as small as possible.
+//!
+//! The order of items with the same PSO in the same layer is kept. Items with different PSOs in the same
+//! layer may be reordered, so drawables, which must be drawn on top of others, need a higher layer.
+//!
+//! A scene only borrows its items for one frame, and its buffers live in a frame arena (a **Bump**
+//! allocator). Frontends keep the arena between frames, and reset it before they create the scene of the
+//! next frame, so the memory of the items is allocated only once.
+//!
+//! The scene also has a camera offset (e.g. for screen shake). It only moves the layers of the game area
+//! (background, world and effects), so the HUD and screens on top stay in place.
+//!
+//! A scene can also be drawn partially (dirty rects): only the regions, which changed since the last
+//! frame, are redrawn (see the **damage** module).
+
+use super::{Backend, CssColor, Damage, Footprint, Interpolation, Palette, Pos, Pso, Renderable};
+use bumpalo::collections::Vec as BumpVec;
+use bumpalo::Bump;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Drawing layers, from the bottom to the top.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Layer {
+    /// The background behind the world (the starfield).
+    Background,
+    /// Entities of the world (projectiles, enemies and the player).
+    World,
+    /// Effects on top of the world (e.g. explosions).
+    Effects,
+    /// The ghost of the personal best run.
+    Ghost,
+    /// Full-screen flashes on top of the game area.
+    Flash,
+    /// Head-up display (score, lives, FPS, notifications).
+    Hud,
+    /// Screens on top of everything else (e.g. game over).
+    Overlay,
+    /// The text of the debug overlay (only with the `debug-overlay` feature).
+    #[cfg(feature = "debug-overlay")]
+    Debug,
+}
+
+impl Layer {
+    /// Checks, if the layer is moved by the camera offset (everything in the game area, which is not
+    /// drawn over all of it).
+    fn follows_camera(self) -> bool {
+        matches!(
+            self,
+            Layer::Background | Layer::World | Layer::Effects | Layer::Ghost
+        )
+    }
+}
+
+/// An item of the scene. The *group* is the index of the first submitted PSO equal to *pso*. The
+/// *footprint* of the drawable is only known while drawing dirty rects.
+struct Item<'a> {
+    layer: Layer,
+    group: usize,
+    pso: &'a Pso,
+    drawable: &'a dyn Renderable,
+    footprint: Option<Footprint>,
+}
+
+/// A scene object, which consists of drawables (e.g. an entity, or a collection of entities).
+pub trait SceneNode {
+    /// Submit all drawables of this object, each with its PSO and layer, to *scene*.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>);
+}
+
+/// The scene of a frame. Collects the submitted items, and draws them in the sorted order with the
+/// *camera* offset, and moving items at the *interpolation* between the last two game ticks.
+pub struct Scene<'a> {
+    items: BumpVec<'a, Item<'a>>,
+    psos: BumpVec<'a, &'a Pso>,
+    camera: Pos,
+    interpolation: Interpolation,
+}
+
+impl<'a> Scene<'a> {
+    /// Create new empty scene, whose buffers are allocated in the frame *arena*.
+    pub fn new(arena: &'a Bump) -> Self {
+        Self {
+            items: BumpVec::with_capacity_in(128, arena),
+            psos: BumpVec::with_capacity_in(16, arena),
+            camera: Pos::new(0.0, 0.0),
+            interpolation: Interpolation::default(),
+        }
+    }
+
+    /// Move the layers of the game area by *offset* virtual pixels.
+    pub fn set_camera(&mut self, offset: Pos) {
+        self.camera = offset;
+    }
+
+    /// Draw moving items at *interpolation* between the last two game ticks (at the last tick, unless it
+    /// is set).
+    pub fn set_interpolation(&mut self, interpolation: Interpolation) {
+        self.interpolation = interpolation;
+    }
+
+    /// Submit *drawable*, which is drawn with *pso* on *layer*.
+    pub fn submit(&mut self, layer: Layer, pso: &'a Pso, drawable: &'a dyn Renderable) {
+        let group = match self.psos.iter().position(|p| *p == pso) {
+            Some(g) => g,
+            None => {
+                self.psos.push(pso);
+                self.psos.len() - 1
+            }
+        };
+
+        self.items.push(Item {
+            layer,
+            group,
+            pso,
+            drawable,
+            footprint: None,
+        });
+    }
+
+    /// Submit all drawables of every node in *nodes*.
+    pub fn add(&mut self, nodes: &[&'a dyn SceneNode]) {
+        for node in nodes {
+            node.submit(self);
+        }
+    }
+
+    /// Draw all items into *backend* with the colors of *palette*. Sorts them by layer and PSO, and binds
+    /// each PSO only once per run of items, which use it. The camera offset is reset afterwards.
+    pub fn draw(&mut self, backend: &mut dyn Backend, palette: &Palette) {
+        self.items.sort_by_key(|i| (i.layer, i.group)); //<- Stable, keeps the submission order.
+        self.draw_sorted(backend, palette, |_| true);
+    }
+
+    /// Draw the scene like **draw()**, but only the regions of the screen, which changed since the last
+    /// frame (tracked by *damage*). Clears them with the *background* color first (or the whole screen,
+    /// when everything is drawn).
+    pub fn draw_damaged(
+        &mut self,
+        backend: &mut dyn Backend,
+        palette: &Palette,
+        background: CssColor,
+        damage: &mut Damage,
+    ) {
+        self.items.sort_by_key(|i| (i.layer, i.group));
+        for item in self.items.iter_mut() {
+            item.footprint = item.drawable.footprint_at(&self.interpolation);
+        }
+
+        let moved = self.camera.x() != 0.0 || self.camera.y() != 0.0;
+        let footprints = self.items.iter().map(|i| {
+            let mut hasher = DefaultHasher::new();
+            (i.layer, i.pso).hash(&mut hasher);
+            (hasher.finish(), i.footprint)
+        });
+
+        match damage.update(footprints, moved, palette) {
+            Some(regions) if backend.clear_regions(background, regions) => {
+                self.draw_sorted(backend, palette, |i| {
+                    regions
+                        .iter()
+                        .any(|r| i.footprint.is_some_and(|f| f.touches(*r)))
+                });
+            }
+            Some(_) => self.draw_sorted(backend, palette, |_| true), //<- Cleared completely.
+            None => {
+                backend.clear(background);
+                self.draw_sorted(backend, palette, |_| true);
+            }
+        }
+    }
+
+    /// Draw the sorted items, for which *filter* returns true.
+    fn draw_sorted<F>(&self, backend: &mut dyn Backend, palette: &Palette, filter: F)
+    where
+        F: Fn(&Item) -> bool,
+    {
+        let origin = Pos::new(0.0, 0.0);
+        let mut bound = None;
+        let mut moved = None;
+        for item in self.items.iter().filter(|i| filter(i)) {
+            let follows = item.layer.follows_camera();
+            if moved != Some(follows) {
+                backend.set_camera(if follows { self.camera } else { origin });
+                moved = Some(follows);
+            }
+            if bound != Some(item.group) {
+                item.pso.bind(backend, palette);
+                bound = Some(item.group);
+            }
+            item.drawable.draw_at(backend, &self.interpolation);
+        }
+        backend.set_camera(origin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Call, RecordingBackend, Role};
+    use std::cell::Cell;
+
+    /// A single virtual pixel at *x*, 0.
+    struct Dot(f64);
+
+    impl Renderable for Dot {
+        fn draw(&self, backend: &mut dyn Backend) {
+            backend.draw_rect(Pos::new(self.0, 0.0), 1.0, 1.0);
+        }
+    }
+
+    #[test]
+    fn arena_reused() {
+        let pso = Pso {
+            fill_color: Some(Role::Shot),
+            ..Default::default()
+        };
+        let mut arena = Bump::new();
+        let first = Dot(1.0);
+        {
+            let mut scene = Scene::new(&arena);
+            scene.submit(Layer::World, &pso, &first);
+            scene.set_camera(Pos::new(1.0, 1.0));
+        }
+        let allocated = arena.allocated_bytes();
+
+        // The next frame fits into the memory of the last one, and only draws its own items, without the
+        // old camera offset.
+        // ----------------------------------------------------------------------------------------------
+        arena.reset();
+        let second = Dot(2.0);
+        let mut scene = Scene::new(&arena);
+        scene.submit(Layer::World, &pso, &second);
+        assert_eq!(arena.allocated_bytes(), allocated);
+        let mut backend = RecordingBackend::new();
+        scene.draw(&mut backend, Palette::default_theme());
+        let rects: Vec<&Call> = backend
+            .calls
+            .iter()
+            .filter(|c| matches!(c, Call::Rect(..) | Call::Camera(..)))
+            .collect();
+        assert_eq!(
+            rects,
+            [
+                &Call::Camera((0.0, 0.0)),
+                &Call::Rect((2.0, 0.0), 1.0, 1.0),
+                &Call::Camera((0.0, 0.0))
+            ]
+        );
+    }
+
+    #[test]
+    fn layers_following_camera() {
+        let pso = Pso {
+            fill_color: Some(Role::Shot),
+            ..Default::default()
+        };
+        let dots: Vec<Dot> = (0..6).map(|x| Dot(x as f64)).collect();
+        let arena = Bump::new();
+        let mut scene = Scene::new(&arena);
+        let layers = [
+            Layer::Background,
+            Layer::World,
+            Layer::Effects,
+            Layer::Ghost,
+            Layer::Flash,
+            Layer::Hud,
+        ];
+        for (layer, dot) in layers.iter().zip(dots.iter()) {
+            scene.submit(*layer, &pso, dot);
+        }
+        scene.set_camera(Pos::new(1.0, 2.0));
+
+        // Everything up to the ghost is moved, the flash and the HUD stay in place.
+        // -------------------------------------------------------------------------
+        let mut backend = RecordingBackend::new();
+        scene.draw(&mut backend, Palette::default_theme());
+        let calls: Vec<&Call> = backend
+            .calls
+            .iter()
+            .filter(|c| matches!(c, Call::Rect(..) | Call::Camera(..)))
+            .collect();
+        assert_eq!(
+            calls,
+            [
+                &Call::Camera((1.0, 2.0)),
+                &Call::Rect((0.0, 0.0), 1.0, 1.0),
+                &Call::Rect((1.0, 0.0), 1.0, 1.0),
+                &Call::Rect((2.0, 0.0), 1.0, 1.0),
+                &Call::Rect((3.0, 0.0), 1.0, 1.0),
+                &Call::Camera((0.0, 0.0)),
+                &Call::Rect((4.0, 0.0), 1.0, 1.0),
+                &Call::Rect((5.0, 0.0), 1.0, 1.0),
+                &Call::Camera((0.0, 0.0))
+            ]
+        );
+    }
+
+    /// A virtual pixel at *x*, 0, which counts how often its footprint is computed.
+    struct Counted(f64, Cell<u32>);
+
+    impl Renderable for Counted {
+        fn draw(&self, backend: &mut dyn Backend) {
+            backend.draw_rect(Pos::new(self.0, 0.0), 1.0, 1.0);
+        }
+
+        fn footprint(&self) -> Option<Footprint> {
+            self.1.set(self.1.get() + 1);
+            Some(Footprint::new(self.0 as i32, 0, 1, 1, 0))
+        }
+    }
+
+    #[test]
+    fn footprints_computed_once() {
+        let pso = Pso {
+            fill_color: Some(Role::Shot),
+            ..Default::default()
+        };
+        let (still, mut moved_calls) = (Counted(1.0, Cell::new(0)), 0);
+        let mut damage = Damage::new();
+        let mut backend = RecordingBackend::new();
+        backend.partial = true;
+        let palette = Palette::default_theme();
+        let background = palette.color(Role::Background);
+        let mut arena = Bump::new();
+        for x in [3.0, 4.0] {
+            let moved = Counted(x, Cell::new(0));
+            arena.reset();
+            let mut scene = Scene::new(&arena);
+            scene.submit(Layer::World, &pso, &still);
+            scene.submit(Layer::World, &pso, &moved);
+            backend.take();
+            scene.draw_damaged(&mut backend, palette, background, &mut damage);
+            moved_calls += moved.1.get();
+        }
+
+        // The second frame only redraws the moved pixel, and every footprint was computed once a frame.
+        // ---------------------------------------------------------------------------------------------
+        let rects: Vec<Call> = backend
+            .take()
+            .into_iter()
+            .filter(|c| matches!(c, Call::Rect(..)))
+            .collect();
+        assert_eq!(rects, [Call::Rect((4.0, 0.0), 1.0, 1.0)]);
+        assert_eq!((still.1.get(), moved_calls), (2, 2));
+    }
+}
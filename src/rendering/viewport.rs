@@ -0,0 +1,162 @@
+//! Viewport module. The transform from the virtual grid of the game into the pixels of a backend.
+//!
+//! Everything is drawn in virtual coordinates: the game area is a grid of 80x60 virtual pixels, no
+//! matter how large the output actually is. Every backend owns a **Viewport**, which scales the grid
+//! as large as possible into its surface, and keeps the aspect ratio. The space left over is split
+//! evenly into bars on both sides (letterbox or pillarbox).
+//!
+//! Rect edges are rounded to whole surface pixels, so neighbouring virtual pixels have no seams and
+//! stay sharp at any scale.
+//!
+//! The viewport also has a camera offset in virtual pixels (used for screen shake), which moves
+//! everything drawn with it.
+
+use super::Pos;
+
+/// Width of the virtual grid in virtual pixels.
+pub const WIDTH: u32 = 80;
+
+/// Height of the virtual grid in virtual pixels.
+pub const HEIGHT: u32 = 60;
+
+/// Transform from virtual coordinates into surface pixels. Contains the size of a virtual pixel in
+/// surface pixels, the offset of the top left corner of the grid (the width of the bars), and the
+/// camera offset.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    scale: f64,
+    offset_x: f64,
+    offset_y: f64,
+    camera_x: f64,
+    camera_y: f64,
+}
+
+impl Viewport {
+    /// Fit the virtual grid into a surface with *width* and *height* in pixels.
+    pub fn fit(width: u32, height: u32) -> Self {
+        let scale = (width as f64 / WIDTH as f64).min(height as f64 / HEIGHT as f64);
+        Self {
+            scale,
+            offset_x: ((width as f64 - WIDTH as f64 * scale) / 2.0).floor(),
+            offset_y: ((height as f64 - HEIGHT as f64 * scale) / 2.0).floor(),
+            camera_x: 0.0,
+            camera_y: 0.0,
+        }
+    }
+
+    /// Move everything transformed from now on by *offset* virtual pixels.
+    pub fn set_camera(&mut self, offset: Pos) {
+        self.camera_x = offset.x;
+        self.camera_y = offset.y;
+    }
+
+    /// Size of a virtual pixel in surface pixels.
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Transform the virtual position *pos* into surface pixels.
+    pub fn point(&self, pos: Pos) -> Pos {
+        Pos::new(
+            self.offset_x + (pos.x + self.camera_x) * self.scale,
+            self.offset_y + (pos.y + self.camera_y) * self.scale,
+        )
+    }
+
+    /// Transform the virtual rect at *pos* with *width* and *height* into surface pixels. Returns x, y,
+    /// width and height with the edges rounded to whole pixels.
+    pub fn rect(&self, pos: Pos, width: f64, height: f64) -> (f64, f64, f64, f64) {
+        let top_left = self.point(pos);
+        let bottom_right = self.point(Pos::new(pos.x + width, pos.y + height));
+        let (x0, y0) = (top_left.x.round(), top_left.y.round());
+        let (x1, y1) = (bottom_right.x.round(), bottom_right.y.round());
+        (x0, y0, x1 - x0, y1 - y0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scale, and the surface position of the top left and bottom right corners of the grid.
+    fn corners(viewport: &Viewport) -> (f64, (f64, f64), (f64, f64)) {
+        let (tl, br) = (
+            viewport.point(Pos::new(0.0, 0.0)),
+            viewport.point(Pos::new(WIDTH as f64, HEIGHT as f64)),
+        );
+        (viewport.scale(), (tl.x(), tl.y()), (br.x(), br.y()))
+    }
+
+    #[test]
+    fn fit_4_3() {
+        let viewport = Viewport::fit(800, 600);
+        assert_eq!(corners(&viewport), (10.0, (0.0, 0.0), (800.0, 600.0)));
+    }
+
+    #[test]
+    fn fit_wide() {
+        let viewport = Viewport::fit(1366, 768);
+        assert_eq!(corners(&viewport), (12.8, (171.0, 0.0), (1195.0, 768.0)));
+    }
+
+    #[test]
+    fn fit_tall() {
+        let viewport = Viewport::fit(600, 1000);
+        assert_eq!(corners(&viewport), (7.5, (0.0, 275.0), (600.0, 725.0)));
+    }
+
+    #[test]
+    fn fit_odd_leftover() {
+        let viewport = Viewport::fit(803, 600); //<- The bars are 1 and 2 pixels wide.
+        assert_eq!(corners(&viewport), (10.0, (1.0, 0.0), (801.0, 600.0)));
+    }
+
+    #[test]
+    fn camera_offset() {
+        let mut viewport = Viewport::fit(800, 600);
+        viewport.set_camera(Pos::new(0.5, -1.0));
+        let p = viewport.point(Pos::new(10.0, 10.0));
+        assert_eq!((p.x(), p.y()), (105.0, 90.0));
+    }
+
+    /// Check, that the rects of adjacent virtual pixels in a row and a column of *viewport* touch
+    /// without gaps or overlaps, and cover the grid from edge to edge. Their sizes differ from the scale
+    /// by at most a pixel.
+    fn assert_gap_free(viewport: &Viewport) {
+        let (_, (left, top), (right, bottom)) = corners(viewport);
+        let (mut x, mut y) = (left.round(), top.round());
+        for i in 0..WIDTH {
+            let (x0, _, w, _) = viewport.rect(Pos::new(i as f64, 0.0), 1.0, 1.0);
+            assert_eq!(x0, x, "column {}", i);
+            assert!((w - viewport.scale()).abs() <= 1.0);
+            x += w;
+        }
+        for i in 0..HEIGHT {
+            let (_, y0, _, h) = viewport.rect(Pos::new(0.0, i as f64), 1.0, 1.0);
+            assert_eq!(y0, y, "row {}", i);
+            assert!((h - viewport.scale()).abs() <= 1.0);
+            y += h;
+        }
+        assert_eq!((x, y), (right.round(), bottom.round()));
+    }
+
+    #[test]
+    fn rects_gap_free() {
+        // Canvases of 800x600 css pixels at fractional device pixel ratios (1.25, 1.5 and 1.75), and
+        // odd sizes.
+        // ------------------------------------------------------------------------------------------
+        for (width, height) in [
+            (1000, 750),
+            (1200, 900),
+            (1400, 1050),
+            (1366, 768),
+            (333, 777),
+        ] {
+            let mut viewport = Viewport::fit(width, height);
+            assert_gap_free(&viewport);
+
+            viewport.set_camera(Pos::new(0.3, -0.6)); //<- Sub-pixel shake.
+            assert_gap_free(&viewport);
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Replay module.
+//!
+//! Because the simulation in the **World** is deterministic, a whole game session can be recorded by
+//! storing the seed of the world, and the timestamp and input state of every frame. A **Replay** can
+//! be encoded into a compact string (for local storage), and played back into a fresh world with the
+//! **ReplayPlayer**.
+
+use crate::input::Input;
+use crate::utils;
+use crate::world::World;
+
+/// Version of the encoded replay format.
+const FORMAT_VERSION: u8 = 1;
+
+/// A single recorded frame. Timestamp (relative to the start of the session) and input state.
+#[derive(Copy, Clone)]
+pub struct Frame {
+    pub ts: u32,
+    pub left: bool,
+    pub right: bool,
+    pub shoot: bool,
+}
+
+impl Frame {
+    /// Input flags packed into the lowest 3 bits.
+    fn flags(&self) -> u32 {
+        self.left as u32 | (self.right as u32) << 1 | (self.shoot as u32) << 2
+    }
+
+    /// Create an **Input** object with the recorded input state.
+    pub fn input(&self) -> Input {
+        Input::from_state(self.left, self.right, self.shoot)
+    }
+}
+
+/// Recording of a game session. The seed of the world and all frames.
+#[derive(Clone)]
+pub struct Replay {
+    seed: u32,
+    frames: Vec<Frame>,
+}
+
+impl Replay {
+    /// Create new empty replay for a world with *seed*.
+    pub fn new(seed: u32) -> Self {
+        Self {
+            seed,
+            frames: Vec::with_capacity(1024),
+        }
+    }
+
+    /// The seed of the recorded world.
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+
+    /// Number of recorded frames.
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Checks, if no frame was recorded yet.
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Timestamp of the last recorded frame (0, when no frame was recorded yet).
+    pub fn duration(&self) -> u32 {
+        self.frames.last().map(|f| f.ts).unwrap_or(0)
+    }
+
+    /// Index of the first frame recorded at or after timestamp *ts* (the number of frames, when there is
+    /// none).
+    pub fn frame_at(&self, ts: u32) -> usize {
+        self.frames.partition_point(|f| f.ts < ts)
+    }
+
+    /// Record a frame with timestamp *ts* and the current state of *input*.
+    pub fn record(&mut self, ts: u32, input: &Input) {
+        self.frames.push(Frame {
+            ts,
+            left: input.left(),
+            right: input.right(),
+            shoot: input.shoot(),
+        });
+    }
+
+    /// Encode the replay into a compact string.
+    ///
+    /// Layout (before base64): format version byte, seed (4 bytes little endian), and then one
+    /// varint per frame, which contains the timestamp delta to the previous frame shifted left by
+    /// 3, and the input flags in the lowest 3 bits.
+    pub fn encode(&self) -> String {
+        let mut data = Vec::with_capacity(5 + self.frames.len() * 2);
+        data.push(FORMAT_VERSION);
+        data.extend_from_slice(&self.seed.to_le_bytes());
+
+        let mut last_ts = 0;
+        for f in self.frames.iter() {
+            let mut v = ((f.ts - last_ts) as u64) << 3 | f.flags() as u64;
+            last_ts = f.ts;
+
+            // Write varint (7 bits per byte, high bit signals continuation).
+            // --------------------------------------------------------------
+            loop {
+                let byte = (v & 0x7f) as u8;
+                v >>= 7;
+                if v == 0 {
+                    data.push(byte);
+                    break;
+                }
+                data.push(byte | 0x80);
+            }
+        }
+
+        utils::base64_encode(&data)
+    }
+
+    /// Decode a replay from a string created by **encode()**. Returns `None` on malformed input.
+    pub fn decode(s: &str) -> Option<Self> {
+        let data = utils::base64_decode(s)?;
+        if data.len() < 5 || data[0] != FORMAT_VERSION {
+            return None;
+        }
+
+        let mut replay = Self::new(u32::from_le_bytes([data[1], data[2], data[3], data[4]]));
+
+        let mut ts = 0u32;
+        let mut v = 0u64;
+        let mut shift = 0;
+        for byte in data[5..].iter() {
+            v |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if shift > 63 {
+                return None;
+            }
+
+            if byte & 0x80 == 0 {
+                ts = ts.checked_add((v >> 3) as u32)?;
+                replay.frames.push(Frame {
+                    ts,
+                    left: v & 1 != 0,
+                    right: v & 2 != 0,
+                    shoot: v & 4 != 0,
+                });
+                v = 0;
+                shift = 0;
+            }
+        }
+
+        if shift != 0 {
+            return None; //<- Truncated varint.
+        }
+
+        Some(replay)
+    }
+}
+
+/// Plays back a **Replay** frame by frame into a world.
+pub struct ReplayPlayer {
+    replay: Replay,
+    cursor: usize,
+}
+
+impl ReplayPlayer {
+    /// Create new player, which starts at the first frame of *replay*.
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, cursor: 0 }
+    }
+
+    /// Checks, if all frames were played back.
+    pub fn finished(&self) -> bool {
+        self.cursor >= self.replay.frames.len()
+    }
+
+    /// Take the next frame without playing it back into a world. Returns `None`, when all frames were
+    /// played back already.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        let frame = self.replay.frames.get(self.cursor).copied();
+        if frame.is_some() {
+            self.cursor += 1;
+        }
+        frame
+    }
+
+    /// Update *world* with the next frame. Returns the number of game ticks simulated (0 when all
+    /// frames were played back already).
+    pub fn step_next(&mut self, world: &mut World) -> u32 {
+        match self.next_frame() {
+            Some(f) => world.update(f.ts, &f.input()),
+            None => 0,
+        }
+    }
+
+    /// Update *world* with all frames not played back yet, which have a timestamp up to (and including)
+    /// *ts*. This allows to step the world in lockstep with another one. Returns the number of game
+    /// ticks simulated.
+    pub fn step_until(&mut self, world: &mut World, ts: u32) -> u32 {
+        let mut ticks = 0;
+
+        while let Some(f) = self.replay.frames.get(self.cursor) {
+            if f.ts > ts {
+                break;
+            }
+            ticks += self.step_next(world);
+        }
+
+        ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A replay with *seed*, and frames with varying deltas (small and large) and all input states.
+    fn sample(seed: u32) -> Replay {
+        let mut replay = Replay::new(seed);
+        let mut ts = 0;
+        for i in 0..64u32 {
+            ts += if i % 16 == 15 { 70_000 } else { 16 + i % 3 };
+            replay.record(ts, &Input::from_state(i & 1 != 0, i & 2 != 0, i & 4 != 0));
+        }
+        replay
+    }
+
+    /// The frames of *replay* as comparable tuples.
+    fn frames(replay: &Replay) -> Vec<(u32, bool, bool, bool)> {
+        replay
+            .frames
+            .iter()
+            .map(|f| (f.ts, f.left, f.right, f.shoot))
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        for seed in [0, 1, 0xdead_beef, u32::MAX].iter() {
+            let replay = sample(*seed);
+            let decoded = Replay::decode(&replay.encode()).unwrap();
+            assert_eq!(decoded.seed(), *seed);
+            assert_eq!(frames(&decoded), frames(&replay));
+        }
+    }
+
+    #[test]
+    fn round_trip_empty() {
+        let decoded = Replay::decode(&Replay::new(42).encode()).unwrap();
+        assert_eq!(decoded.seed(), 42);
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn reject_invalid_base64() {
+        assert!(Replay::decode("AQAAAAA!").is_none());
+        assert!(Replay::decode("AQAAAAAQ\u{e9}").is_none());
+        assert!(Replay::decode("AQAAA").is_none()); //<- A single char in the last group.
+    }
+
+    #[test]
+    fn reject_truncated() {
+        let encoded = sample(7).encode();
+        let data = utils::base64_decode(&encoded).unwrap();
+
+        // Too short for the header.
+        // -------------------------
+        assert!(Replay::decode(&utils::base64_encode(&data[..4])).is_none());
+        assert!(Replay::decode("").is_none());
+
+        // Cut in the middle of a varint (the last byte has the continuation bit set).
+        // ---------------------------------------------------------------------------
+        let mut cut = data.clone();
+        cut.push(0x80);
+        assert!(Replay::decode(&utils::base64_encode(&cut)).is_none());
+    }
+
+    #[test]
+    fn reject_unknown_version() {
+        let mut data = utils::base64_decode(&sample(7).encode()).unwrap();
+        data[0] = FORMAT_VERSION + 1;
+        assert!(Replay::decode(&utils::base64_encode(&data)).is_none());
+    }
+
+    #[test]
+    fn reject_overlong_varint() {
+        let mut data = vec![FORMAT_VERSION, 0, 0, 0, 0];
+        data.extend_from_slice(&[0xff; 10]);
+        data.push(0);
+        assert!(Replay::decode(&utils::base64_encode(&data)).is_none());
+    }
+}
@@ -0,0 +1,73 @@
+//! Rules module.
+//!
+//! The **Rules** are the parameters of the simulation (spawn rate, enemy speed, wave size and lives).
+//! The classic game uses the default rules. Rule **Modifier**s change them for special game modes (like
+//! the daily challenge).
+
+/// Parameters of the simulation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rules {
+    /// Game ticks between two enemy spawns.
+    pub spawn_interval: u8,
+    /// Game ticks between two enemy movements.
+    pub enemy_interval: u8,
+    /// Number of enemies per wave.
+    pub wave_size: u32,
+    /// Number of lives at the start of the game.
+    pub lives: u32,
+}
+
+impl Default for Rules {
+    /// The rules of the classic game.
+    fn default() -> Self {
+        Self {
+            spawn_interval: 200,
+            enemy_interval: 10,
+            wave_size: 10,
+            lives: 3,
+        }
+    }
+}
+
+/// Modifiers, which change the rules.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Modifier {
+    /// Enemies spawn a lot faster.
+    Swarm,
+    /// Enemies move faster.
+    Hurry,
+    /// Waves have more enemies.
+    BigWaves,
+    /// Only a single life.
+    OneLife,
+}
+
+impl Modifier {
+    /// All variants as a static slice.
+    pub const VARIANTS: &'static [Self] = &[
+        Modifier::Swarm,
+        Modifier::Hurry,
+        Modifier::BigWaves,
+        Modifier::OneLife,
+    ];
+
+    /// Name of the modifier.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Modifier::Swarm => "Swarm",
+            Modifier::Hurry => "Hurry",
+            Modifier::BigWaves => "Big waves",
+            Modifier::OneLife => "One life",
+        }
+    }
+
+    /// Apply the modifier to *rules*.
+    pub fn apply(&self, rules: &mut Rules) {
+        match self {
+            Modifier::Swarm => rules.spawn_interval = 120,
+            Modifier::Hurry => rules.enemy_interval = 7,
+            Modifier::BigWaves => rules.wave_size = 16,
+            Modifier::OneLife => rules.lives = 1,
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Session module. The per-frame game logic, which is shared by all frontends.
+//!
+//! A **Session** owns the world, and everything that reacts to it (score, lives, statistics, effects,
+//! feedback and the starfield). The live game in the browser, headless playback and the terminal all
+//! simulate their frames through **Session::simulate()**, so a replay played back natively behaves
+//! exactly like the run it was recorded from.
+//!
+//! Frontends only add what is specific to them around it: their input, the HUD, and subscribers of their
+//! own (e.g. achievements, or JS callbacks).
+
+#[cfg(feature = "debug-overlay")]
+use crate::debug::DebugOverlay;
+use crate::effects::Effects;
+use crate::entites::{FpsCounter, GameOver, Lives, Score};
+use crate::events::{GameEvent, Subscriber};
+use crate::feedback::{Feedback, FeedbackSettings};
+use crate::hud::HudState;
+use crate::input::Input;
+#[cfg(feature = "debug-overlay")]
+use crate::rendering::{BindCounters, DrawCounters};
+use crate::rendering::{Scene, SceneNode};
+use crate::replay::Replay;
+use crate::rules::Rules;
+use crate::starfield::{Starfield, StarfieldSettings};
+use crate::stats::SessionStats;
+use crate::world::World;
+
+/// The state of a game session. The world with its *recording*, and everything driven by its events.
+pub struct Session {
+    world: World,
+    recording: Replay,
+    fps_counter: FpsCounter,
+    effects: Effects,
+    feedback: Feedback,
+    starfield: Starfield,
+    score: Score,
+    lives: Lives,
+    stats: SessionStats,
+    game_over: Option<GameOver>,
+    #[cfg(feature = "debug-overlay")]
+    debug: Option<DebugOverlay>,
+    snap_to_grid: bool,
+}
+
+impl Session {
+    /// Create new session with a world initialized by *seed*, which is played according to *rules*. The
+    /// feedback effects and the starfield start with *feedback* and *starfield* settings.
+    pub fn new(
+        seed: u32,
+        rules: Rules,
+        feedback: FeedbackSettings,
+        starfield: StarfieldSettings,
+    ) -> Self {
+        Self {
+            world: World::with_rules(seed, rules),
+            recording: Replay::new(seed),
+            fps_counter: FpsCounter::new(),
+            effects: Effects::new(),
+            feedback: Feedback::new(feedback),
+            starfield: Starfield::new(starfield),
+            score: Score::new(),
+            lives: Lives::new(rules.lives),
+            stats: SessionStats::new(),
+            game_over: None,
+            #[cfg(feature = "debug-overlay")]
+            debug: None,
+            snap_to_grid: false,
+        }
+    }
+
+    /// Start a frame at timestamp *ts*. Updates the FPS counter and the feedback effects (they also fade
+    /// out after the game is over). Called every frame, even when the world is not simulated.
+    pub fn begin_frame(&mut self, ts: u32) {
+        self.fps_counter.update(ts);
+        self.feedback.update(ts);
+    }
+
+    /// Checks, if the world stands still this frame: the game is over, or there is a hit-stop.
+    pub fn frozen(&self) -> bool {
+        self.game_over.is_some() || self.feedback.hit_stop()
+    }
+
+    /// Simulate a frame at the world timestamp *ts* (without the time the world was stopped) with
+    /// *input*. Records the frame, updates the world, and hands its events to everything in the session,
+    /// and to the *subscribers* of the frontend. Returns true, when the game ended with this frame.
+    pub fn simulate(
+        &mut self,
+        ts: u32,
+        input: &Input,
+        subscribers: &mut [&mut dyn Subscriber],
+    ) -> bool {
+        self.recording.record(ts, input);
+        let ticks = self.world.update(ts, input);
+
+        // Update the effects for every simulated game tick, and scroll the background along with the
+        // world.
+        // ------------------------------------------------------------------------------------------
+        for _ in 0..ticks {
+            self.effects.tick();
+        }
+        self.starfield.update(ts);
+        #[cfg(feature = "debug-overlay")]
+        {
+            if let Some(d) = &mut self.debug {
+                d.count_ticks(ticks);
+            }
+        }
+
+        // Drain the events of this frame, and hand them to everything that subscribed to them.
+        // -------------------------------------------------------------------------------------
+        self.world.dispatch(&mut [
+            &mut self.score as &mut dyn Subscriber,
+            &mut self.effects as &mut dyn Subscriber,
+            &mut self.feedback as &mut dyn Subscriber,
+            &mut self.starfield as &mut dyn Subscriber,
+            &mut self.lives as &mut dyn Subscriber,
+            &mut self.stats as &mut dyn Subscriber,
+            &mut Frontend(subscribers) as &mut dyn Subscriber,
+        ]);
+
+        // Update the session statistics, and end the game when the player has no lives left.
+        // -----------------------------------------------------------------------------------
+        self.stats.update(ts, &self.fps_counter);
+        if self.lives.count() == 0 {
+            self.game_over = Some(GameOver::new(&self.stats));
+            return true;
+        }
+        false
+    }
+
+    /// The state shown by the HUD (FPS, score, lives and wave). Frontends add the rest (e.g. the high
+    /// score).
+    pub fn hud_state(&self) -> HudState<'_> {
+        HudState {
+            fps: self.fps_counter.label(),
+            score: self.score.value(),
+            lives: self.lives.count(),
+            wave: self.world.wave(),
+            ..Default::default()
+        }
+    }
+
+    /// The recording of all frames simulated so far.
+    pub fn recording(&self) -> &Replay {
+        &self.recording
+    }
+
+    /// The feedback effects (screen shake, hit-stop and flash).
+    pub fn feedback(&self) -> &Feedback {
+        &self.feedback
+    }
+
+    /// Mutable access to the feedback effects (e.g. to change their settings).
+    pub fn feedback_mut(&mut self) -> &mut Feedback {
+        &mut self.feedback
+    }
+
+    /// The starfield in the background.
+    pub fn starfield(&self) -> &Starfield {
+        &self.starfield
+    }
+
+    /// Mutable access to the starfield (e.g. to change its settings).
+    pub fn starfield_mut(&mut self) -> &mut Starfield {
+        &mut self.starfield
+    }
+
+    /// The score.
+    pub fn score(&self) -> &Score {
+        &self.score
+    }
+
+    /// The lives of the player.
+    pub fn lives(&self) -> &Lives {
+        &self.lives
+    }
+
+    /// The statistics of the session so far.
+    pub fn stats(&self) -> &SessionStats {
+        &self.stats
+    }
+
+    /// The game over screen, when the game is over.
+    pub fn game_over(&self) -> Option<&GameOver> {
+        self.game_over.as_ref()
+    }
+
+    /// Mutable access to the game over screen (e.g. to add lines to it).
+    pub fn game_over_mut(&mut self) -> Option<&mut GameOver> {
+        self.game_over.as_mut()
+    }
+
+    /// Checks, if moving entities are drawn on the grid instead of smoothly between the game ticks.
+    pub fn snap_to_grid(&self) -> bool {
+        self.snap_to_grid
+    }
+
+    /// Switch snapping moving entities to the grid *on* or off.
+    pub fn set_snap_to_grid(&mut self, on: bool) {
+        self.snap_to_grid = on;
+    }
+
+    /// Checks, if the debug overlay is shown.
+    #[cfg(feature = "debug-overlay")]
+    pub fn debug_overlay(&self) -> bool {
+        self.debug.is_some()
+    }
+
+    /// Show or hide the debug overlay.
+    #[cfg(feature = "debug-overlay")]
+    pub fn set_debug_overlay(&mut self, on: bool) {
+        self.debug = if on { Some(DebugOverlay::new()) } else { None };
+    }
+
+    /// Take a snapshot of the session for the debug overlay (if shown), with the *render* statistics of
+    /// the backend.
+    #[cfg(feature = "debug-overlay")]
+    pub fn update_debug(&mut self, render: Option<(BindCounters, DrawCounters)>) {
+        if let Some(d) = &mut self.debug {
+            d.update(&self.world, &self.effects, render);
+        }
+    }
+}
+
+/// The subscribers of a frontend, which receive the events after the ones of the session.
+struct Frontend<'s, 'a>(&'s mut [&'a mut dyn Subscriber]);
+
+impl Subscriber for Frontend<'_, '_> {
+    fn on_event(&mut self, event: &GameEvent) {
+        for s in self.0.iter_mut() {
+            s.on_event(event);
+        }
+    }
+}
+
+impl SceneNode for Session {
+    /// Submit the background, the world (moving entities between the last two ticks), the effects, and
+    /// the game over screen and debug overlay (when they are shown).
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        scene.set_interpolation(self.world.interpolation(self.snap_to_grid));
+        scene.add(&[
+            &self.starfield as &dyn SceneNode,
+            &self.world as &dyn SceneNode,
+            &self.effects as &dyn SceneNode,
+            &self.feedback as &dyn SceneNode,
+        ]);
+        if let Some(g) = &self.game_over {
+            g.submit(scene);
+        }
+        #[cfg(feature = "debug-overlay")]
+        {
+            if let Some(d) = &self.debug {
+                d.submit(scene);
+            }
+        }
+    }
+}
@@ -0,0 +1,396 @@
+//! Sprites module.
+//!
+//! The pixel art of all entities is defined in the sprite sheet asset `assets/sprites.txt` as ASCII-art
+//! grids, so it can be edited without touching any Rust code (see the file for the format). Every sprite
+//! has a name, one or more frames with a color index per pixel, bounds derived from its frames, and
+//! optionally animation clips of its frames (see the animation module).
+//!
+//! The sheet is embedded into the build, and parsed once at startup (on first use).
+
+use crate::animation::{Clip, Mode};
+use crate::rendering::{Backend, CssColor, Footprint, Pos};
+use std::fmt;
+
+/// Number of color indices (0 is transparent, 1 is the color of the bound PSO).
+const COLORS: usize = 10;
+
+lazy_static! {
+    /// The sprite sheet of the game.
+    static ref SHEET: SpriteSheet = SpriteSheet::parse(include_str!("../assets/sprites.txt"))
+        .unwrap_or_else(|e| panic!("invalid sprite sheet: {}", e));
+}
+
+/// Get the sprite called *name* from the sprite sheet of the game. Panics, when there is no such sprite
+/// (the sheet is part of the build, so this is a bug).
+pub fn get(name: &str) -> &'static Sprite {
+    SHEET
+        .get(name)
+        .unwrap_or_else(|| panic!("missing sprite: {}", name))
+}
+
+/// A single frame of a sprite. Color index per pixel, row by row.
+pub struct Frame {
+    width: u32,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Iterate over the visible pixels of the frame as (x, y, color index).
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32, u8)> + '_ {
+        let width = self.width;
+        self.pixels
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| **c != 0)
+            .map(move |(i, c)| (i as u32 % width, i as u32 / width, *c))
+    }
+}
+
+/// A sprite. Contains its frames, bounds, the colors of its color indices, and its animation clips.
+pub struct Sprite {
+    name: String,
+    frames: Vec<Frame>,
+    bounds: (u32, u32),
+    colors: [Option<CssColor>; COLORS],
+    clips: Vec<Clip>,
+}
+
+impl Sprite {
+    /// Frame with index *frame* (wraps around, so it can be used with a running counter).
+    pub fn frame(&self, frame: usize) -> &Frame {
+        &self.frames[frame % self.frames.len()]
+    }
+
+    /// Animation clip called *name*. Panics, when the sprite has no such clip (like **get()**).
+    pub fn clip(&self, name: &str) -> &Clip {
+        self.clips
+            .iter()
+            .find(|c| c.name() == name)
+            .unwrap_or_else(|| panic!("missing clip: {}.{}", self.name, name))
+    }
+
+    /// Width and height in virtual pixels (the size of the largest frame).
+    pub fn bounds(&self) -> (u32, u32) {
+        self.bounds
+    }
+
+    /// Footprint of *frame* drawn at the virtual position *pos* (the bounds, keyed by the frame).
+    pub fn footprint(&self, frame: usize, pos: Pos) -> Footprint {
+        let (w, h) = self.bounds;
+        let key = (frame % self.frames.len()) as u64;
+        Footprint::covering(pos, w as f64, h as f64, key)
+    }
+
+    /// Draw *frame* at the virtual position *pos* (the top left corner). Positions between whole virtual
+    /// pixels are allowed (e.g. for interpolated movement).
+    ///
+    /// Color index 1 is drawn with the bound PSO. Other color indices set their own fill color, and restore
+    /// the drawing state afterwards.
+    pub fn draw(&self, backend: &mut dyn Backend, frame: usize, pos: Pos) {
+        let frame = self.frame(frame);
+        let draw_px = |backend: &mut dyn Backend, px: u32, py: u32| {
+            let (x, y) = (pos.x() + px as f64, pos.y() + py as f64);
+            backend.draw_rect(Pos::new(x, y), 1.0, 1.0);
+        };
+
+        for (px, py, _) in frame.pixels().filter(|p| p.2 == 1) {
+            draw_px(backend, px, py);
+        }
+
+        for (index, color) in self.colors.iter().enumerate().skip(2) {
+            if let Some(color) = color {
+                let mut pixels = frame.pixels().filter(|p| p.2 as usize == index).peekable();
+                if pixels.peek().is_none() {
+                    continue;
+                }
+
+                backend.save();
+                backend.set_fill_color(*color);
+                for (px, py, _) in pixels {
+                    draw_px(backend, px, py);
+                }
+                backend.restore();
+            }
+        }
+    }
+}
+
+/// Error of parsing a sprite sheet. Contains the line number (starting at 1) and the reason.
+#[derive(Debug, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub reason: &'static str,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+/// A collection of named sprites.
+pub struct SpriteSheet {
+    sprites: Vec<Sprite>,
+}
+
+impl SpriteSheet {
+    /// Parse a sprite sheet from its text *source* (see `assets/sprites.txt` for the format).
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let mut sprites: Vec<Sprite> = vec![];
+        let mut starts = vec![]; //<- Line numbers of the sprites for errors.
+        let mut colors = [None; COLORS];
+        let mut rows: Option<Vec<Vec<u8>>> = None; //<- Rows of the frame, which is parsed right now.
+
+        for (i, line) in source.lines().enumerate() {
+            let err = |reason| ParseError {
+                line: i + 1,
+                reason,
+            };
+            let line = line.trim_end();
+
+            // Any line, which is not a pixel row, ends the current frame.
+            // ---------------------------------------------------------------
+            let is_keyword = line == "frame"
+                || line.starts_with("sprite ")
+                || line.starts_with("color ")
+                || line.starts_with("clip ");
+            if line.is_empty() || is_keyword {
+                if let Some(rows) = rows.take() {
+                    Self::push_frame(sprites.last_mut().unwrap(), rows).map_err(err)?;
+                }
+            }
+
+            if line == "frame" {
+                if sprites.is_empty() {
+                    return Err(err("frame outside of a sprite"));
+                }
+                rows = Some(vec![]);
+            } else if let Some(name) = line.strip_prefix("sprite ") {
+                let name = name.trim();
+                if sprites.iter().any(|s| s.name == name) {
+                    return Err(err("duplicate sprite name"));
+                }
+                starts.push(i + 1);
+                sprites.push(Sprite {
+                    name: name.to_owned(),
+                    frames: vec![],
+                    bounds: (0, 0),
+                    colors: [None; COLORS],
+                    clips: vec![],
+                });
+            } else if let Some(def) = line.strip_prefix("color ") {
+                let values: Vec<u8> = def
+                    .split_whitespace()
+                    .map(|v| v.parse().map_err(|_| err("invalid color definition")))
+                    .collect::<Result<_, _>>()?;
+                match values[..] {
+                    [index, r, g, b] if index >= 2 && (index as usize) < COLORS => {
+                        colors[index as usize] = Some(CssColor::new(r, g, b));
+                    }
+                    _ => return Err(err("invalid color definition")),
+                }
+            } else if let Some(def) = line.strip_prefix("clip ") {
+                let sprite = sprites
+                    .last_mut()
+                    .ok_or_else(|| err("clip outside of a sprite"))?;
+                sprite
+                    .clips
+                    .push(Self::parse_clip(def).ok_or_else(|| err("invalid clip definition"))?);
+                let name = sprite.clips.last().unwrap().name();
+                if sprite.clips.iter().filter(|c| c.name() == name).count() > 1 {
+                    return Err(err("duplicate clip name"));
+                }
+            } else if let Some(rows) = &mut rows {
+                let row = line
+                    .chars()
+                    .map(|c| match c {
+                        '.' => Ok(0),
+                        '#' => Ok(1),
+                        '1'..='9' => Ok(c as u8 - b'0'),
+                        _ => Err(err("invalid pixel")),
+                    })
+                    .collect::<Result<_, _>>()?;
+                rows.push(row);
+            } else if !line.is_empty() && !line.starts_with('#') {
+                return Err(err("unexpected line"));
+            }
+        }
+
+        if let Some(rows) = rows.take() {
+            Self::push_frame(sprites.last_mut().unwrap(), rows).map_err(|reason| ParseError {
+                line: source.lines().count(),
+                reason,
+            })?;
+        }
+
+        // Check the sprites, and hand them the colors they need.
+        // ------------------------------------------------------
+        for (sprite, line) in sprites.iter_mut().zip(starts) {
+            if sprite.frames.is_empty() {
+                return Err(ParseError {
+                    line,
+                    reason: "sprite without frames",
+                });
+            }
+
+            let undefined = sprite
+                .frames
+                .iter()
+                .flat_map(|f| f.pixels.iter())
+                .any(|c| *c >= 2 && colors[*c as usize].is_none());
+            if undefined {
+                return Err(ParseError {
+                    line,
+                    reason: "undefined color index",
+                });
+            }
+            let frames = sprite.frames.len();
+            if sprite
+                .clips
+                .iter()
+                .any(|c| c.frames().iter().any(|f| f.0 >= frames))
+            {
+                return Err(ParseError {
+                    line,
+                    reason: "clip frame out of range",
+                });
+            }
+            sprite.colors = colors;
+        }
+
+        Ok(Self { sprites })
+    }
+
+    /// Parse the clip definition *def* (`<name> <mode> <frame>:<ms> ...`). Returns `None`, when it is
+    /// invalid.
+    fn parse_clip(def: &str) -> Option<Clip> {
+        let mut parts = def.split_whitespace();
+        let name = parts.next()?;
+        let mode = match parts.next()? {
+            "loop" => Mode::Loop,
+            "once" => Mode::Once,
+            "pingpong" => Mode::PingPong,
+            _ => return None,
+        };
+
+        let frames = parts
+            .map(|f| {
+                let (frame, ms) = f.split_once(':')?;
+                Some((frame.parse().ok()?, ms.parse().ok().filter(|ms| *ms > 0)?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if frames.is_empty() {
+            return None;
+        }
+
+        Some(Clip::new(name, mode, frames))
+    }
+
+    /// Append the frame with pixel *rows* to *sprite*, and grow its bounds to fit the frame.
+    fn push_frame(sprite: &mut Sprite, rows: Vec<Vec<u8>>) -> Result<(), &'static str> {
+        let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+        if width == 0 {
+            return Err("empty frame");
+        }
+
+        let mut pixels = vec![0; width * rows.len()];
+        for (y, row) in rows.iter().enumerate() {
+            pixels[y * width..y * width + row.len()].copy_from_slice(row);
+        }
+
+        sprite.bounds = (
+            sprite.bounds.0.max(width as u32),
+            sprite.bounds.1.max(rows.len() as u32),
+        );
+        sprite.frames.push(Frame {
+            width: width as u32,
+            pixels,
+        });
+        Ok(())
+    }
+
+    /// Get the sprite called *name*.
+    pub fn get(&self, name: &str) -> Option<&Sprite> {
+        self.sprites.iter().find(|s| s.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_color_definition() {
+        let source = "\
+# Index 2 is never defined.
+
+sprite dot
+frame
+.2.
+";
+        assert_eq!(
+            SpriteSheet::parse(source).err(),
+            Some(ParseError {
+                line: 3,
+                reason: "undefined color index"
+            })
+        );
+    }
+
+    #[test]
+    fn ragged_frame() {
+        let source = "\
+sprite ragged
+frame
+#
+###
+##
+";
+        let sheet = SpriteSheet::parse(source).unwrap();
+        let sprite = sheet.get("ragged").unwrap();
+        assert_eq!(sprite.bounds(), (3, 3));
+
+        // Short rows are padded with transparent pixels.
+        // ----------------------------------------------
+        let pixels: Vec<_> = sprite.frame(0).pixels().collect();
+        assert_eq!(
+            pixels,
+            [
+                (0, 0, 1),
+                (0, 1, 1),
+                (1, 1, 1),
+                (2, 1, 1),
+                (0, 2, 1),
+                (1, 2, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn row_after_blank_line_is_comment() {
+        let source = "\
+sprite split
+frame
+##
+
+##
+##
+";
+        let sheet = SpriteSheet::parse(source).unwrap();
+        let sprite = sheet.get("split").unwrap();
+        assert_eq!(sprite.bounds(), (2, 1));
+        assert_eq!(sprite.frame(0).pixels().count(), 2);
+    }
+
+    #[test]
+    fn invalid_pixel() {
+        let source = "sprite bad\nframe\n#x#\n";
+        assert_eq!(
+            SpriteSheet::parse(source).err(),
+            Some(ParseError {
+                line: 3,
+                reason: "invalid pixel"
+            })
+        );
+    }
+}
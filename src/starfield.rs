@@ -0,0 +1,343 @@
+//! Starfield module. The parallax background behind the game area.
+//!
+//! The background consists of several layers of stars. Every layer scrolls down with its own speed (far
+//! layers slowly, near layers fast), so it looks like the ship flies through space, and its stars
+//! twinkle. The layers are configured per wave in the **StarfieldSettings**, so the field gets denser and
+//! faster while the game goes on. It can also be switched off completely.
+//!
+//! Stars are purely visual. They are not part of the world (so nothing collides with them), and have
+//! their own random number generator, so the simulation and its replays are not affected. Stars move by
+//! whole virtual pixels, and every star is its own drawable with a footprint of one cell, so dirty rects
+//! only redraw the stars, which moved or changed their brightness. The stars of a layer with the same
+//! brightness share a PSO, so they are drawn in one batch.
+
+use crate::events::{GameEvent, Subscriber};
+use crate::rendering::{
+    Backend, Footprint, Layer, Pos, Pso, Renderable, Role, Scene, SceneNode, HEIGHT, WIDTH,
+};
+use crate::utils::Rng;
+
+/// Opacity of the twinkle steps, from the darkest to the brightest.
+const TWINKLE: [u8; 3] = [90, 170, 255];
+
+/// Shortest and longest duration of a twinkle cycle of a star in ms.
+const TWINKLE_PERIOD: (u32, u32) = (1500, 4000);
+
+/// Configuration of a layer of stars. The number of stars in the whole game area, their speed in virtual
+/// pixels per second, and the role of their color.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StarLayer {
+    pub density: u32,
+    pub speed: f32,
+    pub color: Role,
+}
+
+/// Settings of the starfield. If it is *enabled*, and the layers of every wave (the last entry is used
+/// for all following waves).
+#[derive(Clone, Debug, PartialEq)]
+pub struct StarfieldSettings {
+    pub enabled: bool,
+    pub waves: Vec<Vec<StarLayer>>,
+}
+
+impl Default for StarfieldSettings {
+    /// Three layers, which get denser and faster in the first waves.
+    fn default() -> Self {
+        let layers = |density: u32, speed: f32| {
+            vec![
+                StarLayer {
+                    density: density * 4,
+                    speed,
+                    color: Role::StarDim,
+                },
+                StarLayer {
+                    density: density * 2,
+                    speed: speed * 2.5,
+                    color: Role::StarDim,
+                },
+                StarLayer {
+                    density,
+                    speed: speed * 6.0,
+                    color: Role::Star,
+                },
+            ]
+        };
+
+        Self {
+            enabled: true,
+            waves: vec![
+                layers(6, 1.0),
+                layers(7, 1.3),
+                layers(8, 1.6),
+                layers(10, 2.0),
+            ],
+        }
+    }
+}
+
+impl StarfieldSettings {
+    /// The layers of *wave* (starts at 1).
+    fn layers(&self, wave: u32) -> &[StarLayer] {
+        let index = (wave.max(1) as usize - 1).min(self.waves.len().saturating_sub(1));
+        self.waves.get(index).map_or(&[], |l| l.as_slice())
+    }
+}
+
+/// A single star. Its column, its row (with the part of a pixel it scrolled already), and its twinkle
+/// cycle (the duration, and the offset into it) in ms.
+struct Star {
+    x: u32,
+    y: f32,
+    period: u32,
+    phase: u32,
+}
+
+impl Renderable for Star {
+    /// Draw the star as a single virtual pixel.
+    fn draw(&self, backend: &mut dyn Backend) {
+        backend.draw_rect(Pos::new(self.x as f64, self.y.floor() as f64), 1.0, 1.0);
+    }
+
+    /// A single cell.
+    fn footprint(&self) -> Option<Footprint> {
+        Some(Footprint::new(
+            self.x as i32,
+            self.y.floor() as i32,
+            1,
+            1,
+            0,
+        ))
+    }
+}
+
+impl Star {
+    /// The twinkle step of the star at *time* (an index into **TWINKLE**). It goes up and down once per
+    /// cycle.
+    fn twinkle(&self, time: u32) -> usize {
+        let steps = TWINKLE.len() as u32;
+        let step = (time + self.phase) % self.period * steps * 2 / self.period;
+        (if step < steps {
+            step
+        } else {
+            steps * 2 - 1 - step
+        }) as usize
+    }
+}
+
+/// The stars of a layer, their speed, and a PSO for every twinkle step.
+struct Stars {
+    speed: f32,
+    psos: Vec<Pso>,
+    stars: Vec<Star>,
+}
+
+/// Starfield state. Contains the settings, the current wave, the layers of stars, the timestamp of the
+/// last update, the time since the start in ms (for twinkling), and the random number generator of the
+/// stars (visual only, not part of the simulation).
+pub struct Starfield {
+    settings: StarfieldSettings,
+    wave: u32,
+    layers: Vec<Stars>,
+    last_ts: Option<u32>,
+    time: u32,
+    rng: Rng,
+}
+
+impl Starfield {
+    /// Create new starfield with *settings* in the first wave.
+    pub fn new(settings: StarfieldSettings) -> Self {
+        let mut starfield = Self {
+            settings,
+            wave: 1,
+            layers: vec![],
+            last_ts: None,
+            time: 0,
+            rng: Rng::new(0x57a2_f1e1),
+        };
+        starfield.configure();
+        starfield
+    }
+
+    /// The settings.
+    pub fn settings(&self) -> &StarfieldSettings {
+        &self.settings
+    }
+
+    /// Change the *settings*. The layers of the current wave are configured again.
+    pub fn set_settings(&mut self, settings: StarfieldSettings) {
+        self.settings = settings;
+        self.configure();
+    }
+
+    /// Configure the layers for the current wave. Stars, which are still needed, stay where they are,
+    /// missing ones are added at random positions.
+    fn configure(&mut self) {
+        let config = self.settings.layers(self.wave).to_vec();
+        self.layers.truncate(config.len());
+
+        for (i, layer) in config.iter().enumerate() {
+            if i == self.layers.len() {
+                self.layers.push(Stars {
+                    speed: 0.0,
+                    psos: vec![],
+                    stars: vec![],
+                });
+            }
+
+            let stars = &mut self.layers[i];
+            stars.speed = layer.speed;
+            stars.psos = TWINKLE
+                .iter()
+                .map(|alpha| Pso {
+                    fill_color: Some(layer.color),
+                    alpha: Some(*alpha),
+                    ..Default::default()
+                })
+                .collect();
+            stars.stars.truncate(layer.density as usize);
+            while stars.stars.len() < layer.density as usize {
+                let y = self.rng.rand(HEIGHT - 1) as f32;
+                let star = Self::star(&mut self.rng, y);
+                stars.stars.push(star);
+            }
+        }
+    }
+
+    /// Create new star in the row *y* with a random column and twinkle cycle.
+    fn star(rng: &mut Rng, y: f32) -> Star {
+        let period = TWINKLE_PERIOD.0 + rng.rand(TWINKLE_PERIOD.1 - TWINKLE_PERIOD.0);
+        Star {
+            x: rng.rand(WIDTH - 1),
+            y,
+            period,
+            phase: rng.rand(period),
+        }
+    }
+
+    /// Scroll all layers to timestamp *ts* (in ms). Stars, which leave the game area at the bottom, come
+    /// back at the top in a random column.
+    pub fn update(&mut self, ts: u32) {
+        let dt = ts.saturating_sub(self.last_ts.unwrap_or(ts));
+        self.last_ts = Some(ts);
+        self.time = self.time.wrapping_add(dt);
+
+        for layer in self.layers.iter_mut() {
+            for star in layer.stars.iter_mut() {
+                star.y += layer.speed * dt as f32 / 1000.0;
+                if star.y >= HEIGHT as f32 {
+                    *star = Self::star(&mut self.rng, star.y - HEIGHT as f32);
+                }
+            }
+        }
+    }
+}
+
+impl Subscriber for Starfield {
+    /// Switch to the layers of the next wave, when a wave is cleared.
+    fn on_event(&mut self, event: &GameEvent) {
+        if let GameEvent::WaveCleared(result) = event {
+            self.wave = result.number + 1;
+            self.configure();
+        }
+    }
+}
+
+impl SceneNode for Starfield {
+    /// Submit all stars with the PSO of their layer and twinkle step (unless the starfield is disabled).
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        if !self.settings.enabled {
+            return;
+        }
+
+        for layer in self.layers.iter() {
+            for star in layer.stars.iter() {
+                let pso = &layer.psos[star.twinkle(self.time)];
+                scene.submit(Layer::Background, pso, star);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waves::WaveResult;
+
+    /// Densities of the layers of *starfield*.
+    fn densities(starfield: &Starfield) -> Vec<usize> {
+        starfield.layers.iter().map(|l| l.stars.len()).collect()
+    }
+
+    #[test]
+    fn wave_layers() {
+        let settings = StarfieldSettings::default();
+        assert_eq!(settings.layers(0), settings.waves[0].as_slice());
+        assert_eq!(settings.layers(4), settings.waves[3].as_slice());
+        assert_eq!(settings.layers(20), settings.waves[3].as_slice()); //<- The last wave is kept.
+
+        let mut starfield = Starfield::new(settings);
+        assert_eq!(densities(&starfield), [24, 12, 6]);
+        let first = (
+            starfield.layers[0].stars[0].x,
+            starfield.layers[0].stars[0].y,
+        );
+
+        // Clearing a wave switches to the layers of the next one, and keeps the stars, which are
+        // still needed, where they are.
+        // --------------------------------------------------------------------------------------
+        starfield.on_event(&GameEvent::WaveCleared(WaveResult {
+            number: 1,
+            escaped: 0,
+        }));
+        assert_eq!(densities(&starfield), [28, 14, 7]);
+        assert_eq!(starfield.layers[2].speed, 1.3 * 6.0);
+        let star = &starfield.layers[0].stars[0];
+        assert_eq!((star.x, star.y), first);
+    }
+
+    #[test]
+    fn twinkle_phase() {
+        let star = |phase| Star {
+            x: 0,
+            y: 0.0,
+            period: 3000,
+            phase,
+        };
+
+        // Up and down once per cycle, shifted by the phase.
+        // -------------------------------------------------
+        let steps: Vec<usize> = (0..7).map(|i| star(0).twinkle(i * 500)).collect();
+        assert_eq!(steps, [0, 1, 2, 2, 1, 0, 0]);
+        assert_eq!(star(1000).twinkle(0), 2);
+        assert_eq!(star(1000).twinkle(2000), 0);
+    }
+
+    #[test]
+    fn scrolled_stars_wrap() {
+        let mut starfield = Starfield::new(StarfieldSettings {
+            enabled: true,
+            waves: vec![vec![StarLayer {
+                density: 1,
+                speed: 10.0,
+                color: Role::Star,
+            }]],
+        });
+        starfield.layers[0].stars[0].y = 58.75;
+        starfield.update(1000);
+        starfield.update(1050);
+        starfield.update(1100);
+        assert_eq!(starfield.layers[0].stars[0].y, 59.75);
+
+        // Stars leaving at the bottom come back at the top, with the part of the pixel they
+        // scrolled.
+        // ---------------------------------------------------------------------------------
+        starfield.update(1150);
+        let star = &starfield.layers[0].stars[0];
+        assert_eq!(star.y, 0.25);
+        assert!(star.x < WIDTH);
+        assert_eq!(
+            star.footprint(),
+            Some(Footprint::new(star.x as i32, 0, 1, 1, 0))
+        );
+    }
+}
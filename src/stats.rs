@@ -0,0 +1,166 @@
+//! Session statistics module.
+//!
+//! **SessionStats** subscribes to the gameplay events, and counts shots, hits, kills per enemy type,
+//! escaped enemies and combos. Together with the survived time and the FPS measurements of the
+//! **FpsCounter**, they make up the statistics of a game session.
+//!
+//! The statistics do not depend on the DOM. They can be exported as JSON string, which allows to
+//! compare balance changes across builds.
+
+use crate::entites::{EnemyType, FpsCounter};
+use crate::events::{GameEvent, Subscriber};
+use std::fmt::Write;
+
+/// Statistics of a game session.
+pub struct SessionStats {
+    start_ts: Option<u32>,
+    time_survived: u32,
+    shots_fired: u32,
+    hits: u32,
+    kills: [u32; EnemyType::VARIANTS.len()],
+    escaped: u32,
+    combo: u32,
+    longest_combo: u32,
+    fps_avg: f64,
+    fps_min: f64,
+}
+
+impl SessionStats {
+    /// Create new empty statistics.
+    pub fn new() -> Self {
+        Self {
+            start_ts: None,
+            time_survived: 0,
+            shots_fired: 0,
+            hits: 0,
+            kills: [0; EnemyType::VARIANTS.len()],
+            escaped: 0,
+            combo: 0,
+            longest_combo: 0,
+            fps_avg: 0.0,
+            fps_min: 0.0,
+        }
+    }
+
+    /// Update the statistics every game loop, while the session is running. Measures the survived
+    /// time starting at the first update, and takes over the FPS measurements of *fps_counter*.
+    pub fn update(&mut self, ts: u32, fps_counter: &FpsCounter) {
+        let start_ts = *self.start_ts.get_or_insert(ts);
+        self.time_survived = ts - start_ts;
+        self.fps_avg = fps_counter.average();
+        self.fps_min = fps_counter.min();
+    }
+
+    /// Number of fired projectiles.
+    pub fn shots_fired(&self) -> u32 {
+        self.shots_fired
+    }
+
+    /// Number of projectiles, which hit an enemy.
+    pub fn hits(&self) -> u32 {
+        self.hits
+    }
+
+    /// Ratio of hits to fired shots (0 to 1).
+    pub fn accuracy(&self) -> f64 {
+        if self.shots_fired == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.shots_fired as f64
+        }
+    }
+
+    /// Number of destroyed enemies of type *kind*.
+    pub fn kills(&self, kind: EnemyType) -> u32 {
+        self.kills[kind as usize]
+    }
+
+    /// Number of enemies, which escaped into the void.
+    pub fn escaped(&self) -> u32 {
+        self.escaped
+    }
+
+    /// Longest streak of kills without a miss.
+    pub fn longest_combo(&self) -> u32 {
+        self.longest_combo
+    }
+
+    /// Survived time in ms.
+    pub fn time_survived(&self) -> u32 {
+        self.time_survived
+    }
+
+    /// Average FPS of the session.
+    pub fn fps_avg(&self) -> f64 {
+        self.fps_avg
+    }
+
+    /// Lowest FPS of the session.
+    pub fn fps_min(&self) -> f64 {
+        self.fps_min
+    }
+
+    /// Serialize the statistics into a JSON object string.
+    pub fn to_json(&self) -> String {
+        let mut kills = String::new();
+        for (i, kind) in EnemyType::VARIANTS.iter().enumerate() {
+            if i > 0 {
+                kills.push(',');
+            }
+            write!(kills, "\"{}\":{}", kind.name(), self.kills(*kind)).unwrap();
+        }
+
+        format!(
+            concat!(
+                "{{\"shots_fired\":{},\"hits\":{},\"accuracy\":{:.4},\"kills\":{{{}}},",
+                "\"escaped\":{},\"longest_combo\":{},\"time_survived_ms\":{},",
+                "\"fps_avg\":{:.2},\"fps_min\":{:.2}}}"
+            ),
+            self.shots_fired,
+            self.hits,
+            self.accuracy(),
+            kills,
+            self.escaped,
+            self.longest_combo,
+            self.time_survived,
+            self.fps_avg,
+            self.fps_min,
+        )
+    }
+
+    /// Human readable lines of the main statistics (used by the game over screen, they fit into a line of
+    /// the virtual grid). The kills by enemy type and the FPS are only in the JSON.
+    pub fn summary_lines(&self) -> Vec<String> {
+        vec![
+            format!("Time: {:.1} s", self.time_survived as f64 / 1000.0),
+            format!("Accuracy: {:.1} %", self.accuracy() * 100.0),
+            format!("Hits: {} / {}", self.hits, self.shots_fired),
+            format!("Escaped: {}", self.escaped),
+            format!("Combo: {}", self.longest_combo),
+        ]
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Subscriber for SessionStats {
+    /// Count the events.
+    fn on_event(&mut self, event: &GameEvent) {
+        match *event {
+            GameEvent::ShotFired => self.shots_fired += 1,
+            GameEvent::EnemyKilled { kind, .. } => {
+                self.hits += 1;
+                self.kills[kind as usize] += 1;
+                self.combo += 1;
+                self.longest_combo = self.longest_combo.max(self.combo);
+            }
+            GameEvent::ProjectileMissed => self.combo = 0,
+            GameEvent::EnemyEscaped => self.escaped += 1,
+            GameEvent::PlayerHit { .. } | GameEvent::WaveCleared(_) => {}
+        }
+    }
+}
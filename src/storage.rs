@@ -0,0 +1,23 @@
+//! Persistent storage module. Thin wrapper around the DOM **localStorage** API.
+//!
+//! Storage is best effort. The browser may deny access (e.g. private browsing mode), in which case
+//! loading returns nothing, and storing is silently ignored.
+
+use web_sys::Storage;
+
+/// Get the local storage of the current window, if there is one accessible.
+fn local_storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Load the string stored under *key*.
+pub fn load(key: &str) -> Option<String> {
+    local_storage()?.get_item(key).ok()?
+}
+
+/// Store *value* under *key*.
+pub fn store(key: &str, value: &str) {
+    if let Some(s) = local_storage() {
+        let _ = s.set_item(key, value);
+    }
+}
@@ -1,11 +1,97 @@
 //! Utilities module (various stuff).
 
-use js_sys::Math;
+/// Seedable pseudo random number generator (xorshift32).
+///
+/// The simulation uses it instead of the Ecmascript Math.random() API, so it is deterministic, and a
+/// world can be recreated from its seed.
+pub struct Rng {
+    state: u32,
+}
+
+impl Rng {
+    /// Create new generator from *seed*. The generator does not work with a zero state, so the seed 0
+    /// is replaced by a fixed constant.
+    pub fn new(seed: u32) -> Self {
+        Self {
+            state: if seed == 0 { 0x9e37_79b9 } else { seed },
+        }
+    }
 
-/// Generate a random number from 0 to (including) *max*. Uses Ecmascript Math.random() API under
-/// the hood.
-pub fn rand(max: u32) -> u32 {
-    (Math::random() * (max + 1) as f64) as u32
+    /// Generate the next raw 32 bit number.
+    pub fn next_u32(&mut self) -> u32 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.state = x;
+        x
+    }
+
+    /// Generate a random number from 0 to (including) *max*.
+    pub fn rand(&mut self, max: u32) -> u32 {
+        ((self.next_u32() as u64 * (max as u64 + 1)) >> 32) as u32
+    }
+}
+
+/// Hash *data* with the 32 bit FNV-1a hash function. It is not cryptographically secure, but stable
+/// across builds and platforms.
+pub fn fnv1a(data: &[u8]) -> u32 {
+    data.iter().fold(0x811c_9dc5, |h, b| {
+        (h ^ *b as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// Base64 alphabet (standard, with padding).
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encode *data* as base64 string.
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                s.push(BASE64[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+
+    s
+}
+
+/// Decode a base64 string. Returns `None`, when *s* is not valid base64.
+pub fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.trim_end_matches('=').as_bytes();
+    let mut data = Vec::with_capacity(s.len() * 3 / 4);
+    let mut n = 0u32;
+
+    for (i, c) in s.iter().enumerate() {
+        n = n << 6 | BASE64.iter().position(|b| b == c)? as u32;
+        if i % 4 == 3 {
+            data.extend_from_slice(&[(n >> 16) as u8, (n >> 8) as u8, n as u8]);
+            n = 0;
+        }
+    }
+
+    // Handle the trailing partial group (2 or 3 chars, which encode 1 or 2 bytes).
+    // -----------------------------------------------------------------------------
+    match s.len() % 4 {
+        0 => {}
+        2 => data.push((n >> 4) as u8),
+        3 => data.extend_from_slice(&[(n >> 10) as u8, (n >> 2) as u8]),
+        _ => return None,
+    }
+
+    Some(data)
 }
 
 /// Cap a number (or anything partially orderable) between *min* and *max*.
@@ -19,6 +105,17 @@ pub fn cap<N: PartialOrd>(val: N, min: N, max: N) -> N {
     }
 }
 
+/// Position between *from* (*t* = 0) and *to* (*t* = 1). With *snap*, it is rounded to a whole number
+/// (used to interpolate virtual positions, and optionally snap them to the grid).
+pub fn lerp(from: u32, to: u32, t: f32, snap: bool) -> f64 {
+    let pos = from as f64 + (to as f64 - from as f64) * t as f64;
+    if snap {
+        pos.round()
+    } else {
+        pos
+    }
+}
+
 /// A timer used for various game mechanics. It provies a repeated mode, and a one-shot mode.
 /// The timer operates by receiving the game high res timestamp for every update.
 pub struct Timer {
@@ -52,6 +149,11 @@ impl Timer {
         }
     }
 
+    /// The timestamp of the next timer event.
+    pub fn deadline(&self) -> u32 {
+        self.future_ts
+    }
+
     /// Use this method in the game loop. Supply the current high res *ts*, and a closure to execute.
     /// The method will then only execute the callback closure, if a timer event was raised (e.g. ts reached, interval...).
     ///
@@ -0,0 +1,74 @@
+//! Wave module.
+//!
+//! Enemies are spawned in waves. A wave consists of a fixed number of enemies (given by the rules). Once all enemies of
+//! a wave were spawned, no new enemies appear, until the last one of them is either destroyed or
+//! escaped into the void. Then the wave is cleared and the next one starts.
+
+/// Wave state. Contains the number of enemies per wave, the current wave number, the number of enemies
+/// spawned in this wave, and how many of them escaped.
+pub struct Waves {
+    size: u32,
+    number: u32,
+    spawned: u32,
+    escaped: u32,
+}
+
+/// Result of a cleared wave.
+#[derive(Copy, Clone, Debug)]
+pub struct WaveResult {
+    /// Number of the cleared wave (starts at 1).
+    pub number: u32,
+    /// How many enemies of the wave escaped into the void.
+    pub escaped: u32,
+}
+
+impl Waves {
+    /// Create new wave state with *size* enemies per wave, starting at wave 1.
+    pub fn new(size: u32) -> Self {
+        Self {
+            size,
+            number: 1,
+            spawned: 0,
+            escaped: 0,
+        }
+    }
+
+    /// Number of the current wave (starts at 1).
+    pub fn number(&self) -> u32 {
+        self.number
+    }
+
+    /// Checks if the current wave still has enemies left to spawn.
+    pub fn can_spawn(&self) -> bool {
+        self.spawned < self.size
+    }
+
+    /// Notify the wave, that a new enemy was spawned.
+    pub fn on_spawn(&mut self) {
+        self.spawned += 1;
+    }
+
+    /// Notify the wave, that an enemy escaped into the void.
+    pub fn on_escape(&mut self) {
+        self.escaped += 1;
+    }
+
+    /// Check if the current wave is cleared, by supplying the number of enemies still alive. When
+    /// yes, the next wave is started and the result of the cleared one is returned.
+    pub fn check_cleared(&mut self, enemies_alive: usize) -> Option<WaveResult> {
+        if self.spawned < self.size || enemies_alive > 0 {
+            return None;
+        }
+
+        let result = WaveResult {
+            number: self.number,
+            escaped: self.escaped,
+        };
+
+        self.number += 1;
+        self.spawned = 0;
+        self.escaped = 0;
+
+        Some(result)
+    }
+}
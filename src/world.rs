@@ -0,0 +1,404 @@
+//! World module. Contains the game simulation.
+//!
+//! The **World** holds the state of all entities, which take part in the simulation (player,
+//! projectiles and enemies), and advances them depending on the supplied input and timestamp. The
+//! outcomes of the simulation are emitted as gameplay events.
+//!
+//! The simulation is deterministic. A world created with the same seed, and updated with the same
+//! sequence of timestamps and input states will always end up in the same state. This is what makes
+//! replays possible.
+
+use crate::entites::{Enemy, Player, Projectile};
+use crate::events::{Events, GameEvent, Subscriber};
+use crate::input::Input;
+use crate::rendering::{Interpolation, Layer, Scene, SceneNode};
+use crate::rules::Rules;
+use crate::utils::{Rng, Timer};
+use crate::waves::Waves;
+
+/// Game tick. All 10 ms.
+/// ---------------------
+pub const TICK: u32 = 10;
+
+/// State of the tick counters of the world: the deadline of the game tick timer, and the enemy and spawn
+/// ticks with their intervals (in game ticks).
+#[cfg(feature = "debug-overlay")]
+pub struct TickCounters {
+    pub game_tick: u32,
+    pub enemy_tick: u8,
+    pub enemy_interval: u8,
+    pub spawn_tick: u8,
+    pub spawn_interval: u8,
+}
+
+/// The simulated game world.
+pub struct World {
+    rules: Rules,
+    player: Player,
+    projectiles: Vec<Projectile>,
+    enemies: Vec<Enemy>,
+    game_tick: Timer,
+    enemy_tick: u8,
+    enemy_spawn_tick: u8,
+    waves: Waves,
+    rng: Rng,
+    events: Events,
+    fraction: f32,
+}
+
+impl World {
+    /// Create a new world with the classic rules. The *seed* initializes the random number generator
+    /// of the simulation.
+    pub fn new(seed: u32) -> Self {
+        Self::with_rules(seed, Rules::default())
+    }
+
+    /// Create a new world with *seed*, which is simulated according to *rules*.
+    pub fn with_rules(seed: u32, rules: Rules) -> Self {
+        Self {
+            rules,
+            player: Player::new(),
+            projectiles: vec![],
+            enemies: vec![],
+            game_tick: Timer::interval(0, TICK),
+            enemy_tick: 0,
+            enemy_spawn_tick: 0,
+            waves: Waves::new(rules.wave_size),
+            rng: Rng::new(seed),
+            events: Events::new(),
+            fraction: 0.0,
+        }
+    }
+
+    /// The player entity.
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    /// Number of the current wave (starts at 1).
+    pub fn wave(&self) -> u32 {
+        self.waves.number()
+    }
+
+    /// Update the world.
+    ///
+    /// This calculates the next state of the world depending on previous state, current input, and
+    /// the current timestamp. Returns the number of game ticks, which were simulated.
+    pub fn update(&mut self, ts: u32, input: &Input) -> u32 {
+        // Update the player depending on input. This needs
+        // a mutable reference to projectiles, so it can spawn new ones on shoot.
+        // ----------------------------------------------------------------------
+        self.player
+            .update(ts, input, &mut self.projectiles, &mut self.events);
+
+        // Get all the mutable references for fields from **World** into lifetimes scope.
+        // This has to be done to prevent confusing the closures lifetime below with the lifetime
+        // of the fields. I think it is related to the fact, that the closure does not get any gurantee
+        // from `game_tick.check()` regarding outliving any of the mutable resources in **World**.
+        //
+        // TODO: Maybe it is possible to annotate this update function, the World type, or the closure
+        // with lifetimes.
+        let rules = &self.rules;
+        let game_tick = &mut self.game_tick;
+        let player = &self.player;
+        let projectiles = &mut self.projectiles;
+        let enemies = &mut self.enemies;
+        let enemy_tick = &mut self.enemy_tick;
+        let enemy_spawn_tick = &mut self.enemy_spawn_tick;
+        let waves = &mut self.waves;
+        let rng = &mut self.rng;
+        let events = &mut self.events;
+
+        // This will check if the gametick is fired. When yes, it executes the given closure.
+        // The *off* parameter for the closure tells, how many ms the timer may have missed.
+        // ----------------------------------------------------------------------------------
+        let ticks = game_tick
+            .check(ts, |off| {
+                // Calculate how many ticks we missed (should be 1 for no misses almost always),
+                // and perform all of them right here in the loop body.
+                // -----------------------------------------------------------------------------
+                let ticks = (off + TICK) / TICK;
+                for _ in 0..ticks {
+                    // Check if projectile would move into the void. The *retain()* iterator is used,
+                    // to signal which projectiles survive, or die in this game tick.
+                    // ------------------------------------------------------------------------------
+                    projectiles.retain(|e| {
+                        if e.needs_removal() {
+                            events.emit(GameEvent::ProjectileMissed);
+                            false //<- Also please die.
+                        } else {
+                            true //<- Allowed to live another day.
+                        }
+                    });
+
+                    // Now actually update the projectile.
+                    // -----------------------------------
+                    for p in projectiles.iter_mut() {
+                        p.tick();
+                    }
+
+                    // Advance the walk cycle of the enemies, and when the enemy tick is here, update them all.
+                    // -----------------------------------------------------------------------------------------
+                    for e in enemies.iter_mut() {
+                        e.animate(TICK);
+                    }
+                    if *enemy_tick >= rules.enemy_interval {
+                        for e in enemies.iter_mut() {
+                            e.tick();
+                        }
+                        enemies.retain(|e| {
+                            if let Some((x, y)) = player.hit_by(e) {
+                                //<- Crashed into the player ship.
+                                events.emit(GameEvent::PlayerHit { x, y });
+                                false
+                            } else if e.needs_removal() {
+                                //<- Check if they have to die because of the void.
+                                events.emit(GameEvent::EnemyEscaped);
+                                waves.on_escape();
+                                false
+                            } else {
+                                true
+                            }
+                        });
+                        *enemy_tick = 0;
+                    } else {
+                        *enemy_tick += 1;
+                    }
+
+                    // Every enemy spawn tick, spawn new enemies (currently only one per spawn tick), as
+                    // long as the current wave has enemies left to spawn.
+                    // They are spawned along the horizontal axis on the top of the canvas.
+                    // ------------------------------------------------------------------------------
+                    if *enemy_spawn_tick >= rules.spawn_interval {
+                        if waves.can_spawn() {
+                            enemies.push(Enemy::new_random(rng));
+                            waves.on_spawn();
+                        }
+                        *enemy_spawn_tick = 0;
+                    } else {
+                        *enemy_spawn_tick += 1;
+                    }
+
+                    // Collison check and removal for every projectile by retaining iterator.
+                    // ----------------------------------------------------------------------
+                    projectiles.retain(|p| {
+                        if let Some(e) = enemies
+                            .iter()
+                            .enumerate()
+                            // Find collisiony between current projectile and all enemies.
+                            // -----------------------------------------------------------
+                            .find(|(_, e)| e.intersects_with(p.pos_x, p.pos_y))
+                            // Get only the index of the enemy.
+                            // --------------------------------
+                            .map(|(i, _)| i)
+                        {
+                            // Collision found. Remove enemy by index, emit the kill on this position, and
+                            // then mark the projectile for removal.
+                            // ----------------------------------------------------------------------------
+                            let kind = enemies.remove(e).kind();
+                            events.emit(GameEvent::EnemyKilled {
+                                x: p.pos_x,
+                                y: p.pos_y,
+                                kind,
+                            });
+                            false
+                        } else {
+                            true
+                        }
+                    });
+
+                    // Start the next wave, when all enemies of the current one are gone.
+                    // ------------------------------------------------------------------
+                    if let Some(wave) = waves.check_cleared(enemies.len()) {
+                        events.emit(GameEvent::WaveCleared(wave));
+                    }
+                }
+                ticks
+            })
+            .unwrap_or(0);
+
+        // Remember how far the next game tick is, so entities can be drawn between their last two
+        // positions.
+        // ------------------------------------------------------------------------------------------
+        let until_tick = self.game_tick.deadline().saturating_sub(ts).min(TICK);
+        self.fraction = (TICK - until_tick) as f32 / TICK as f32;
+        ticks
+    }
+
+    /// The point between the last two ticks at the last update, for drawing the projectiles and enemies
+    /// between their last two positions, so they move smoothly between the ticks, which move them
+    /// (snapped to the grid with *snap*). Projectiles move every game tick, so they are placed at the tick
+    /// fraction. Enemies only move every enemy tick, so they are placed at the fraction of the enemy
+    /// interval, which passed.
+    ///
+    /// This is only for drawing. The simulation (and the collisions) always use the current positions.
+    pub fn interpolation(&self, snap: bool) -> Interpolation {
+        let interval = self.rules.enemy_interval as f32 + 1.0;
+        Interpolation {
+            tick: self.fraction,
+            enemy: ((self.enemy_tick as f32 + self.fraction) / interval).min(1.0),
+            snap,
+        }
+    }
+
+    /// Drain the events emitted by the simulation since the last call, and hand them to
+    /// *subscribers*.
+    pub fn dispatch(&mut self, subscribers: &mut [&mut dyn Subscriber]) {
+        self.events.dispatch(subscribers);
+    }
+}
+
+/// Accessors for the debug overlay.
+#[cfg(feature = "debug-overlay")]
+impl World {
+    /// The interpolation fraction: how much of the current game tick passed at the last update (from 0,
+    /// right after a tick, to 1).
+    pub fn tick_fraction(&self) -> f32 {
+        self.fraction
+    }
+
+    /// The projectiles.
+    pub fn projectiles(&self) -> &[Projectile] {
+        &self.projectiles
+    }
+
+    /// The enemies.
+    pub fn enemies(&self) -> &[Enemy] {
+        &self.enemies
+    }
+
+    /// The state of the tick counters.
+    pub fn tick_counters(&self) -> TickCounters {
+        TickCounters {
+            game_tick: self.game_tick.deadline(),
+            enemy_tick: self.enemy_tick,
+            enemy_interval: self.rules.enemy_interval,
+            spawn_tick: self.enemy_spawn_tick,
+            spawn_interval: self.rules.spawn_interval,
+        }
+    }
+}
+
+impl SceneNode for World {
+    /// Submit all entities of the world with their PSOs.
+    fn submit<'a>(&'a self, scene: &mut Scene<'a>) {
+        for p in self.projectiles.iter() {
+            scene.submit(Layer::World, Projectile::pso(), p);
+        }
+
+        for e in self.enemies.iter() {
+            scene.submit(Layer::World, Enemy::pso(), e);
+        }
+
+        scene.submit(Layer::World, self.player.pso(), &self.player);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::{Call, Palette, RecordingBackend, Renderable};
+    use bumpalo::Bump;
+
+    /// A world with a projectile at x = 12 (shot right after the start), and an enemy at x = 10, updated
+    /// every 10 ms until *ts* (in the middle of a tick).
+    fn world_at(ts: u32) -> World {
+        let mut world = World::new(1);
+        let input = Input::from_state(false, false, false);
+        world.update(0, &input);
+        world.projectiles.push(Projectile::new(12));
+        world.enemies.push(Enemy::new(10, &mut Rng::new(1)));
+        for ts in (10..ts).step_by(10).chain(Some(ts)) {
+            world.update(ts, &input);
+        }
+        world
+    }
+
+    /// The top left corner of the rects drawn by *drawable* with *lerp*.
+    fn rects(drawable: &dyn Renderable, lerp: &Interpolation) -> Vec<(f64, f64)> {
+        let mut backend = RecordingBackend::new();
+        drawable.draw_at(&mut backend, lerp);
+        backend
+            .take()
+            .into_iter()
+            .filter_map(|c| match c {
+                Call::Rect(pos, ..) => Some(pos),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn interpolation_mid_tick() {
+        // 19 ticks, and half of the next one. The enemy moved at tick 11, and 8 ticks of its next
+        // interval (11 ticks) passed.
+        // ---------------------------------------------------------------------------------------
+        let world = world_at(195);
+        let lerp = world.interpolation(false);
+        let enemy_t = 8.5_f32 / 11.0;
+        assert_eq!((lerp.tick, lerp.enemy), (0.5, enemy_t));
+
+        let projectile = &world.projectiles[0];
+        assert_eq!(
+            (projectile.pos_y, rects(projectile, &lerp)[0]),
+            (37, (12.0, 37.5))
+        );
+
+        // The enemy moved from y = 0 to 1, so it is drawn shifted up by the rest of the interval.
+        // ---------------------------------------------------------------------------------------
+        let enemy = &world.enemies[0];
+        let shift = 1.0 - enemy_t as f64;
+        let current = rects(enemy, &Interpolation::default());
+        let between: Vec<(f64, f64)> = current.iter().map(|(x, y)| (*x, y - shift)).collect();
+        assert_eq!(rects(enemy, &lerp), between);
+    }
+
+    #[test]
+    fn interpolation_snapped() {
+        let world = world_at(195);
+        let lerp = world.interpolation(true);
+        assert_eq!(rects(&world.projectiles[0], &lerp)[0], (12.0, 38.0)); //<- 37.5 rounds up.
+        let enemy = &world.enemies[0];
+        assert_eq!(rects(enemy, &lerp), rects(enemy, &Interpolation::default()));
+
+        // Right after a tick, and before the middle of the enemy interval, both are snapped to their last
+        // positions.
+        // -----------------------------------------------------------------------------------------------
+        let world = world_at(150);
+        let lerp = world.interpolation(true);
+        assert_eq!(rects(&world.projectiles[0], &lerp)[0], (12.0, 42.0));
+        let enemy = &world.enemies[0];
+        let last: Vec<(f64, f64)> = rects(enemy, &Interpolation::default())
+            .into_iter()
+            .map(|(x, y)| (x, y - 1.0))
+            .collect();
+        assert_eq!(rects(enemy, &lerp), last);
+    }
+
+    #[test]
+    fn interpolation_at_submit() {
+        // The scene draws at the interpolation it was given, not at the one of the last update.
+        // -------------------------------------------------------------------------------------
+        let world = world_at(195);
+        let mut backend = RecordingBackend::new();
+        for snap in [false, true] {
+            let arena = Bump::new();
+            let mut scene = Scene::new(&arena);
+            scene.set_interpolation(world.interpolation(snap));
+            world.submit(&mut scene);
+            scene.draw(&mut backend, Palette::default_theme());
+        }
+        let shots: Vec<Call> = backend
+            .take()
+            .into_iter()
+            .filter(|c| matches!(c, Call::Rect((x, y), ..) if *x == 12.0 && *y > 30.0))
+            .collect();
+        assert_eq!(
+            shots,
+            [
+                Call::Rect((12.0, 37.5), 1.0, 1.0),
+                Call::Rect((12.0, 38.0), 1.0, 1.0)
+            ]
+        );
+    }
+}
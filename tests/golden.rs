@@ -0,0 +1,81 @@
+//! Golden-image tests. A recorded replay is played back headless, and selected frames are compared
+//! with the PNG screenshots in `tests/golden`.
+//!
+//! The tests run natively, so the target has to be given explicitly (the default one is wasm):
+//!
+//! ```sh
+//! cargo test --target x86_64-unknown-linux-gnu
+//! ```
+//!
+//! After an intended change of the rendering, set `UPDATE_GOLDEN=1` to rewrite the golden images. On a
+//! mismatch, the actual frame is written next to the golden one as `<name>.actual.png`.
+//!
+//! Dirty rect rendering is checked against full redraws of every frame of the replay.
+
+use rinvaders::headless::Headless;
+use rinvaders::{Damage, Framebuffer, Replay};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Play back the golden replay up to *frame*, and compare the rendered frame with the golden image.
+fn check_frame(frame: u32) {
+    let replay = Replay::decode(include_str!("golden/replay.txt").trim()).unwrap();
+    let mut headless = Headless::new(replay);
+    assert_eq!(headless.step_to(frame), frame);
+
+    let mut screen = Framebuffer::new(800, 600);
+    headless.draw(&mut screen);
+    let actual = screen.to_png();
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let golden = dir.join(format!("frame_{}.png", frame));
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&golden, &actual).unwrap();
+        return;
+    }
+
+    let expected = fs::read(&golden).unwrap();
+    if expected != actual {
+        let path = dir.join(format!("frame_{}.actual.png", frame));
+        fs::write(&path, &actual).unwrap();
+        panic!(
+            "frame {} differs from {}, see {}",
+            frame,
+            golden.display(),
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn dirty_rects_match_full_redraw() {
+    let replay = Replay::decode(include_str!("golden/replay.txt").trim()).unwrap();
+    let mut headless = Headless::new(replay);
+    let mut damage = Damage::new();
+    let mut dirty = Framebuffer::new(160, 120);
+    let mut full = Framebuffer::new(160, 120);
+
+    let mut frame = 0;
+    while headless.step() {
+        frame += 1;
+        headless.draw_damaged(&mut dirty, &mut damage);
+        headless.draw(&mut full);
+        assert!(
+            dirty.pixels() == full.pixels(),
+            "dirty rects differ from full redraw in frame {}",
+            frame
+        );
+    }
+    assert!(frame > 500);
+}
+
+#[test]
+fn frame_100() {
+    check_frame(100);
+}
+
+#[test]
+fn frame_500() {
+    check_frame(500);
+}
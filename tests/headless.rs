@@ -0,0 +1,77 @@
+//! Headless simulation tests. The golden replay is played back headless, and live games are played
+//! with scripted input. The state of the session (statistics, feedback effects, recording) is checked.
+//!
+//! Like the golden-image tests, they run natively:
+//!
+//! ```sh
+//! cargo test --target x86_64-unknown-linux-gnu
+//! ```
+
+use rinvaders::headless::Headless;
+use rinvaders::{EnemyType, Replay};
+
+/// Create a headless playback of the golden replay.
+fn golden() -> Headless {
+    Headless::new(Replay::decode(include_str!("golden/replay.txt").trim()).unwrap())
+}
+
+#[test]
+fn stats_after_replay() {
+    let mut headless = golden();
+    while headless.step() {}
+    assert_eq!(headless.frame(), 1500);
+
+    let stats = headless.stats();
+    assert_eq!(stats.shots_fired(), 215);
+    assert_eq!(stats.hits(), 3);
+    assert_eq!(stats.kills(EnemyType::Star), 0);
+    assert_eq!(stats.kills(EnemyType::Thin), 2);
+    assert_eq!(stats.kills(EnemyType::Arrow), 1);
+    assert_eq!(stats.escaped(), 2);
+    assert_eq!(stats.longest_combo(), 1);
+    assert_eq!(stats.time_survived(), 23986);
+    assert!(stats
+        .to_json()
+        .starts_with(r#"{"shots_fired":215,"hits":3,"accuracy":0.0140,"#));
+}
+
+#[test]
+fn hit_stops_not_recorded() {
+    // Play live with 16 ms frames, moving and shooting, until a few enemies are killed.
+    // ---------------------------------------------------------------------------------
+    let mut headless = Headless::live(42);
+    let kinds = [EnemyType::Star, EnemyType::Thin, EnemyType::Arrow];
+    let kills = |h: &Headless| kinds.iter().map(|k| h.stats().kills(*k)).sum::<u32>();
+    let mut ts = 0;
+    while kills(&headless) < 3 && !headless.game_over() && ts < 60_000 {
+        ts += 16;
+        let left = (ts / 2000) % 2 == 0;
+        headless.update(ts, left, !left, true);
+    }
+    for _ in 0..4 {
+        ts += 16;
+        headless.update(ts, false, false, false); //<- Let the last hit-stop pass.
+    }
+    let kills = kills(&headless);
+    assert!(kills >= 3);
+
+    // Every kill stopped the world for 50 ms (3 frames), which are neither recorded, nor part of the
+    // world timestamps.
+    // ----------------------------------------------------------------------------------------------
+    let replay = headless.replay().clone();
+    assert_eq!(replay.len() as u32, ts / 16 - kills * 3);
+    assert_eq!(replay.duration(), ts - kills * 50);
+
+    // Played back, the recording gives the same game (the FPS differ, they see the stopped frames).
+    // ---------------------------------------------------------------------------------------------
+    let mut playback = Headless::new(replay);
+    while playback.step() {}
+    let (live, played) = (headless.stats(), playback.stats());
+    assert_eq!(playback.score(), headless.score());
+    assert_eq!(played.shots_fired(), live.shots_fired());
+    assert_eq!(played.hits(), live.hits());
+    for kind in kinds.iter() {
+        assert_eq!(played.kills(*kind), live.kills(*kind));
+    }
+    assert_eq!(played.time_survived(), live.time_survived());
+}